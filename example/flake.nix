@@ -36,6 +36,7 @@
             };
             chainId = 84532;
             hyperstackapikey = "";
+            rootsigner = "0x3e166454c7781d3fD4ceaB18055cad87136970Ea";
           };
         }
       ];
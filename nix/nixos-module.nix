@@ -41,26 +41,97 @@ in
       };
 
       claimerkey = lib.mkOption {
-        type = lib.types.str;
+        type = lib.types.nullOr lib.types.str;
+        default = null;
         example = "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80";
         description = ''
-          The private key of the claim signer.
+          The private key of the claim signer. Not needed when claims are published as Merkle proofs.
         '';
       };
 
       tokenownerkey = lib.mkOption {
-        type = lib.types.str;
+        type = lib.types.nullOr lib.types.str;
+        default = null;
         example = "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80";
         description = ''
-          The private key of the tokenized server owner.
+          The private key of the tokenized server owner. Ignored when tokenownersigner is set.
         '';
       };
 
       tokenminterkey = lib.mkOption {
-        type = lib.types.str;
+        type = lib.types.nullOr lib.types.str;
+        default = null;
         example = "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80";
         description = ''
-          The private key of the tokenized server minter.
+          The private key of the tokenized server minter. Ignored when tokenmintersigner is set.
+        '';
+      };
+
+      claimersigner = lib.mkOption {
+        type = lib.types.nullOr lib.types.attrs;
+        default = null;
+        example = {
+          type = "keystore";
+          path = "/run/secrets/claimer.json";
+          password_file = "/run/secrets/claimer-password";
+        };
+        description = ''
+          Signer of claims instead of claimerkey: a keystore (path, password_file), a Web3Signer compatible remote (url, address) or a local key.
+        '';
+      };
+
+      tokenownersigner = lib.mkOption {
+        type = lib.types.nullOr lib.types.attrs;
+        default = null;
+        example = {
+          type = "remote";
+          url = "http://localhost:9000";
+          address = "0x3e166454c7781d3fD4ceaB18055cad87136970Ea";
+        };
+        description = ''
+          Signer of the tokenized server owner instead of tokenownerkey.
+        '';
+      };
+
+      tokenmintersigner = lib.mkOption {
+        type = lib.types.nullOr lib.types.attrs;
+        default = null;
+        example = {
+          type = "keystore";
+          path = "/run/secrets/minter.json";
+          password_file = "/run/secrets/minter-password";
+        };
+        description = ''
+          Signer of the tokenized server minter instead of tokenminterkey.
+        '';
+      };
+
+      rootsigner = lib.mkOption {
+        type = lib.types.str;
+        example = "0x3e166454c7781d3fD4ceaB18055cad87136970Ea";
+        description = ''
+          Address that authorizes and revokes admin signers. Once a role has signers in the database, its signer option below is no longer accepted.
+        '';
+      };
+
+      adminthresholds = lib.mkOption {
+        type = lib.types.attrsOf lib.types.ints.positive;
+        default = { };
+        example = {
+          manual_tokens = 2;
+          promo_code = 2;
+        };
+        description = ''
+          Approvals of active signers required before a manual tokens or promo code upload is applied. Roles above 1 only accept uploads through proposals.
+        '';
+      };
+
+      adminproposalexpiry = lib.mkOption {
+        type = lib.types.ints.positive;
+        default = 604800;
+        example = 86400;
+        description = ''
+          How many seconds an admin proposal can collect approvals.
         '';
       };
 
@@ -82,6 +153,24 @@ in
         '';
       };
 
+      jobsigner = lib.mkOption {
+        type = lib.types.str;
+        default = "0x3e166454c7781d3fD4ceaB18055cad87136970Ea";
+        example = "0x3e166454c7781d3fD4ceaB18055cad87136970Ea";
+        description = ''
+          Address that signs manual job runs.
+        '';
+      };
+
+      denylistsigner = lib.mkOption {
+        type = lib.types.str;
+        default = "0x3e166454c7781d3fD4ceaB18055cad87136970Ea";
+        example = "0x3e166454c7781d3fD4ceaB18055cad87136970Ea";
+        description = ''
+          Address that signs denylist changes.
+        '';
+      };
+
       agreementsigner = lib.mkOption {
         type = lib.types.str;
         default = "0x3e166454c7781d3fD4ceaB18055cad87136970Ea";
@@ -109,6 +198,15 @@ in
         '';
       };
 
+      dnsresolver = lib.mkOption {
+        type = lib.types.str;
+        default = "https://cloudflare-dns.com/dns-query";
+        example = "https://dns.google/resolve";
+        description = ''
+          DNS over HTTPS (JSON API) resolver to verify custom domain challenges.
+        '';
+      };
+
       rpc = {
         http = lib.mkOption {
           type = lib.types.str;
@@ -205,6 +303,257 @@ in
         '';
       };
 
+      ownaiv1tiers = lib.mkOption {
+        type = lib.types.nullOr (lib.types.listOf lib.types.attrs);
+        default = null;
+        example = [
+          {
+            id = "rtx-a6000";
+            name = "RTX A6000";
+            flavor = "n3-RTX-A6000x1";
+            regions = [ "NORWAY-1" ];
+            price = 200000000;
+            stock = "Provider";
+          }
+        ];
+        description = ''
+          OwnAIV1 hardware tiers with their monthly price in 6 decimals (first is the default). Defaults to a single RTX A4000 tier at ownaiv1price.
+        '';
+      };
+
+      ownaiv1presets = lib.mkOption {
+        type = lib.types.nullOr (lib.types.listOf lib.types.attrs);
+        default = null;
+        example = [
+          {
+            id = "inference-api";
+            name = "Inference API";
+            description = "Ollama API serving a local AI model.";
+            forward = "http://127.0.0.1:11434";
+            config = "services.ollama = { enable = true; acceleration = \"cuda\"; loadModels = [ \"{{model}}\" ]; };";
+            parameters = [
+              {
+                name = "model";
+                description = "Ollama model to serve.";
+                default = "llama3.1:8b";
+              }
+            ];
+          }
+        ];
+        description = ''
+          OwnAIV1 app presets selectable at mint (first is the default). {{domain}} and {{parameter}} are substituted in forward and config. Defaults to AI chat and inference API presets.
+        '';
+      };
+
+      stakingrates = lib.mkOption {
+        type = lib.types.listOf lib.types.attrs;
+        default = [ ];
+        example = [
+          {
+            version = "2026-11";
+            collection = "ownaiv1";
+            effective = 1761955200;
+            emission = 1000000000;
+            tiers = {
+              rtx-a6000 = 2.0;
+            };
+            holding = [
+              {
+                days = 30;
+                multiplier = 1.1;
+              }
+              {
+                days = 90;
+                multiplier = 1.25;
+              }
+            ];
+            uptime = true;
+          }
+        ];
+        description = ''
+          Versioned daily staking reward rates per collection, the latest effective version applies. The emission (6 decimals) is split over active servers weighted by tier, holding duration and uptime multipliers.
+        '';
+      };
+
+      autorenewbefore = lib.mkOption {
+        type = lib.types.ints.unsigned;
+        default = 86400;
+        example = 259200;
+        description = ''
+          How many seconds before expiry auto renewing servers should be extended.
+        '';
+      };
+
+      deployment = {
+        providers = lib.mkOption {
+          type = lib.types.listOf lib.types.attrs;
+          default = [
+            {
+              provider = "Hyperstack";
+              regions = [
+                {
+                  name = "NORWAY-1";
+                  environment = "default-NORWAY-1";
+                }
+              ];
+              flavors = [
+                {
+                  name = "n3-RTX-A4000x1";
+                  model = "RTX-A4000";
+                  cost = 0;
+                }
+              ];
+            }
+          ];
+          example = [
+            {
+              provider = {
+                Mock = {
+                  available = 10;
+                };
+              };
+              regions = [
+                {
+                  name = "LOCAL";
+                  environment = "local";
+                }
+              ];
+              flavors = [
+                {
+                  name = "mock";
+                  model = "mock";
+                  cost = 0;
+                }
+              ];
+            }
+          ];
+          description = ''
+            Deployment providers with their regions and flavors to deploy tokenized servers on.
+          '';
+        };
+
+        policy = lib.mkOption {
+          type = lib.types.str;
+          default = "failover";
+          example = "region:CANADA-1,NORWAY-1";
+          description = ''
+            How to pick a provider: cheapest, failover (configured order) or region:<preferred regions>.
+          '';
+        };
+      };
+
+      reconcile = {
+        orphanCleanup = lib.mkOption {
+          type = lib.types.bool;
+          default = false;
+          example = true;
+          description = ''
+            Whether to delete provider virtual machines not referenced by any tokenized server.
+          '';
+        };
+
+        orphanAge = lib.mkOption {
+          type = lib.types.ints.unsigned;
+          default = 3600;
+          example = 86400;
+          description = ''
+            How many seconds a virtual machine should be detected as orphaned before it is deleted.
+          '';
+        };
+      };
+
+      autoheal = {
+        enable = lib.mkOption {
+          type = lib.types.bool;
+          default = false;
+          example = true;
+          description = ''
+            Whether to automatically redeploy servers that are terminated or have been unreachable for too long.
+          '';
+        };
+
+        after = lib.mkOption {
+          type = lib.types.ints.positive;
+          default = 1800;
+          example = 3600;
+          description = ''
+            How many seconds a server should fail its health checks before it is redeployed.
+          '';
+        };
+      };
+
+      signaturewindow = lib.mkOption {
+        type = lib.types.ints.positive;
+        default = 600;
+        example = 300;
+        description = ''
+          How many seconds after signing a signed request is still accepted. Every signature can only be used once.
+        '';
+      };
+
+      signaturedomain = lib.mkOption {
+        type = lib.types.str;
+        default = "OpenxAI";
+        example = "OpenxAI Staging";
+        description = ''
+          EIP-712 domain name user actions are signed for, together with the chain id.
+        '';
+      };
+
+      legacysignaturesuntil = lib.mkOption {
+        type = lib.types.nullOr lib.types.int;
+        default = null;
+        example = 1798761600;
+        description = ''
+          Unix timestamp until which plain text signatures of user actions are still accepted next to EIP-712 typed data. Null keeps accepting them.
+        '';
+      };
+
+      claimmerkle = lib.mkOption {
+        type = lib.types.bool;
+        default = false;
+        example = true;
+        description = ''
+          Whether to publish claims as Merkle proofs instead of signing them, so the claimer key is not needed.
+        '';
+      };
+
+      claimdeadline = lib.mkOption {
+        type = lib.types.ints.positive;
+        default = 3600;
+        example = 600;
+        description = ''
+          How many seconds a claim signature with deadline stays valid.
+        '';
+      };
+
+      poweractioncooldown = lib.mkOption {
+        type = lib.types.ints.unsigned;
+        default = 300;
+        example = 60;
+        description = ''
+          How many seconds an owner has to wait between power actions (reboot, stop, start, reinstall) on a server.
+        '';
+      };
+
+      healthalertfailures = lib.mkOption {
+        type = lib.types.ints.positive;
+        default = 3;
+        example = 5;
+        description = ''
+          After how many consecutive failed health checks (1 per minute) the server owner gets alerted.
+        '';
+      };
+
+      stockcachettl = lib.mkOption {
+        type = lib.types.ints.positive;
+        default = 30;
+        example = 60;
+        description = ''
+          How many seconds fetched provider stock stays cached before it is refreshed.
+        '';
+      };
+
       hyperstackapikey = lib.mkOption {
         type = lib.types.str;
         example = "7a12411b-0074-4d01-a375-ca91376f0bb8";
@@ -237,18 +586,39 @@ in
         "network.target"
         "postgresql.target"
       ];
-      environment = {
-        HOSTNAME = cfg.hostname;
-        PORT = toString cfg.port;
-        RUST_LOG = cfg.verbosity;
+      environment = lib.optionalAttrs (cfg.ownaiv1tiers != null) {
+        OWNAIV1TIERS = builtins.toJSON cfg.ownaiv1tiers;
+      } // lib.optionalAttrs (cfg.ownaiv1presets != null) {
+        OWNAIV1PRESETS = builtins.toJSON cfg.ownaiv1presets;
+      } // lib.optionalAttrs (cfg.legacysignaturesuntil != null) {
+        LEGACYSIGNATURESUNTIL = toString cfg.legacysignaturesuntil;
+      } // lib.optionalAttrs (cfg.claimerkey != null) {
         CLAIMERKEY = cfg.claimerkey;
+      } // lib.optionalAttrs (cfg.claimersigner != null) {
+        CLAIMERSIGNER = builtins.toJSON cfg.claimersigner;
+      } // lib.optionalAttrs (cfg.tokenownerkey != null) {
         TOKENOWNERKEY = cfg.tokenownerkey;
+      } // lib.optionalAttrs (cfg.tokenownersigner != null) {
+        TOKENOWNERSIGNER = builtins.toJSON cfg.tokenownersigner;
+      } // lib.optionalAttrs (cfg.tokenminterkey != null) {
         TOKENMINTERKEY = cfg.tokenminterkey;
+      } // lib.optionalAttrs (cfg.tokenmintersigner != null) {
+        TOKENMINTERSIGNER = builtins.toJSON cfg.tokenmintersigner;
+      } // {
+        HOSTNAME = cfg.hostname;
+        PORT = toString cfg.port;
+        RUST_LOG = cfg.verbosity;
+        ROOTSIGNER = cfg.rootsigner;
+        ADMINTHRESHOLDS = builtins.toJSON cfg.adminthresholds;
+        ADMINPROPOSALEXPIRY = toString cfg.adminproposalexpiry;
         MANUALTOKENSIGNER = cfg.manualtokensigner;
         PROMOCODESIGNER = cfg.promocodesigner;
+        JOBSIGNER = cfg.jobsigner;
+        DENYLISTSIGNER = cfg.denylistsigner;
         AGREEMENTSIGNER = cfg.agreementsigner;
         DATABASE = cfg.database;
         SUBDOMAINDISTRIBUTOR = cfg.subdomaindistributor;
+        DNSRESOLVER = cfg.dnsresolver;
         HTTPRPC = cfg.rpc.http;
         WSRPC = cfg.rpc.ws;
         CHAINID = toString cfg.chainId;
@@ -258,6 +628,21 @@ in
         DEPOSIT = cfg.contracts.deposit;
         USDC = cfg.contracts.usdc;
         OWNAIV1PRICE = toString cfg.ownaiv1price;
+        AUTORENEWBEFORE = toString cfg.autorenewbefore;
+        DEPLOYMENTPROVIDERS = builtins.toJSON cfg.deployment.providers;
+        STAKINGRATES = builtins.toJSON cfg.stakingrates;
+        DEPLOYMENTPOLICY = cfg.deployment.policy;
+        STOCKCACHETTL = toString cfg.stockcachettl;
+        HEALTHALERTFAILURES = toString cfg.healthalertfailures;
+        AUTOHEAL = lib.boolToString cfg.autoheal.enable;
+        AUTOHEALAFTER = toString cfg.autoheal.after;
+        SIGNATUREWINDOW = toString cfg.signaturewindow;
+        SIGNATUREDOMAIN = cfg.signaturedomain;
+        CLAIMMERKLE = lib.boolToString cfg.claimmerkle;
+        CLAIMDEADLINE = toString cfg.claimdeadline;
+        POWERACTIONCOOLDOWN = toString cfg.poweractioncooldown;
+        RECONCILEORPHANCLEANUP = lib.boolToString cfg.reconcile.orphanCleanup;
+        RECONCILEORPHANAGE = toString cfg.reconcile.orphanAge;
         HYPERSTACKAPIKEY = cfg.hyperstackapikey;
       };
       serviceConfig = {
@@ -0,0 +1,240 @@
+use std::str::FromStr;
+
+use actix_web::{HttpResponse, Responder, get, post, web};
+use alloy::{primitives::keccak256, providers::DynProvider};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    database::{
+        Database,
+        admin_proposal::{DatabaseAdminProposal, DatabaseAdminProposalApproval, ProposalStatus},
+    },
+    utils::{
+        admin_signer::{AdminRole, active_admin_signers, validate_admin_signature},
+        env::adminproposalexpiry,
+        proposal::{ProposalPayload, apply_if_approved, proposal_threshold},
+        replay::Freshness,
+        time::get_time_i64,
+    },
+};
+
+#[get("/admin_proposals")]
+async fn get_proposals(database: web::Data<Database>) -> impl Responder {
+    if let Err(e) = DatabaseAdminProposal::expire(&database, get_time_i64()).await {
+        log::error!("COULD NOT EXPIRE ADMIN PROPOSALS: {e}");
+    }
+
+    match DatabaseAdminProposal::get_all(&database).await {
+        Ok(proposals) => HttpResponse::Ok().json(proposals),
+        Err(e) => {
+            log::error!("Fetching admin proposals: {e}");
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct AdminProposal {
+    pub proposal: DatabaseAdminProposal,
+    pub approvals: Vec<DatabaseAdminProposalApproval>,
+}
+#[get("/admin_proposals/{id}")]
+async fn get_proposal(database: web::Data<Database>, path: web::Path<i32>) -> impl Responder {
+    let id = path.into_inner();
+    if let Err(e) = DatabaseAdminProposal::expire(&database, get_time_i64()).await {
+        log::error!("COULD NOT EXPIRE ADMIN PROPOSALS: {e}");
+    }
+
+    let proposal = match DatabaseAdminProposal::get_by_id(&database, id).await {
+        Ok(proposal) => match proposal {
+            Some(proposal) => proposal,
+            None => {
+                return HttpResponse::NotFound().finish();
+            }
+        },
+        Err(e) => {
+            log::error!("Fetching admin proposal {id}: {e}");
+            return HttpResponse::InternalServerError().finish();
+        }
+    };
+    match DatabaseAdminProposalApproval::get_all_by_proposal_id(&database, id).await {
+        Ok(approvals) => HttpResponse::Ok().json(AdminProposal {
+            proposal,
+            approvals,
+        }),
+        Err(e) => {
+            log::error!("Fetching approvals of admin proposal {id}: {e}");
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct AdminProposalCreate {
+    pub role: String,
+    pub payload: String,
+    /// Signature over the payload, counts as the first approval
+    pub signature: String,
+    #[serde(flatten)]
+    pub freshness: Freshness,
+}
+#[post("/admin_proposals/create")]
+async fn post_create(
+    database: web::Data<Database>,
+    provider: web::Data<DynProvider>,
+    data: web::Json<AdminProposalCreate>,
+) -> impl Responder {
+    let role = match AdminRole::from_str(&data.role) {
+        Ok(role) => role,
+        Err(_e) => {
+            return HttpResponse::BadRequest().finish();
+        }
+    };
+    if ProposalPayload::parse(role, &data.payload).is_none() {
+        return HttpResponse::BadRequest().finish();
+    }
+
+    // A threshold above the number of signers could never be reached
+    let threshold = proposal_threshold(role);
+    match active_admin_signers(&database, role).await {
+        Ok(signers) if (signers.len() as i32) < threshold => {
+            return HttpResponse::BadRequest().finish();
+        }
+        Ok(_) => (),
+        Err(e) => {
+            log::error!("Fetching {role} signers: {e}");
+            return HttpResponse::InternalServerError().finish();
+        }
+    }
+
+    let Some(proposer) = validate_admin_signature(
+        provider.get_ref(),
+        &database,
+        role,
+        &data.payload,
+        &data.signature,
+        &data.freshness,
+    )
+    .await
+    else {
+        return HttpResponse::Unauthorized().finish();
+    };
+
+    let now = get_time_i64();
+    let mut proposal = DatabaseAdminProposal {
+        id: 0,
+        role: role.to_string(),
+        payload: data.payload.clone(),
+        proposer: proposer.clone(),
+        threshold,
+        status: ProposalStatus::Pending.to_string(),
+        error: None,
+        created_at: now,
+        expires: now + adminproposalexpiry(),
+        applied_at: None,
+    };
+    proposal.id = match proposal.insert(&database).await {
+        Ok(id) => id,
+        Err(e) => {
+            log::error!("COULD NOT INSERT ADMIN PROPOSAL {proposal:?}: {e}");
+            return HttpResponse::InternalServerError().finish();
+        }
+    };
+
+    let approval = DatabaseAdminProposalApproval {
+        proposal_id: proposal.id,
+        signer: proposer,
+        signature: data.signature.clone(),
+        created_at: now,
+    };
+    match approval.insert(&database).await {
+        Ok(true) => (),
+        Ok(false) => {
+            // Already approved by this signer
+            return HttpResponse::Conflict().finish();
+        }
+        Err(e) => {
+            log::error!("COULD NOT INSERT ADMIN PROPOSAL APPROVAL {approval:?}: {e}");
+            return HttpResponse::InternalServerError().finish();
+        }
+    }
+    if let Err(e) = apply_if_approved(&database, &mut proposal).await {
+        log::error!("COULD NOT APPLY ADMIN PROPOSAL {proposal:?}: {e}");
+        return HttpResponse::InternalServerError().finish();
+    }
+
+    HttpResponse::Ok().json(proposal)
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct AdminProposalApprove {
+    pub signature: String,
+    #[serde(flatten)]
+    pub freshness: Freshness,
+}
+#[post("/admin_proposals/{id}/approve")]
+async fn post_approve(
+    database: web::Data<Database>,
+    provider: web::Data<DynProvider>,
+    path: web::Path<i32>,
+    data: web::Json<AdminProposalApprove>,
+) -> impl Responder {
+    let id = path.into_inner();
+    let now = get_time_i64();
+    if let Err(e) = DatabaseAdminProposal::expire(&database, now).await {
+        log::error!("COULD NOT EXPIRE ADMIN PROPOSALS: {e}");
+        return HttpResponse::InternalServerError().finish();
+    }
+
+    let mut proposal = match DatabaseAdminProposal::get_by_id(&database, id).await {
+        Ok(proposal) => match proposal {
+            Some(proposal) => proposal,
+            None => {
+                return HttpResponse::NotFound().finish();
+            }
+        },
+        Err(_e) => {
+            return HttpResponse::BadRequest().finish();
+        }
+    };
+    if proposal.status != ProposalStatus::Pending.to_string() {
+        return HttpResponse::Conflict().finish();
+    }
+    let Ok(role) = AdminRole::from_str(&proposal.role) else {
+        return HttpResponse::BadRequest().finish();
+    };
+
+    let message = format!(
+        "Approve {role} proposal {id}: {hash}",
+        hash = keccak256(&proposal.payload)
+    );
+    let Some(signer) = validate_admin_signature(
+        provider.get_ref(),
+        &database,
+        role,
+        &message,
+        &data.signature,
+        &data.freshness,
+    )
+    .await
+    else {
+        return HttpResponse::Unauthorized().finish();
+    };
+
+    let approval = DatabaseAdminProposalApproval {
+        proposal_id: id,
+        signer,
+        signature: data.signature.clone(),
+        created_at: now,
+    };
+    if let Err(e) = approval.insert(&database).await {
+        log::error!("COULD NOT INSERT ADMIN PROPOSAL APPROVAL {approval:?}: {e}");
+        return HttpResponse::InternalServerError().finish();
+    }
+    if let Err(e) = apply_if_approved(&database, &mut proposal).await {
+        log::error!("COULD NOT APPLY ADMIN PROPOSAL {proposal:?}: {e}");
+        return HttpResponse::InternalServerError().finish();
+    }
+
+    HttpResponse::Ok().json(proposal)
+}
@@ -0,0 +1,154 @@
+use std::str::FromStr;
+
+use actix_web::{HttpResponse, Responder, get, post, web};
+use alloy::{primitives::Address, providers::DynProvider};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    database::{Database, admin_signer::DatabaseAdminSigner},
+    utils::{
+        admin_signer::AdminRole,
+        env::rootsigner,
+        replay::{Freshness, validate_fresh_signature},
+        time::get_time_i64,
+    },
+};
+
+#[get("/admin_signers")]
+async fn get_admin_signers(database: web::Data<Database>) -> impl Responder {
+    match DatabaseAdminSigner::get_all(&database).await {
+        Ok(signers) => HttpResponse::Ok().json(signers),
+        Err(e) => {
+            log::error!("Fetching admin signers: {e}");
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct AdminSignerAdd {
+    pub role: String,
+    pub signer: String,
+    pub valid_from: i64,
+    pub valid_until: Option<i64>,
+    pub root_signature: String,
+    #[serde(flatten)]
+    pub freshness: Freshness,
+}
+#[post("/admin_signers/add")]
+async fn post_add(
+    database: web::Data<Database>,
+    provider: web::Data<DynProvider>,
+    data: web::Json<AdminSignerAdd>,
+) -> impl Responder {
+    let role = match AdminRole::from_str(&data.role) {
+        Ok(role) => role,
+        Err(_e) => {
+            return HttpResponse::BadRequest().finish();
+        }
+    };
+    // Signers are keyed by their checksummed address, so one address never gets two entries
+    let Ok(signer) = Address::parse_checksummed(&data.signer, None) else {
+        return HttpResponse::BadRequest().finish();
+    };
+    if data
+        .valid_until
+        .is_some_and(|valid_until| valid_until <= data.valid_from)
+    {
+        return HttpResponse::BadRequest().finish();
+    }
+
+    let root = rootsigner();
+    let message = format!(
+        "Authorize {signer} as {role} signer from {valid_from} until {valid_until}",
+        valid_from = data.valid_from,
+        valid_until = data
+            .valid_until
+            .map(|valid_until| valid_until.to_string())
+            .unwrap_or("never".to_string())
+    );
+    if !validate_fresh_signature(
+        provider.get_ref(),
+        &database,
+        &root,
+        &message,
+        &data.root_signature,
+        &data.freshness,
+    )
+    .await
+    {
+        return HttpResponse::Unauthorized().finish();
+    }
+
+    let signer = DatabaseAdminSigner {
+        role: role.to_string(),
+        signer: signer.to_string(),
+        valid_from: data.valid_from,
+        valid_until: data.valid_until,
+        revoked_at: None,
+        added_by: root,
+        created_at: get_time_i64(),
+    };
+    if let Err(e) = signer.upsert(&database).await {
+        log::error!("COULD NOT INSERT ADMIN SIGNER {signer:?}: {e}");
+        return HttpResponse::InternalServerError().finish();
+    }
+
+    HttpResponse::Ok().finish()
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct AdminSignerRevoke {
+    pub role: String,
+    pub signer: String,
+    pub root_signature: String,
+    #[serde(flatten)]
+    pub freshness: Freshness,
+}
+#[post("/admin_signers/revoke")]
+async fn post_revoke(
+    database: web::Data<Database>,
+    provider: web::Data<DynProvider>,
+    data: web::Json<AdminSignerRevoke>,
+) -> impl Responder {
+    let role = match AdminRole::from_str(&data.role) {
+        Ok(role) => role,
+        Err(_e) => {
+            return HttpResponse::BadRequest().finish();
+        }
+    };
+
+    let Ok(signer) = Address::parse_checksummed(&data.signer, None) else {
+        return HttpResponse::BadRequest().finish();
+    };
+
+    let message = format!("Revoke {signer} as {role} signer");
+    if !validate_fresh_signature(
+        provider.get_ref(),
+        &database,
+        &rootsigner(),
+        &message,
+        &data.root_signature,
+        &data.freshness,
+    )
+    .await
+    {
+        return HttpResponse::Unauthorized().finish();
+    }
+
+    match DatabaseAdminSigner::revoke(
+        &database,
+        &role.to_string(),
+        &signer.to_string(),
+        get_time_i64(),
+    )
+    .await
+    {
+        Ok(true) => HttpResponse::Ok().finish(),
+        Ok(false) => HttpResponse::NotFound().finish(),
+        Err(e) => {
+            log::error!("COULD NOT REVOKE {role} SIGNER {signer}: {e}");
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
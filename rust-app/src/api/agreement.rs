@@ -4,7 +4,12 @@ use serde::{Deserialize, Serialize};
 
 use crate::{
     database::{Database, agreement::DatabaseAgreement},
-    utils::{env::agreementsigner, signature_validator::validate_signature, time::get_time_i64},
+    utils::{
+        admin_signer::{AdminRole, validate_admin_signature},
+        replay::{Freshness, validate_fresh_typed_signature},
+        time::get_time_i64,
+        typed_data::SignAgreement,
+    },
 };
 
 #[get("/agreement/list")]
@@ -39,6 +44,8 @@ pub struct CreateAgreement {
     pub title: String,
     pub description: String,
     pub signature: String,
+    #[serde(flatten)]
+    pub freshness: Freshness,
 }
 #[post("/agreement/create")]
 async fn create(
@@ -53,13 +60,16 @@ async fn create(
         for_account = data.for_account
     ));
     let message = format!("Create agreement {hash}");
-    if !validate_signature(
+    if validate_admin_signature(
         provider.get_ref(),
-        &agreementsigner(),
+        &database,
+        AdminRole::Agreement,
         &message,
         &data.signature,
+        &data.freshness,
     )
     .await
+    .is_none()
     {
         return HttpResponse::Unauthorized().finish();
     }
@@ -87,6 +97,8 @@ pub struct AgreementSignature {
     pub agreement: i32,
     pub signature: String,
     pub signed_at: i64,
+    #[serde(flatten)]
+    pub freshness: Freshness,
 }
 #[post("/agreement/sign")]
 async fn sign(
@@ -116,11 +128,20 @@ async fn sign(
         "I agree to {hash} at {signed_at}",
         signed_at = data.signed_at
     );
-    if !validate_signature(
+    let typed = SignAgreement {
+        agreement: hash,
+        signedAt: data.signed_at,
+        nonce: data.freshness.nonce.clone(),
+        timestamp: data.freshness.timestamp,
+    };
+    if !validate_fresh_typed_signature(
         provider.get_ref(),
+        &database,
         &agreement.for_account,
-        &message,
+        &typed,
+        Some(&message),
         &data.signature,
+        &data.freshness,
     )
     .await
     {
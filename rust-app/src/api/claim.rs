@@ -1,10 +1,27 @@
-use actix_web::{HttpResponse, Responder, get, post, web};
-use alloy::primitives::{Address, U256};
+use actix_web::{HttpRequest, HttpResponse, Responder, get, post, web};
+use alloy::{
+    primitives::{Address, U256},
+    signers::Signature,
+};
+use serde::{Deserialize, Serialize};
 
 use crate::{
-    blockchain::claimer::Claim,
-    database::{Database, claim::DatabaseClaim},
-    utils::{decimals::to_18_decimals, wallet::get_claimer_signature},
+    blockchain::claimer::{Claim, ClaimWithDeadline},
+    database::{
+        Database,
+        claim::DatabaseClaim,
+        claim_merkle::{DatabaseClaimMerkleLeaf, DatabaseClaimMerkleRoot},
+        claim_signature::DatabaseClaimSignature,
+    },
+    utils::{
+        claim::{get_account_claim_summary, get_claim_summary},
+        decimals::to_18_decimals,
+        denylist::{DenylistScope, is_denied},
+        env::{claimdeadline, claimmerkle},
+        merkle::{ClaimMerkleLeaf, get_claim_merkle_export},
+        time::get_time_i64,
+        wallet::get_claimer_signature,
+    },
 };
 
 #[get("/{account}/claim")]
@@ -35,34 +52,110 @@ async fn get_claim_total(database: web::Data<Database>, path: web::Path<String>)
     }
 }
 
-#[post("/{account}/claim")]
-async fn post_claim(database: web::Data<Database>, path: web::Path<String>) -> impl Responder {
+#[get("/{account}/claim_summary")]
+async fn get_account_summary(
+    database: web::Data<Database>,
+    path: web::Path<String>,
+) -> impl Responder {
     let account = path.into_inner();
-    if account == "0x0FF350487269Fda1aE176620D42c8ab9958493E2"
-        || account == "0xE87C55363A51845352a9eD15521ab6AB6AA33Dc4"
-        || account == "0x03f0c4A0652B2E02Ab476D647E34F1F4CfbFA724"
-        || account == "0xd438C6D4a1450b55284847f06E0ed291fb053238"
-    {
-        return HttpResponse::BadRequest().finish();
+    match get_account_claim_summary(&database, &account).await {
+        Ok(summary) => HttpResponse::Ok().json(summary),
+        Err(e) => {
+            log::error!("Fetching claim summary for {account}: {e}");
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
+
+#[get("/claim/summary")]
+async fn get_summary(database: web::Data<Database>) -> impl Responder {
+    match get_claim_summary(&database).await {
+        Ok(summary) => HttpResponse::Ok().json(summary),
+        Err(e) => {
+            log::error!("Fetching claim summary: {e}");
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
+
+/// Claimer address and total (6 decimals) the account may receive a signature for.
+async fn claim_total(database: &Database, account: &str) -> Result<(Address, i64), HttpResponse> {
+    if claimmerkle() {
+        // Claims go through the Merkle distributor, use the claim proof instead
+        return Err(HttpResponse::NotFound().finish());
     }
-    let claimer = match Address::parse_checksummed(&account, None) {
+    match is_denied(database, account, DenylistScope::Claim).await {
+        Ok(false) => (),
+        Ok(true) => {
+            return Err(HttpResponse::BadRequest().finish());
+        }
+        Err(e) => {
+            log::error!("Fetching claim denylist for {account}: {e}");
+            return Err(HttpResponse::InternalServerError().finish());
+        }
+    }
+    let claimer = match Address::parse_checksummed(account, None) {
         Ok(claimer) => claimer,
         Err(_e) => {
-            return HttpResponse::BadRequest().finish();
+            return Err(HttpResponse::BadRequest().finish());
         }
     };
 
-    let total = match DatabaseClaim::get_total_amount_by_account(&database, &account).await {
-        Ok(total) => total
-            .map(|total| to_18_decimals(U256::from(total)))
-            .unwrap_or(U256::from(0)),
+    match DatabaseClaim::get_total_amount_by_account(database, account).await {
+        Ok(total) => Ok((claimer, total.unwrap_or(0))),
         Err(e) => {
             log::error!("Fetching claim for {account}: {e}");
-            return HttpResponse::InternalServerError().finish();
+            Err(HttpResponse::InternalServerError().finish())
+        }
+    }
+}
+
+/// Issued signatures are recorded before they are handed out.
+async fn record_claim_signature(
+    database: &Database,
+    request: &HttpRequest,
+    account: String,
+    total: i64,
+    deadline: Option<i64>,
+    signature: &Signature,
+) -> Result<(), HttpResponse> {
+    let claim_signature = DatabaseClaimSignature {
+        account,
+        total,
+        deadline,
+        signature: signature.to_string(),
+        requester_ip: request
+            .connection_info()
+            .realip_remote_addr()
+            .map(str::to_string),
+        date: get_time_i64(),
+    };
+    if let Err(e) = claim_signature.insert(database).await {
+        log::error!("COULD NOT INSERT CLAIM SIGNATURE {claim_signature:?}: {e}");
+        return Err(HttpResponse::InternalServerError().finish());
+    }
+
+    Ok(())
+}
+
+#[post("/{account}/claim")]
+async fn post_claim(
+    database: web::Data<Database>,
+    request: HttpRequest,
+    path: web::Path<String>,
+) -> impl Responder {
+    let account = path.into_inner();
+    let (claimer, total) = match claim_total(&database, &account).await {
+        Ok(claim_total) => claim_total,
+        Err(response) => {
+            return response;
         }
     };
 
-    let claim = Claim { claimer, total };
+    let claim = Claim {
+        claimer,
+        total: to_18_decimals(U256::from(total)),
+    };
     let signature = match get_claimer_signature(&claim).await {
         Ok(signature) => signature,
         Err(e) => {
@@ -74,6 +167,203 @@ async fn post_claim(database: web::Data<Database>, path: web::Path<String>) -> i
             return HttpResponse::InternalServerError().finish();
         }
     };
+    if let Err(response) =
+        record_claim_signature(&database, &request, account, total, None, &signature).await
+    {
+        return response;
+    }
 
     HttpResponse::Ok().json(signature.to_string())
 }
+
+#[derive(Serialize, Deserialize)]
+pub struct ClaimWithDeadlineSignature {
+    pub total: U256,
+    pub deadline: i64,
+    pub signature: String,
+}
+#[post("/{account}/claim_with_deadline")]
+async fn post_claim_with_deadline(
+    database: web::Data<Database>,
+    request: HttpRequest,
+    path: web::Path<String>,
+) -> impl Responder {
+    let account = path.into_inner();
+    let (claimer, total) = match claim_total(&database, &account).await {
+        Ok(claim_total) => claim_total,
+        Err(response) => {
+            return response;
+        }
+    };
+
+    let deadline = get_time_i64() + claimdeadline();
+    let claim = ClaimWithDeadline {
+        claimer,
+        total: to_18_decimals(U256::from(total)),
+        deadline: U256::from(deadline),
+    };
+    let signature = match get_claimer_signature(&claim).await {
+        Ok(signature) => signature,
+        Err(e) => {
+            log::error!(
+                "Signing claim of {total} until {deadline} for {claimer}: {e}",
+                total = claim.total,
+                claimer = claim.claimer
+            );
+            return HttpResponse::InternalServerError().finish();
+        }
+    };
+    if let Err(response) = record_claim_signature(
+        &database,
+        &request,
+        account,
+        total,
+        Some(deadline),
+        &signature,
+    )
+    .await
+    {
+        return response;
+    }
+
+    HttpResponse::Ok().json(ClaimWithDeadlineSignature {
+        total: claim.total,
+        deadline,
+        signature: signature.to_string(),
+    })
+}
+
+#[get("/{account}/claim_signatures")]
+async fn get_account_signatures(
+    database: web::Data<Database>,
+    path: web::Path<String>,
+) -> impl Responder {
+    let account = path.into_inner();
+    match DatabaseClaimSignature::get_all_by_account(&database, &account).await {
+        Ok(signatures) => HttpResponse::Ok().json(signatures),
+        Err(e) => {
+            log::error!("Fetching claim signatures for {account}: {e}");
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
+
+#[get("/claim/signatures")]
+async fn get_signatures(database: web::Data<Database>) -> impl Responder {
+    match DatabaseClaimSignature::get_latest(&database, 1000).await {
+        Ok(signatures) => HttpResponse::Ok().json(signatures),
+        Err(e) => {
+            log::error!("Fetching claim signatures: {e}");
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
+
+#[get("/claim/signatures/excess")]
+async fn get_signatures_excess(database: web::Data<Database>) -> impl Responder {
+    match DatabaseClaimSignature::get_all_excess(&database).await {
+        Ok(excess) => HttpResponse::Ok().json(excess),
+        Err(e) => {
+            log::error!("Fetching claim signatures exceeding earnings: {e}");
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ClaimProof {
+    pub root: String,
+    #[serde(flatten)]
+    pub leaf: ClaimMerkleLeaf,
+}
+#[get("/{account}/claim_proof")]
+async fn get_claim_proof(database: web::Data<Database>, path: web::Path<String>) -> impl Responder {
+    let account = path.into_inner();
+    let root = match DatabaseClaimMerkleRoot::get_latest(&database).await {
+        Ok(root) => match root {
+            Some(root) => root,
+            None => {
+                return HttpResponse::NotFound().finish();
+            }
+        },
+        Err(e) => {
+            log::error!("Fetching latest claim merkle root: {e}");
+            return HttpResponse::InternalServerError().finish();
+        }
+    };
+
+    match DatabaseClaimMerkleLeaf::get_by_root_id_account(&database, root.id, &account).await {
+        Ok(Some(leaf)) => HttpResponse::Ok().json(ClaimProof {
+            root: root.root,
+            leaf: leaf.into(),
+        }),
+        Ok(None) => HttpResponse::NotFound().finish(),
+        Err(e) => {
+            log::error!("Fetching claim proof for {account}: {e}");
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
+
+#[get("/claim/merkle")]
+async fn get_merkle_roots(database: web::Data<Database>) -> impl Responder {
+    match DatabaseClaimMerkleRoot::get_all(&database).await {
+        Ok(roots) => HttpResponse::Ok().json(roots),
+        Err(e) => {
+            log::error!("Fetching claim merkle roots: {e}");
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
+
+#[get("/claim/merkle/export")]
+async fn get_merkle_export(database: web::Data<Database>) -> impl Responder {
+    let root = match DatabaseClaimMerkleRoot::get_latest(&database).await {
+        Ok(root) => match root {
+            Some(root) => root,
+            None => {
+                return HttpResponse::NotFound().finish();
+            }
+        },
+        Err(e) => {
+            log::error!("Fetching latest claim merkle root: {e}");
+            return HttpResponse::InternalServerError().finish();
+        }
+    };
+
+    match get_claim_merkle_export(&database, root).await {
+        Ok(export) => HttpResponse::Ok().json(export),
+        Err(e) => {
+            log::error!("Fetching claim merkle export: {e}");
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
+
+#[get("/claim/merkle/{root}/export")]
+async fn get_merkle_root_export(
+    database: web::Data<Database>,
+    path: web::Path<String>,
+) -> impl Responder {
+    let root = path.into_inner();
+    let root = match DatabaseClaimMerkleRoot::get_by_root(&database, &root).await {
+        Ok(merkle_root) => match merkle_root {
+            Some(merkle_root) => merkle_root,
+            None => {
+                return HttpResponse::NotFound().finish();
+            }
+        },
+        Err(e) => {
+            log::error!("Fetching claim merkle root {root}: {e}");
+            return HttpResponse::InternalServerError().finish();
+        }
+    };
+
+    match get_claim_merkle_export(&database, root).await {
+        Ok(export) => HttpResponse::Ok().json(export),
+        Err(e) => {
+            log::error!("Fetching claim merkle export: {e}");
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
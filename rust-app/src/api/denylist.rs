@@ -0,0 +1,163 @@
+use std::str::FromStr;
+
+use actix_web::{HttpResponse, Responder, get, post, web};
+use alloy::providers::DynProvider;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    database::{Database, denial::DatabaseDenial, denylist::DatabaseDenylist},
+    utils::{
+        admin_signer::{AdminRole, validate_admin_signature},
+        denylist::{DenylistScope, is_denied},
+        replay::Freshness,
+        time::get_time_i64,
+    },
+};
+
+/// Forbidden when the account is denied the scope.
+pub async fn ensure_not_denied(
+    database: &Database,
+    account: &str,
+    scope: DenylistScope,
+) -> Result<(), HttpResponse> {
+    match is_denied(database, account, scope).await {
+        Ok(false) => Ok(()),
+        Ok(true) => Err(HttpResponse::Forbidden().finish()),
+        Err(e) => {
+            log::error!("Fetching {scope} denylist for {account}: {e}");
+            Err(HttpResponse::InternalServerError().finish())
+        }
+    }
+}
+
+#[get("/denylist")]
+async fn get_denylist(database: web::Data<Database>) -> impl Responder {
+    match DatabaseDenylist::get_all(&database).await {
+        Ok(denylist) => HttpResponse::Ok().json(denylist),
+        Err(e) => {
+            log::error!("Fetching denylist: {e}");
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
+
+#[get("/denylist/denials")]
+async fn get_denials(database: web::Data<Database>) -> impl Responder {
+    match DatabaseDenial::get_latest(&database, 1000).await {
+        Ok(denials) => HttpResponse::Ok().json(denials),
+        Err(e) => {
+            log::error!("Fetching denials: {e}");
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct DenylistAdd {
+    pub account: String,
+    pub scope: String,
+    pub reason: String,
+    pub expires: Option<i64>,
+    pub signature: String,
+    #[serde(flatten)]
+    pub freshness: Freshness,
+}
+#[post("/denylist/add")]
+async fn post_add(
+    database: web::Data<Database>,
+    provider: web::Data<DynProvider>,
+    data: web::Json<DenylistAdd>,
+) -> impl Responder {
+    let scope = match DenylistScope::from_str(&data.scope) {
+        Ok(scope) => scope,
+        Err(_e) => {
+            return HttpResponse::BadRequest().finish();
+        }
+    };
+
+    let message = format!(
+        "Deny {scope} for {account} until {expires}: {reason}",
+        account = data.account,
+        expires = data
+            .expires
+            .map(|expires| expires.to_string())
+            .unwrap_or("never".to_string()),
+        reason = data.reason
+    );
+    let Some(signer) = validate_admin_signature(
+        provider.get_ref(),
+        &database,
+        AdminRole::Denylist,
+        &message,
+        &data.signature,
+        &data.freshness,
+    )
+    .await
+    else {
+        return HttpResponse::Unauthorized().finish();
+    };
+
+    let entry = DatabaseDenylist {
+        account: data.account.clone(),
+        scope: scope.to_string(),
+        reason: data.reason.clone(),
+        added_by: signer,
+        created_at: get_time_i64(),
+        expires: data.expires,
+    };
+    if let Err(e) = entry.upsert(&database).await {
+        log::error!("COULD NOT INSERT DENYLIST ENTRY {entry:?}: {e}");
+        return HttpResponse::InternalServerError().finish();
+    }
+
+    HttpResponse::Ok().finish()
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct DenylistRemove {
+    pub account: String,
+    pub scope: String,
+    pub signature: String,
+    #[serde(flatten)]
+    pub freshness: Freshness,
+}
+#[post("/denylist/remove")]
+async fn post_remove(
+    database: web::Data<Database>,
+    provider: web::Data<DynProvider>,
+    data: web::Json<DenylistRemove>,
+) -> impl Responder {
+    let scope = match DenylistScope::from_str(&data.scope) {
+        Ok(scope) => scope,
+        Err(_e) => {
+            return HttpResponse::BadRequest().finish();
+        }
+    };
+
+    let message = format!("Allow {scope} for {account}", account = data.account);
+    if validate_admin_signature(
+        provider.get_ref(),
+        &database,
+        AdminRole::Denylist,
+        &message,
+        &data.signature,
+        &data.freshness,
+    )
+    .await
+    .is_none()
+    {
+        return HttpResponse::Unauthorized().finish();
+    }
+
+    match DatabaseDenylist::delete(&database, &data.account, &scope.to_string()).await {
+        Ok(true) => HttpResponse::Ok().finish(),
+        Ok(false) => HttpResponse::NotFound().finish(),
+        Err(e) => {
+            log::error!(
+                "COULD NOT REMOVE {account} FROM {scope} DENYLIST: {e}",
+                account = data.account
+            );
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
@@ -9,7 +9,11 @@ use crate::{
             DatabaseDeploymentSignature, DatabaseDeploymentSignaturePerDayCount,
         },
     },
-    utils::{signature_validator::validate_signature, time::get_time_i64},
+    utils::{
+        replay::{Freshness, validate_fresh_typed_signature},
+        time::get_time_i64,
+        typed_data::DeployApp,
+    },
 };
 
 #[get("/deployment_signature/total")]
@@ -80,6 +84,8 @@ pub struct DeploymentSignature {
     pub version: String,
     pub deployer: Option<String>,
     pub signature: Option<String>,
+    #[serde(flatten)]
+    pub freshness: Option<Freshness>,
 }
 #[post("/deployment_signature/upload")]
 async fn post_upload(
@@ -88,18 +94,34 @@ async fn post_upload(
     data: web::Json<DeploymentSignature>,
 ) -> impl Responder {
     if let Some(deployer) = &data.deployer {
-        match &data.signature {
-            Some(signature) => {
+        match (&data.signature, &data.freshness) {
+            (Some(signature), Some(freshness)) => {
                 let message = format!(
                     "I just deployed {version} of {app} on OpenxAI Studio!",
                     version = data.version,
                     app = data.app
                 );
-                if !validate_signature(provider.get_ref(), deployer, &message, signature).await {
+                let typed = DeployApp {
+                    app: data.app.clone(),
+                    version: data.version.clone(),
+                    nonce: freshness.nonce.clone(),
+                    timestamp: freshness.timestamp,
+                };
+                if !validate_fresh_typed_signature(
+                    provider.get_ref(),
+                    &database,
+                    deployer,
+                    &typed,
+                    Some(&message),
+                    signature,
+                    freshness,
+                )
+                .await
+                {
                     return HttpResponse::Unauthorized().finish();
                 }
             }
-            None => {
+            _ => {
                 return HttpResponse::BadRequest().finish();
             }
         }
@@ -0,0 +1,47 @@
+use actix_web::{HttpResponse, Responder, post, web};
+use alloy::providers::DynProvider;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    database::{Database, inventory_issue::DatabaseInventoryIssue},
+    utils::{
+        admin_signer::{AdminRole, validate_admin_signature},
+        replay::Freshness,
+    },
+};
+
+#[derive(Serialize, Deserialize)]
+pub struct InventoryIssuesRequest {
+    pub signature: String,
+    #[serde(flatten)]
+    pub freshness: Freshness,
+}
+/// Issues expose provider VM names and ids, so they are for the operators running the reconciliation job only.
+#[post("/inventory/issues")]
+async fn post_issues(
+    database: web::Data<Database>,
+    provider: web::Data<DynProvider>,
+    data: web::Json<InventoryIssuesRequest>,
+) -> impl Responder {
+    if validate_admin_signature(
+        provider.get_ref(),
+        &database,
+        AdminRole::Job,
+        "List inventory issues",
+        &data.signature,
+        &data.freshness,
+    )
+    .await
+    .is_none()
+    {
+        return HttpResponse::Unauthorized().finish();
+    }
+
+    match DatabaseInventoryIssue::get_all(&database).await {
+        Ok(issues) => HttpResponse::Ok().json(issues),
+        Err(e) => {
+            log::error!("Fetching inventory issues: {e}");
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
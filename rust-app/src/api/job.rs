@@ -0,0 +1,122 @@
+use actix_web::{HttpResponse, Responder, post, web};
+use alloy::providers::DynProvider;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    database::{Database, job::DatabaseJob, job_run::DatabaseJobRun},
+    utils::{
+        admin_signer::{AdminRole, validate_admin_signature},
+        replay::Freshness,
+        time::get_time_i64,
+    },
+};
+
+#[derive(Serialize, Deserialize)]
+pub struct JobList {
+    pub signature: String,
+    #[serde(flatten)]
+    pub freshness: Freshness,
+}
+/// Errors of runs can contain database errors and account data, so listing is for admins only.
+#[post("/jobs")]
+async fn post_jobs(
+    database: web::Data<Database>,
+    provider: web::Data<DynProvider>,
+    data: web::Json<JobList>,
+) -> impl Responder {
+    if validate_admin_signature(
+        provider.get_ref(),
+        &database,
+        AdminRole::Job,
+        "List jobs",
+        &data.signature,
+        &data.freshness,
+    )
+    .await
+    .is_none()
+    {
+        return HttpResponse::Unauthorized().finish();
+    }
+
+    match DatabaseJob::get_all(&database).await {
+        Ok(jobs) => HttpResponse::Ok().json(jobs),
+        Err(e) => {
+            log::error!("Fetching jobs: {e}");
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
+
+#[post("/jobs/{name}/runs")]
+async fn post_runs(
+    database: web::Data<Database>,
+    provider: web::Data<DynProvider>,
+    path: web::Path<String>,
+    data: web::Json<JobList>,
+) -> impl Responder {
+    let name = path.into_inner();
+
+    let message = format!("List runs of job {name}");
+    if validate_admin_signature(
+        provider.get_ref(),
+        &database,
+        AdminRole::Job,
+        &message,
+        &data.signature,
+        &data.freshness,
+    )
+    .await
+    .is_none()
+    {
+        return HttpResponse::Unauthorized().finish();
+    }
+
+    match DatabaseJobRun::get_latest_by_name(&database, &name, 100).await {
+        Ok(runs) => HttpResponse::Ok().json(runs),
+        Err(e) => {
+            log::error!("Fetching runs of job {name}: {e}");
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct JobTrigger {
+    pub signature: String,
+    #[serde(flatten)]
+    pub freshness: Freshness,
+}
+#[post("/jobs/{name}/run")]
+async fn post_run(
+    database: web::Data<Database>,
+    provider: web::Data<DynProvider>,
+    path: web::Path<String>,
+    data: web::Json<JobTrigger>,
+) -> impl Responder {
+    let name = path.into_inner();
+
+    let message = format!("Run job {name}");
+    if validate_admin_signature(
+        provider.get_ref(),
+        &database,
+        AdminRole::Job,
+        &message,
+        &data.signature,
+        &data.freshness,
+    )
+    .await
+    .is_none()
+    {
+        return HttpResponse::Unauthorized().finish();
+    }
+
+    // Picked up by the scheduler of whichever instance gets the job lock
+    match DatabaseJob::trigger(&database, &name, get_time_i64()).await {
+        Ok(true) => HttpResponse::Ok().finish(),
+        Ok(false) => HttpResponse::NotFound().finish(),
+        Err(e) => {
+            log::error!("COULD NOT TRIGGER JOB {name}: {e}");
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
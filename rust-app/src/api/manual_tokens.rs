@@ -4,7 +4,12 @@ use serde::{Deserialize, Serialize};
 
 use crate::{
     database::{Database, manual_tokens::DatabaseManualTokens},
-    utils::{env::manualtokensigner, signature_validator::validate_signature},
+    utils::{
+        admin_signer::{AdminRole, validate_admin_signature},
+        manual_tokens::{ManualToken, add_manual_tokens},
+        proposal::proposal_threshold,
+        replay::Freshness,
+    },
 };
 
 #[get("/{account}/manual_tokens")]
@@ -22,17 +27,12 @@ async fn get_manual_tokens(
     }
 }
 
-#[derive(Serialize, Deserialize)]
-pub struct ManualToken {
-    pub account: String,
-    pub amount: i64,
-    pub description: String,
-    pub release_after: i64,
-}
 #[derive(Serialize, Deserialize)]
 pub struct ManualTokensSignature {
     pub manual_tokens: String,
     pub signature: String,
+    #[serde(flatten)]
+    pub freshness: Freshness,
 }
 #[post("/manual_tokens/upload")]
 async fn post_upload(
@@ -40,13 +40,21 @@ async fn post_upload(
     provider: web::Data<DynProvider>,
     data: web::Json<ManualTokensSignature>,
 ) -> impl Responder {
-    if !validate_signature(
+    if proposal_threshold(AdminRole::ManualTokens) > 1 {
+        // Needs approval from multiple signers through a proposal
+        return HttpResponse::Forbidden().finish();
+    }
+
+    if validate_admin_signature(
         provider.get_ref(),
-        &manualtokensigner(),
+        &database,
+        AdminRole::ManualTokens,
         &data.manual_tokens,
         &data.signature,
+        &data.freshness,
     )
     .await
+    .is_none()
     {
         return HttpResponse::Unauthorized().finish();
     }
@@ -58,19 +66,7 @@ async fn post_upload(
         }
     };
 
-    for token in &manual_tokens {
-        let token = DatabaseManualTokens {
-            account: token.account.clone(),
-            amount: token.amount,
-            approval_signature: data.signature.clone(),
-            description: token.description.clone(),
-            release_after: token.release_after,
-            released: false,
-        };
-        if let Err(e) = token.insert(&database).await {
-            log::error!("COULD NOT INSERT MANUAL TOKEN {token:?}: {e}");
-        }
-    }
+    add_manual_tokens(&database, &manual_tokens, &data.signature).await;
 
     HttpResponse::Ok().finish()
 }
@@ -1,17 +1,33 @@
 use actix_web::web::ServiceConfig;
 
+pub mod admin_proposal;
+pub mod admin_signer;
 pub mod agreement;
 pub mod claim;
 pub mod credits;
+pub mod denylist;
 pub mod deployment_signature;
+pub mod inventory;
+pub mod job;
 pub mod manual_tokens;
 pub mod nft_staking;
+pub mod notification;
 pub mod ownai_v1;
 pub mod participated;
 pub mod promo_code;
+pub mod subdomain;
 pub mod tokens_claimed;
 
 pub fn configure(cfg: &mut ServiceConfig) {
+    cfg.service(admin_proposal::get_proposals);
+    cfg.service(admin_proposal::get_proposal);
+    cfg.service(admin_proposal::post_create);
+    cfg.service(admin_proposal::post_approve);
+
+    cfg.service(admin_signer::get_admin_signers);
+    cfg.service(admin_signer::post_add);
+    cfg.service(admin_signer::post_revoke);
+
     cfg.service(agreement::list);
     cfg.service(agreement::info);
     cfg.service(agreement::create);
@@ -19,11 +35,26 @@ pub fn configure(cfg: &mut ServiceConfig) {
 
     cfg.service(claim::get_claim);
     cfg.service(claim::get_claim_total);
+    cfg.service(claim::get_account_summary);
+    cfg.service(claim::get_summary);
     cfg.service(claim::post_claim);
+    cfg.service(claim::post_claim_with_deadline);
+    cfg.service(claim::get_account_signatures);
+    cfg.service(claim::get_signatures);
+    cfg.service(claim::get_signatures_excess);
+    cfg.service(claim::get_claim_proof);
+    cfg.service(claim::get_merkle_roots);
+    cfg.service(claim::get_merkle_export);
+    cfg.service(claim::get_merkle_root_export);
 
     cfg.service(credits::get_credits);
     cfg.service(credits::get_total_credits);
 
+    cfg.service(denylist::get_denylist);
+    cfg.service(denylist::get_denials);
+    cfg.service(denylist::post_add);
+    cfg.service(denylist::post_remove);
+
     cfg.service(deployment_signature::get_total);
     cfg.service(deployment_signature::get_per_day);
     cfg.service(deployment_signature::get_latest);
@@ -31,6 +62,12 @@ pub fn configure(cfg: &mut ServiceConfig) {
     cfg.service(deployment_signature::get_app_version_total);
     cfg.service(deployment_signature::post_upload);
 
+    cfg.service(inventory::post_issues);
+
+    cfg.service(job::post_jobs);
+    cfg.service(job::post_runs);
+    cfg.service(job::post_run);
+
     cfg.service(manual_tokens::get_manual_tokens);
     cfg.service(manual_tokens::post_upload);
 
@@ -38,19 +75,46 @@ pub fn configure(cfg: &mut ServiceConfig) {
     cfg.service(ownai_v1::get_owner_servers);
     cfg.service(ownai_v1::get_controller_servers);
     cfg.service(ownai_v1::post_controller);
+    cfg.service(ownai_v1::get_controllers);
+    cfg.service(ownai_v1::post_controllers_add);
+    cfg.service(ownai_v1::post_controllers_remove);
+    cfg.service(ownai_v1::get_domains);
+    cfg.service(ownai_v1::post_domain);
+    cfg.service(ownai_v1::post_domain_verify);
+    cfg.service(ownai_v1::post_domain_remove);
+    cfg.service(ownai_v1::post_power_action);
+    cfg.service(ownai_v1::get_actions);
+    cfg.service(ownai_v1::get_health);
+    cfg.service(ownai_v1::get_heals);
     cfg.service(ownai_v1::post_expires);
+    cfg.service(ownai_v1::get_auto_renew);
+    cfg.service(ownai_v1::post_auto_renew);
+    cfg.service(ownai_v1::post_auto_renew_cancel);
     cfg.service(ownai_v1::get_price);
     cfg.service(ownai_v1::get_available);
+    cfg.service(ownai_v1::get_tiers);
+    cfg.service(ownai_v1::get_presets);
+    cfg.service(ownai_v1::post_preset);
     cfg.service(ownai_v1::post_mint);
+    cfg.service(ownai_v1::post_reserve);
+    cfg.service(ownai_v1::get_reservation);
+    cfg.service(ownai_v1::get_reservations);
+    cfg.service(ownai_v1::post_reservation_cancel);
     cfg.service(ownai_v1::get_active);
     cfg.service(ownai_v1::get_staking);
 
+    cfg.service(notification::get_notifications);
+
     cfg.service(participated::get_participated);
 
     cfg.service(promo_code::post_redeem);
     cfg.service(promo_code::post_add);
 
+    cfg.service(subdomain::get_subdomains);
+
     cfg.service(nft_staking::get_leaderboard);
+    cfg.service(nft_staking::get_preview);
+    cfg.service(nft_staking::get_runs);
     cfg.service(nft_staking::get_staking);
     cfg.service(nft_staking::get_total_staking);
 
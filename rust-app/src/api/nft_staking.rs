@@ -1,8 +1,12 @@
 use actix_web::{HttpResponse, Responder, get, web};
 
-use crate::database::{
-    Database,
-    nft_staking::{DatabaseNFTStaking, DatabaseNFTStakingLeaderboard},
+use crate::{
+    database::{
+        Database,
+        nft_staking::{DatabaseNFTStaking, DatabaseNFTStakingLeaderboard},
+        staking_run::DatabaseStakingRun,
+    },
+    utils::staking::{calculate_staking_rewards, next_staking_distribution},
 };
 
 #[get("/nft_staking/leaderboard")]
@@ -16,6 +20,30 @@ async fn get_leaderboard(database: web::Data<Database>) -> impl Responder {
     }
 }
 
+/// Distribution as it would be committed at the next UTC midnight with the current servers.
+#[get("/nft_staking/preview")]
+async fn get_preview(database: web::Data<Database>) -> impl Responder {
+    match calculate_staking_rewards(&database, next_staking_distribution().timestamp()).await {
+        Ok(distributions) => HttpResponse::Ok().json(distributions),
+        Err(e) => {
+            log::error!("Calculating nft staking preview: {e}");
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
+
+#[get("/nft_staking/{collection}/runs")]
+async fn get_runs(database: web::Data<Database>, path: web::Path<String>) -> impl Responder {
+    let collection = path.into_inner();
+    match DatabaseStakingRun::get_all_by_collection(&database, &collection).await {
+        Ok(runs) => HttpResponse::Ok().json(runs),
+        Err(e) => {
+            log::error!("Fetching nft staking runs of {collection}: {e}");
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
+
 #[get("/{account}/nft_staking")]
 async fn get_staking(database: web::Data<Database>, path: web::Path<String>) -> impl Responder {
     let account = path.into_inner();
@@ -0,0 +1,18 @@
+use actix_web::{HttpResponse, Responder, get, web};
+
+use crate::database::{Database, notification::DatabaseNotification};
+
+#[get("/{account}/notifications")]
+async fn get_notifications(
+    database: web::Data<Database>,
+    path: web::Path<String>,
+) -> impl Responder {
+    let account = path.into_inner();
+    match DatabaseNotification::get_all_by_account(&database, &account).await {
+        Ok(notifications) => HttpResponse::Ok().json(notifications),
+        Err(e) => {
+            log::error!("Fetching notifications for {account}: {e}");
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
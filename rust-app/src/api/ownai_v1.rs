@@ -1,25 +1,48 @@
-use std::sync::{
-    Arc,
-    atomic::{AtomicI64, Ordering},
-};
+use std::{collections::BTreeMap, str::FromStr};
 
 use actix_web::{HttpResponse, Responder, get, post, web};
-use alloy::{primitives::Address, providers::DynProvider};
+use alloy::{
+    primitives::{Address, keccak256},
+    providers::DynProvider,
+};
 use serde::{Deserialize, Serialize};
+use sqlx::types::Json;
 
 use crate::{
+    api::denylist::ensure_not_denied,
     database::{
         Database,
+        auto_renew::DatabaseAutoRenew,
         credits::DatabaseCredits,
+        custom_domain::DatabaseCustomDomain,
+        heal_event::DatabaseHealEvent,
+        health_sample::DatabaseHealthSample,
         nft_staking::DatabaseNFTStaking,
+        power_action::DatabasePowerAction,
+        reservation::{DatabaseReservation, ReservationStatus},
+        server_controller::DatabaseServerController,
         tokenized_server::{Chain, Collection, DatabaseTokenizedServer},
     },
     utils::{
-        env::ownaiv1price,
-        signature_validator::validate_signature,
+        controller::{ControlledXnode, ControllerRole, is_valid_controller, server_controllers},
+        custom_domain::{
+            DohResolver, apply_custom_domains, challenge_name, is_valid_domain,
+            update_custom_domains, verify_challenge,
+        },
+        denylist::DenylistScope,
+        env::poweractioncooldown,
+        health::UPTIME_PERIOD,
+        mint::{OwnAIV1TokenCounter, mint_v1},
+        power::{PowerAction, PowerError, execute_power_action},
+        preset::{self, get_preset, legacy_app_config},
+        provider::get_deployment_targets,
+        replay::{Freshness, validate_fresh_signature, validate_fresh_typed_signature},
+        stock::StockClient,
+        subdomain::server_domain,
+        tier::{self, get_tier},
         time::get_time_i64,
-        wallet::mint_tokenized_server,
-        xnode::{available_v1, deploy_v1, str_to_xnode_user, update_controller},
+        typed_data::{AuthorizeAutoRenew, ExtendExpiry, MintServer, UpdateController},
+        xnode::{update_controller, update_controllers},
     },
 };
 
@@ -119,6 +142,8 @@ async fn get_controller_servers(
 pub struct ControllerUpdate {
     pub controller: String,
     pub owner_signature: String,
+    #[serde(flatten)]
+    pub freshness: Freshness,
 }
 #[post("/ownaiv1/{chain}/{token_id}/controller")]
 async fn post_controller(
@@ -130,6 +155,10 @@ async fn post_controller(
     let (chain, token_id) = path.into_inner();
     let collection = Collection::OwnAIv1.to_string();
 
+    if !is_valid_controller(&data.controller) {
+        return HttpResponse::BadRequest().finish();
+    }
+
     let mut server = match DatabaseTokenizedServer::get_by_collection_token_id(
         &database,
         &collection,
@@ -153,11 +182,22 @@ async fn post_controller(
         "Update controller for {collection}@{chain}@{token_id} to {controller}",
         controller = data.controller
     );
-    if !validate_signature(
+    let typed = UpdateController {
+        collection: collection.clone(),
+        chain: chain.clone(),
+        tokenId: token_id.clone(),
+        controller: data.controller.clone(),
+        nonce: data.freshness.nonce.clone(),
+        timestamp: data.freshness.timestamp,
+    };
+    if !validate_fresh_typed_signature(
         provider.get_ref(),
+        &database,
         &server.owner,
-        &message,
+        &typed,
+        Some(&message),
         &data.owner_signature,
+        &data.freshness,
     )
     .await
     {
@@ -169,23 +209,15 @@ async fn post_controller(
     HttpResponse::Ok().finish()
 }
 
-#[derive(Serialize, Deserialize)]
-pub struct ExpiresExtend {
-    pub months: i64,
-    pub payer_address: String,
-    pub payer_signature: String,
-}
-#[post("/ownaiv1/{chain}/{token_id}/expires")]
-async fn post_expires(
+#[get("/ownaiv1/{chain}/{token_id}/controllers")]
+async fn get_controllers(
     database: web::Data<Database>,
-    provider: web::Data<DynProvider>,
     path: web::Path<(String, String)>,
-    data: web::Json<ExpiresExtend>,
 ) -> impl Responder {
     let (chain, token_id) = path.into_inner();
     let collection = Collection::OwnAIv1.to_string();
 
-    let mut server = match DatabaseTokenizedServer::get_by_collection_token_id(
+    let server = match DatabaseTokenizedServer::get_by_collection_token_id(
         &database,
         &collection,
         &chain,
@@ -204,167 +236,1622 @@ async fn post_expires(
         }
     };
 
-    if server.deployment.is_none() {
-        // Redeployment of expired servers not possible
+    match server_controllers(&database, &server).await {
+        Ok(controllers) => HttpResponse::Ok().json(controllers),
+        Err(e) => {
+            log::error!("Fetching controllers of {collection}@{chain}@{token_id}: {e}");
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ControllerAdd {
+    pub controller: String,
+    pub role: ControllerRole,
+    pub owner_signature: String,
+    #[serde(flatten)]
+    pub freshness: Freshness,
+}
+#[post("/ownaiv1/{chain}/{token_id}/controllers/add")]
+async fn post_controllers_add(
+    database: web::Data<Database>,
+    provider: web::Data<DynProvider>,
+    path: web::Path<(String, String)>,
+    data: web::Json<ControllerAdd>,
+) -> impl Responder {
+    let (chain, token_id) = path.into_inner();
+    let collection = Collection::OwnAIv1.to_string();
+
+    if !is_valid_controller(&data.controller) {
         return HttpResponse::BadRequest().finish();
     }
 
+    let server = match DatabaseTokenizedServer::get_by_collection_token_id(
+        &database,
+        &collection,
+        &chain,
+        &token_id,
+    )
+    .await
+    {
+        Ok(server) => match server {
+            Some(server) => server,
+            None => {
+                return HttpResponse::BadRequest().finish();
+            }
+        },
+        Err(_e) => {
+            return HttpResponse::BadRequest().finish();
+        }
+    };
+
     let message = format!(
-        "Extend expiry of {collection}@{chain}@{token_id} by {months} months",
-        months = data.months
+        "Add controller {controller} as {role} to {collection}@{chain}@{token_id}",
+        controller = data.controller,
+        role = data.role
     );
-    if !validate_signature(
+    if !validate_fresh_signature(
         provider.get_ref(),
-        &data.payer_address,
+        &database,
+        &server.owner,
         &message,
-        &data.payer_signature,
+        &data.owner_signature,
+        &data.freshness,
     )
     .await
     {
         return HttpResponse::Unauthorized().finish();
     }
 
-    if let Err(_e) = (DatabaseCredits {
-        account: data.payer_address.clone(),
-        credits: -ownaiv1price() * data.months,
-        description: format!(
-            "Extend expiry of {collection}@{chain}@{token_id} by {months} months",
-            months = data.months
-        ),
-        date: get_time_i64(),
-    })
-    .insert(&database)
-    .await
-    {
-        return HttpResponse::PaymentRequired().finish();
+    // The main controller is always admin, it is changed through the controller update instead
+    if data.controller == server.controller {
+        return HttpResponse::BadRequest().finish();
     }
 
-    let one_month = 30 * 24 * 60 * 60; // 1 month in seconds
-    if let Err(e) = server
-        .update_expires(&database, server.expires + data.months * one_month)
-        .await
-    {
-        log::error!(
-            "COULD NOT EXTEND TOKENIZED SERVER EXPIRES {collection}@{chain}@{token_id} BY {months} MONTHS: {e}",
-            months = data.months
-        );
+    let controller = DatabaseServerController {
+        collection,
+        chain,
+        token_id,
+        controller: data.controller.clone(),
+        role: data.role.to_string(),
+        created_at: get_time_i64(),
+    };
+    if let Err(e) = controller.upsert(&database).await {
+        log::error!("COULD NOT INSERT SERVER CONTROLLER {controller:?}: {e}");
         return HttpResponse::InternalServerError().finish();
     }
 
+    update_controllers(&database, &server).await;
+
     HttpResponse::Ok().finish()
 }
 
-#[derive(Clone)]
-pub struct OwnAIV1TokenCounter {
-    counter: Arc<AtomicI64>,
+#[derive(Serialize, Deserialize)]
+pub struct ControllerRemove {
+    pub controller: String,
+    pub owner_signature: String,
+    #[serde(flatten)]
+    pub freshness: Freshness,
 }
-impl OwnAIV1TokenCounter {
-    pub async fn new(database: Database) -> Self {
-        let collection = Collection::OwnAIv1.to_string();
-        let chain = Chain::Base.to_string();
-        let max = match DatabaseTokenizedServer::get_max_token_id_by_collection(
-            &database,
-            &collection,
-            &chain,
-        )
-        .await
-        {
-            Ok(max) => max.unwrap_or(0),
-            Err(e) => {
-                panic!("Could not fetch max token_id for {collection} minting: {e}")
+#[post("/ownaiv1/{chain}/{token_id}/controllers/remove")]
+async fn post_controllers_remove(
+    database: web::Data<Database>,
+    provider: web::Data<DynProvider>,
+    path: web::Path<(String, String)>,
+    data: web::Json<ControllerRemove>,
+) -> impl Responder {
+    let (chain, token_id) = path.into_inner();
+    let collection = Collection::OwnAIv1.to_string();
+
+    let server = match DatabaseTokenizedServer::get_by_collection_token_id(
+        &database,
+        &collection,
+        &chain,
+        &token_id,
+    )
+    .await
+    {
+        Ok(server) => match server {
+            Some(server) => server,
+            None => {
+                return HttpResponse::BadRequest().finish();
             }
-        };
+        },
+        Err(_e) => {
+            return HttpResponse::BadRequest().finish();
+        }
+    };
+
+    let message = format!(
+        "Remove controller {controller} from {collection}@{chain}@{token_id}",
+        controller = data.controller
+    );
+    if !validate_fresh_signature(
+        provider.get_ref(),
+        &database,
+        &server.owner,
+        &message,
+        &data.owner_signature,
+        &data.freshness,
+    )
+    .await
+    {
+        return HttpResponse::Unauthorized().finish();
+    }
 
-        Self {
-            counter: Arc::new(AtomicI64::from(max + 1)),
+    match DatabaseServerController::delete(
+        &database,
+        &collection,
+        &chain,
+        &token_id,
+        &data.controller,
+    )
+    .await
+    {
+        Ok(true) => (),
+        Ok(false) => {
+            return HttpResponse::BadRequest().finish();
+        }
+        Err(e) => {
+            log::error!(
+                "COULD NOT DELETE SERVER CONTROLLER {controller} OF {collection}@{chain}@{token_id}: {e}",
+                controller = data.controller
+            );
+            return HttpResponse::InternalServerError().finish();
         }
     }
-}
 
-#[get("/ownaiv1/{chain}/price")]
-async fn get_price() -> impl Responder {
-    HttpResponse::Ok().json(ownaiv1price())
+    update_controllers(&database, &server).await;
+
+    HttpResponse::Ok().finish()
 }
 
-#[get("/ownaiv1/{chain}/available")]
-async fn get_available() -> impl Responder {
-    HttpResponse::Ok().json(available_v1().await)
+#[get("/ownaiv1/{chain}/{token_id}/domains")]
+async fn get_domains(
+    database: web::Data<Database>,
+    path: web::Path<(String, String)>,
+) -> impl Responder {
+    let (chain, token_id) = path.into_inner();
+    let collection = Collection::OwnAIv1.to_string();
+
+    match DatabaseCustomDomain::get_all_by_collection_token_id(
+        &database,
+        &collection,
+        &chain,
+        &token_id,
+    )
+    .await
+    {
+        Ok(domains) => HttpResponse::Ok().json(domains),
+        Err(e) => {
+            log::error!("Fetching custom domains of {collection}@{chain}@{token_id}: {e}");
+            HttpResponse::InternalServerError().finish()
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize)]
-pub struct Mint {
-    pub to: String,
-    pub payer_address: String,
-    pub payer_signature: String,
+pub struct DomainAdd {
+    pub domain: String,
+    pub owner_signature: String,
+    #[serde(flatten)]
+    pub freshness: Freshness,
 }
-#[post("/ownaiv1/{chain}/mint")]
-async fn post_mint(
+#[derive(Serialize, Deserialize)]
+pub struct DomainChallenge {
+    pub name: String,
+    pub value: String,
+}
+#[post("/ownaiv1/{chain}/{token_id}/domain")]
+async fn post_domain(
     database: web::Data<Database>,
     provider: web::Data<DynProvider>,
-    counter: web::Data<OwnAIV1TokenCounter>,
-    path: web::Path<String>,
-    data: web::Json<Mint>,
+    path: web::Path<(String, String)>,
+    data: web::Json<DomainAdd>,
 ) -> impl Responder {
-    let to = match Address::parse_checksummed(&data.to, None) {
-        Ok(account) => account,
+    let (chain, token_id) = path.into_inner();
+    let collection = Collection::OwnAIv1.to_string();
+
+    if !is_valid_domain(&data.domain) {
+        return HttpResponse::BadRequest().finish();
+    }
+
+    let server = match DatabaseTokenizedServer::get_by_collection_token_id(
+        &database,
+        &collection,
+        &chain,
+        &token_id,
+    )
+    .await
+    {
+        Ok(server) => match server {
+            Some(server) => server,
+            None => {
+                return HttpResponse::BadRequest().finish();
+            }
+        },
         Err(_e) => {
             return HttpResponse::BadRequest().finish();
         }
     };
 
-    if available_v1().await == 0 {
-        return HttpResponse::FailedDependency().finish();
-    }
-
-    let chain = path.into_inner();
-    if chain != Chain::Base.to_string() {
-        return HttpResponse::BadRequest().finish();
-    }
-    let collection = Collection::OwnAIv1.to_string();
-
-    let message = format!("Mint new {collection}@{chain} to {to}", to = data.to);
-    if !validate_signature(
+    let message = format!(
+        "Add domain {domain} to {collection}@{chain}@{token_id}",
+        domain = data.domain
+    );
+    if !validate_fresh_signature(
         provider.get_ref(),
-        &data.payer_address,
+        &database,
+        &server.owner,
         &message,
-        &data.payer_signature,
+        &data.owner_signature,
+        &data.freshness,
     )
     .await
     {
         return HttpResponse::Unauthorized().finish();
     }
 
-    if let Err(_e) = (DatabaseCredits {
-        account: data.payer_address.clone(),
-        credits: -ownaiv1price(),
-        description: format!("Mint of {collection}@{chain} to {to}", to = data.to),
-        date: get_time_i64(),
-    })
-    .insert(&database)
-    .await
-    {
-        return HttpResponse::PaymentRequired().finish();
+    match DatabaseCustomDomain::get_by_domain(&database, &data.domain).await {
+        Ok(Some(existing)) => {
+            if existing.verified {
+                return HttpResponse::Conflict().finish();
+            }
+            // Unverified claims can be taken over, only the DNS challenge proves ownership
+            if let Err(e) = existing.delete(&database).await {
+                log::error!("COULD NOT DELETE UNVERIFIED CUSTOM DOMAIN {existing:?}: {e}");
+                return HttpResponse::InternalServerError().finish();
+            }
+        }
+        Ok(None) => (),
+        Err(e) => {
+            log::error!("Fetching custom domain {domain}: {e}", domain = data.domain);
+            return HttpResponse::InternalServerError().finish();
+        }
     }
 
-    let token_id = counter.counter.fetch_add(1, Ordering::Relaxed);
-    let one_month = 30 * 24 * 60 * 60; // 1 month in seconds
-    let mut server = DatabaseTokenizedServer {
+    let custom_domain = DatabaseCustomDomain {
+        domain: data.domain.clone(),
         collection,
         chain,
-        token_id: token_id.to_string(),
-        owner: data.to.clone(),
-        controller: str_to_xnode_user(&data.to),
-        deployment: None,
-        expires: get_time_i64() + one_month,
-    };
-    if let Err(e) = server.insert(&database).await {
-        log::error!("COULD NOT INSERT TOKENIZED SERVER {server:?}: {e}");
+        token_id,
+        challenge: keccak256(format!(
+            "{message} {signature}",
+            signature = data.owner_signature
+        ))
+        .to_string(),
+        verified: false,
+        created_at: get_time_i64(),
+    };
+    if let Err(e) = custom_domain.insert(&database).await {
+        log::error!("COULD NOT INSERT CUSTOM DOMAIN {custom_domain:?}: {e}");
         return HttpResponse::InternalServerError().finish();
     }
-    mint_tokenized_server(provider.get_ref(), to, token_id).await;
-    deploy_v1(&database, &mut server).await;
 
-    HttpResponse::Ok().json(token_id)
+    HttpResponse::Ok().json(DomainChallenge {
+        name: challenge_name(&custom_domain.domain),
+        value: custom_domain.challenge,
+    })
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct DomainVerify {
+    pub domain: String,
+}
+#[post("/ownaiv1/{chain}/{token_id}/domain/verify")]
+async fn post_domain_verify(
+    database: web::Data<Database>,
+    resolver: web::Data<DohResolver>,
+    path: web::Path<(String, String)>,
+    data: web::Json<DomainVerify>,
+) -> impl Responder {
+    let (chain, token_id) = path.into_inner();
+    let collection = Collection::OwnAIv1.to_string();
+
+    let mut custom_domain = match DatabaseCustomDomain::get_by_domain(&database, &data.domain).await
+    {
+        Ok(Some(custom_domain))
+            if custom_domain.collection == collection
+                && custom_domain.chain == chain
+                && custom_domain.token_id == token_id =>
+        {
+            custom_domain
+        }
+        Ok(_) => {
+            return HttpResponse::BadRequest().finish();
+        }
+        Err(e) => {
+            log::error!("Fetching custom domain {domain}: {e}", domain = data.domain);
+            return HttpResponse::InternalServerError().finish();
+        }
+    };
+
+    if !custom_domain.verified {
+        match verify_challenge(resolver.get_ref(), &custom_domain).await {
+            Ok(true) => (),
+            Ok(false) => {
+                return HttpResponse::FailedDependency().finish();
+            }
+            Err(e) => {
+                log::warn!(
+                    "Resolving challenge of {domain} failed: {e}",
+                    domain = custom_domain.domain
+                );
+                return HttpResponse::ServiceUnavailable().finish();
+            }
+        }
+
+        if let Err(e) = custom_domain.verify(&database).await {
+            log::error!("COULD NOT VERIFY CUSTOM DOMAIN {custom_domain:?}: {e}");
+            return HttpResponse::InternalServerError().finish();
+        }
+    }
+
+    let server = match DatabaseTokenizedServer::get_by_collection_token_id(
+        &database,
+        &collection,
+        &chain,
+        &token_id,
+    )
+    .await
+    {
+        Ok(Some(server)) => server,
+        Ok(None) => {
+            return HttpResponse::BadRequest().finish();
+        }
+        Err(e) => {
+            log::error!("Fetching tokenized server {collection}@{chain}@{token_id}: {e}");
+            return HttpResponse::InternalServerError().finish();
+        }
+    };
+    update_custom_domains(&database, &server).await;
+
+    HttpResponse::Ok().finish()
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct DomainRemove {
+    pub domain: String,
+    pub owner_signature: String,
+    #[serde(flatten)]
+    pub freshness: Freshness,
+}
+#[post("/ownaiv1/{chain}/{token_id}/domain/remove")]
+async fn post_domain_remove(
+    database: web::Data<Database>,
+    provider: web::Data<DynProvider>,
+    path: web::Path<(String, String)>,
+    data: web::Json<DomainRemove>,
+) -> impl Responder {
+    let (chain, token_id) = path.into_inner();
+    let collection = Collection::OwnAIv1.to_string();
+
+    let server = match DatabaseTokenizedServer::get_by_collection_token_id(
+        &database,
+        &collection,
+        &chain,
+        &token_id,
+    )
+    .await
+    {
+        Ok(server) => match server {
+            Some(server) => server,
+            None => {
+                return HttpResponse::BadRequest().finish();
+            }
+        },
+        Err(_e) => {
+            return HttpResponse::BadRequest().finish();
+        }
+    };
+
+    let message = format!(
+        "Remove domain {domain} from {collection}@{chain}@{token_id}",
+        domain = data.domain
+    );
+    if !validate_fresh_signature(
+        provider.get_ref(),
+        &database,
+        &server.owner,
+        &message,
+        &data.owner_signature,
+        &data.freshness,
+    )
+    .await
+    {
+        return HttpResponse::Unauthorized().finish();
+    }
+
+    let custom_domain = match DatabaseCustomDomain::get_by_domain(&database, &data.domain).await {
+        Ok(Some(custom_domain))
+            if custom_domain.collection == collection
+                && custom_domain.chain == chain
+                && custom_domain.token_id == token_id =>
+        {
+            custom_domain
+        }
+        Ok(_) => {
+            return HttpResponse::BadRequest().finish();
+        }
+        Err(e) => {
+            log::error!("Fetching custom domain {domain}: {e}", domain = data.domain);
+            return HttpResponse::InternalServerError().finish();
+        }
+    };
+    if let Err(e) = custom_domain.delete(&database).await {
+        log::error!("COULD NOT DELETE CUSTOM DOMAIN {custom_domain:?}: {e}");
+        return HttpResponse::InternalServerError().finish();
+    }
+
+    if custom_domain.verified {
+        update_custom_domains(&database, &server).await;
+    }
+
+    HttpResponse::Ok().finish()
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct PowerActionRequest {
+    pub owner_signature: String,
+    #[serde(flatten)]
+    pub freshness: Freshness,
+}
+#[post("/ownaiv1/{chain}/{token_id}/{action:reboot|stop|start|reinstall}")]
+async fn post_power_action(
+    database: web::Data<Database>,
+    provider: web::Data<DynProvider>,
+    stock: web::Data<StockClient>,
+    path: web::Path<(String, String, String)>,
+    data: web::Json<PowerActionRequest>,
+) -> impl Responder {
+    let (chain, token_id, action) = path.into_inner();
+    let collection = Collection::OwnAIv1.to_string();
+    let action = match PowerAction::from_str(&action) {
+        Ok(action) => action,
+        Err(_e) => {
+            return HttpResponse::BadRequest().finish();
+        }
+    };
+
+    let server = match DatabaseTokenizedServer::get_by_collection_token_id(
+        &database,
+        &collection,
+        &chain,
+        &token_id,
+    )
+    .await
+    {
+        Ok(server) => match server {
+            Some(server) => server,
+            None => {
+                return HttpResponse::BadRequest().finish();
+            }
+        },
+        Err(_e) => {
+            return HttpResponse::BadRequest().finish();
+        }
+    };
+
+    let message = action.message(&collection, &chain, &token_id);
+    if !validate_fresh_signature(
+        provider.get_ref(),
+        &database,
+        &server.owner,
+        &message,
+        &data.owner_signature,
+        &data.freshness,
+    )
+    .await
+    {
+        return HttpResponse::Unauthorized().finish();
+    }
+
+    if server.deployment.is_none() {
+        return HttpResponse::BadRequest().finish();
+    }
+
+    let now = get_time_i64();
+    let claim = DatabasePowerAction {
+        id: 0,
+        collection: collection.clone(),
+        chain: chain.clone(),
+        token_id: token_id.clone(),
+        action: action.to_string(),
+        account: server.owner.clone(),
+        success: None,
+        error: None,
+        date: now,
+    };
+    let id = match claim.claim(&database, now - poweractioncooldown()).await {
+        Ok(Some(id)) => id,
+        Ok(None) => {
+            return HttpResponse::TooManyRequests().finish();
+        }
+        Err(e) => {
+            log::error!("COULD NOT CLAIM POWER ACTION {claim:?}: {e}");
+            return HttpResponse::InternalServerError().finish();
+        }
+    };
+
+    match execute_power_action(&database, &stock, server, &action, id).await {
+        Ok(()) => HttpResponse::Ok().finish(),
+        Err(PowerError::NotDeployed) => HttpResponse::BadRequest().finish(),
+        Err(e) => {
+            log::warn!("Power action {id} failed: {e}");
+            HttpResponse::FailedDependency().finish()
+        }
+    }
+}
+
+#[get("/ownaiv1/{chain}/{token_id}/actions")]
+async fn get_actions(
+    database: web::Data<Database>,
+    path: web::Path<(String, String)>,
+) -> impl Responder {
+    let (chain, token_id) = path.into_inner();
+    let collection = Collection::OwnAIv1.to_string();
+
+    match DatabasePowerAction::get_all_by_collection_token_id(
+        &database,
+        &collection,
+        &chain,
+        &token_id,
+    )
+    .await
+    {
+        Ok(actions) => HttpResponse::Ok().json(actions),
+        Err(e) => {
+            log::error!("Fetching power actions of {collection}@{chain}@{token_id}: {e}");
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ServerHealth {
+    /// up, down or unknown (no samples)
+    pub status: String,
+    pub latest: Option<DatabaseHealthSample>,
+    /// Fraction of healthy samples in the last 30 days
+    pub uptime: Option<f64>,
+}
+#[get("/ownaiv1/{chain}/{token_id}/health")]
+async fn get_health(
+    database: web::Data<Database>,
+    path: web::Path<(String, String)>,
+) -> impl Responder {
+    let (chain, token_id) = path.into_inner();
+    let collection = Collection::OwnAIv1.to_string();
+
+    let latest = match DatabaseHealthSample::get_latest_by_collection_token_id(
+        &database,
+        &collection,
+        &chain,
+        &token_id,
+        1,
+    )
+    .await
+    {
+        Ok(mut samples) => samples.pop(),
+        Err(e) => {
+            log::error!("Fetching health samples of {collection}@{chain}@{token_id}: {e}");
+            return HttpResponse::InternalServerError().finish();
+        }
+    };
+    let now = get_time_i64();
+    let uptime = match DatabaseHealthSample::get_uptime_by_collection_token_id(
+        &database,
+        &collection,
+        &chain,
+        &token_id,
+        now - UPTIME_PERIOD,
+        now,
+    )
+    .await
+    {
+        Ok(uptime) => {
+            if uptime.total == 0 {
+                None
+            } else {
+                Some(uptime.healthy as f64 / uptime.total as f64)
+            }
+        }
+        Err(e) => {
+            log::error!("Fetching uptime of {collection}@{chain}@{token_id}: {e}");
+            return HttpResponse::InternalServerError().finish();
+        }
+    };
+
+    HttpResponse::Ok().json(ServerHealth {
+        status: match &latest {
+            Some(sample) if sample.healthy => "up",
+            Some(_) => "down",
+            None => "unknown",
+        }
+        .to_string(),
+        latest,
+        uptime,
+    })
+}
+
+#[get("/ownaiv1/{chain}/{token_id}/heals")]
+async fn get_heals(
+    database: web::Data<Database>,
+    path: web::Path<(String, String)>,
+) -> impl Responder {
+    let (chain, token_id) = path.into_inner();
+    let collection = Collection::OwnAIv1.to_string();
+
+    match DatabaseHealEvent::get_all_by_collection_token_id(
+        &database,
+        &collection,
+        &chain,
+        &token_id,
+    )
+    .await
+    {
+        Ok(heals) => HttpResponse::Ok().json(heals),
+        Err(e) => {
+            log::error!("Fetching heal events of {collection}@{chain}@{token_id}: {e}");
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ExpiresExtend {
+    pub months: i64,
+    pub payer_address: String,
+    pub payer_signature: String,
+    #[serde(flatten)]
+    pub freshness: Freshness,
+}
+#[post("/ownaiv1/{chain}/{token_id}/expires")]
+async fn post_expires(
+    database: web::Data<Database>,
+    provider: web::Data<DynProvider>,
+    path: web::Path<(String, String)>,
+    data: web::Json<ExpiresExtend>,
+) -> impl Responder {
+    let (chain, token_id) = path.into_inner();
+    let collection = Collection::OwnAIv1.to_string();
+
+    let mut server = match DatabaseTokenizedServer::get_by_collection_token_id(
+        &database,
+        &collection,
+        &chain,
+        &token_id,
+    )
+    .await
+    {
+        Ok(server) => match server {
+            Some(server) => server,
+            None => {
+                return HttpResponse::BadRequest().finish();
+            }
+        },
+        Err(_e) => {
+            return HttpResponse::BadRequest().finish();
+        }
+    };
+
+    if server.deployment.is_none() {
+        // Redeployment of expired servers not possible
+        return HttpResponse::BadRequest().finish();
+    }
+
+    let message = format!(
+        "Extend expiry of {collection}@{chain}@{token_id} by {months} months",
+        months = data.months
+    );
+    let typed = ExtendExpiry {
+        collection: collection.clone(),
+        chain: chain.clone(),
+        tokenId: token_id.clone(),
+        months: data.months,
+        nonce: data.freshness.nonce.clone(),
+        timestamp: data.freshness.timestamp,
+    };
+    if !validate_fresh_typed_signature(
+        provider.get_ref(),
+        &database,
+        &data.payer_address,
+        &typed,
+        Some(&message),
+        &data.payer_signature,
+        &data.freshness,
+    )
+    .await
+    {
+        return HttpResponse::Unauthorized().finish();
+    }
+
+    if let Err(response) =
+        ensure_not_denied(&database, &data.payer_address, DenylistScope::Credits).await
+    {
+        return response;
+    }
+
+    let tier = match get_tier(server.tier.as_deref()) {
+        Some(tier) => tier,
+        None => {
+            log::error!(
+                "UNKNOWN TIER {tier:?} OF {collection}@{chain}@{token_id}",
+                tier = server.tier
+            );
+            return HttpResponse::InternalServerError().finish();
+        }
+    };
+    if let Err(_e) = (DatabaseCredits {
+        account: data.payer_address.clone(),
+        credits: -tier.price * data.months,
+        description: format!(
+            "Extend expiry of {collection}@{chain}@{token_id} by {months} months",
+            months = data.months
+        ),
+        date: get_time_i64(),
+    })
+    .insert(&database)
+    .await
+    {
+        return HttpResponse::PaymentRequired().finish();
+    }
+
+    let one_month = 30 * 24 * 60 * 60; // 1 month in seconds
+    if let Err(e) = server
+        .update_expires(&database, server.expires + data.months * one_month)
+        .await
+    {
+        log::error!(
+            "COULD NOT EXTEND TOKENIZED SERVER EXPIRES {collection}@{chain}@{token_id} BY {months} MONTHS: {e}",
+            months = data.months
+        );
+        return HttpResponse::InternalServerError().finish();
+    }
+
+    HttpResponse::Ok().finish()
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct AutoRenew {
+    pub payer: String,
+    pub max_months: i64,
+    pub renewed_months: i64,
+}
+#[get("/ownaiv1/{chain}/{token_id}/auto_renew")]
+async fn get_auto_renew(
+    database: web::Data<Database>,
+    path: web::Path<(String, String)>,
+) -> impl Responder {
+    let (chain, token_id) = path.into_inner();
+    let collection = Collection::OwnAIv1.to_string();
+
+    match DatabaseAutoRenew::get_by_collection_token_id(&database, &collection, &chain, &token_id)
+        .await
+    {
+        Ok(auto_renew) => HttpResponse::Ok().json(auto_renew.map(|auto_renew| AutoRenew {
+            payer: auto_renew.payer,
+            max_months: auto_renew.max_months,
+            renewed_months: auto_renew.renewed_months,
+        })),
+        Err(e) => {
+            log::error!("Fetching auto renew for {collection}@{chain}@{token_id}: {e}");
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct AutoRenewAuthorization {
+    pub max_months: i64,
+    pub payer_address: String,
+    pub owner_signature: String,
+    pub payer_signature: String,
+    #[serde(flatten)]
+    pub freshness: Freshness,
+}
+#[post("/ownaiv1/{chain}/{token_id}/auto_renew")]
+async fn post_auto_renew(
+    database: web::Data<Database>,
+    provider: web::Data<DynProvider>,
+    path: web::Path<(String, String)>,
+    data: web::Json<AutoRenewAuthorization>,
+) -> impl Responder {
+    let (chain, token_id) = path.into_inner();
+    let collection = Collection::OwnAIv1.to_string();
+
+    if data.max_months <= 0 {
+        return HttpResponse::BadRequest().finish();
+    }
+
+    let server = match DatabaseTokenizedServer::get_by_collection_token_id(
+        &database,
+        &collection,
+        &chain,
+        &token_id,
+    )
+    .await
+    {
+        Ok(server) => match server {
+            Some(server) => server,
+            None => {
+                return HttpResponse::BadRequest().finish();
+            }
+        },
+        Err(_e) => {
+            return HttpResponse::BadRequest().finish();
+        }
+    };
+
+    let message = format!(
+        "Auto renew {collection}@{chain}@{token_id} for up to {max_months} months paid by {payer}",
+        max_months = data.max_months,
+        payer = data.payer_address
+    );
+    let typed = AuthorizeAutoRenew {
+        collection: collection.clone(),
+        chain: chain.clone(),
+        tokenId: token_id.clone(),
+        maxMonths: data.max_months,
+        payer: data.payer_address.clone(),
+        nonce: data.freshness.nonce.clone(),
+        timestamp: data.freshness.timestamp,
+    };
+    // A self-paid renewal is signed once, as every signature can only be used once
+    let self_paid = data.payer_address.eq_ignore_ascii_case(&server.owner);
+    if !validate_fresh_typed_signature(
+        provider.get_ref(),
+        &database,
+        &server.owner,
+        &typed,
+        Some(&message),
+        &data.owner_signature,
+        &data.freshness,
+    )
+    .await
+        || (!self_paid
+            && !validate_fresh_typed_signature(
+                provider.get_ref(),
+                &database,
+                &data.payer_address,
+                &typed,
+                Some(&message),
+                &data.payer_signature,
+                &data.freshness,
+            )
+            .await)
+    {
+        return HttpResponse::Unauthorized().finish();
+    }
+
+    if let Err(response) =
+        ensure_not_denied(&database, &data.payer_address, DenylistScope::Credits).await
+    {
+        return response;
+    }
+
+    let auto_renew = DatabaseAutoRenew {
+        collection,
+        chain,
+        token_id,
+        payer: data.payer_address.clone(),
+        max_months: data.max_months,
+        renewed_months: 0,
+        owner_signature: data.owner_signature.clone(),
+        // The payer signature of a self-paid renewal is never validated, the owner signature covers it
+        payer_signature: if self_paid {
+            data.owner_signature.clone()
+        } else {
+            data.payer_signature.clone()
+        },
+        failed_expires: None,
+    };
+    if let Err(e) = auto_renew.upsert(&database).await {
+        log::error!("COULD NOT INSERT AUTO RENEW {auto_renew:?}: {e}");
+        return HttpResponse::InternalServerError().finish();
+    }
+
+    HttpResponse::Ok().finish()
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct AutoRenewCancel {
+    pub account: String,
+    pub signature: String,
+    #[serde(flatten)]
+    pub freshness: Freshness,
+}
+#[post("/ownaiv1/{chain}/{token_id}/auto_renew/cancel")]
+async fn post_auto_renew_cancel(
+    database: web::Data<Database>,
+    provider: web::Data<DynProvider>,
+    path: web::Path<(String, String)>,
+    data: web::Json<AutoRenewCancel>,
+) -> impl Responder {
+    let (chain, token_id) = path.into_inner();
+    let collection = Collection::OwnAIv1.to_string();
+
+    let auto_renew = match DatabaseAutoRenew::get_by_collection_token_id(
+        &database,
+        &collection,
+        &chain,
+        &token_id,
+    )
+    .await
+    {
+        Ok(auto_renew) => match auto_renew {
+            Some(auto_renew) => auto_renew,
+            None => {
+                return HttpResponse::BadRequest().finish();
+            }
+        },
+        Err(_e) => {
+            return HttpResponse::BadRequest().finish();
+        }
+    };
+    let owner = match DatabaseTokenizedServer::get_by_collection_token_id(
+        &database,
+        &collection,
+        &chain,
+        &token_id,
+    )
+    .await
+    {
+        Ok(server) => server.map(|server| server.owner),
+        Err(_e) => {
+            return HttpResponse::BadRequest().finish();
+        }
+    };
+
+    // Both the owner and the payer can stop future renewals
+    if data.account != auto_renew.payer && Some(&data.account) != owner.as_ref() {
+        return HttpResponse::Unauthorized().finish();
+    }
+
+    let message = format!("Cancel auto renew of {collection}@{chain}@{token_id}");
+    if !validate_fresh_signature(
+        provider.get_ref(),
+        &database,
+        &data.account,
+        &message,
+        &data.signature,
+        &data.freshness,
+    )
+    .await
+    {
+        return HttpResponse::Unauthorized().finish();
+    }
+
+    if let Err(e) = DatabaseAutoRenew::delete(&database, &collection, &chain, &token_id).await {
+        log::error!("COULD NOT DELETE AUTO RENEW OF {collection}@{chain}@{token_id}: {e}");
+        return HttpResponse::InternalServerError().finish();
+    }
+
+    HttpResponse::Ok().finish()
+}
+
+#[get("/ownaiv1/{chain}/price")]
+async fn get_price() -> impl Responder {
+    match get_tier(None) {
+        Some(tier) => HttpResponse::Ok().json(tier.price),
+        None => HttpResponse::InternalServerError().finish(),
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ModelAvailability {
+    pub model: String,
+    pub flavor: String,
+    pub available: u64,
+}
+#[derive(Serialize, Deserialize)]
+pub struct RegionAvailability {
+    pub region: String,
+    pub models: Vec<ModelAvailability>,
+}
+#[get("/ownaiv1/{chain}/available")]
+async fn get_available(stock: web::Data<StockClient>) -> impl Responder {
+    let mut regions: Vec<RegionAvailability> = vec![];
+    for target in get_deployment_targets() {
+        let available = match target.available(&stock).await {
+            Ok(available) => available,
+            Err(e) => {
+                log::error!("Fetching availability of {target:?}: {e}");
+                return HttpResponse::ServiceUnavailable().finish();
+            }
+        };
+        let model = ModelAvailability {
+            model: target.flavor.model,
+            flavor: target.flavor.name,
+            available,
+        };
+        match regions
+            .iter_mut()
+            .find(|region| region.region == target.region.name)
+        {
+            Some(region) => region.models.push(model),
+            None => regions.push(RegionAvailability {
+                region: target.region.name,
+                models: vec![model],
+            }),
+        }
+    }
+
+    HttpResponse::Ok().json(regions)
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct PublicTier {
+    pub id: String,
+    pub name: String,
+    pub flavor: String,
+    pub regions: Vec<String>,
+    pub price: i64,
+    /// None when the stock could not be checked
+    pub available: Option<u64>,
+}
+#[get("/ownaiv1/{chain}/tiers")]
+async fn get_tiers(database: web::Data<Database>, stock: web::Data<StockClient>) -> impl Responder {
+    let mut tiers = vec![];
+    for tier in tier::get_tiers() {
+        tiers.push(PublicTier {
+            available: tier.available(&database, &stock).await.ok(),
+            id: tier.id,
+            name: tier.name,
+            flavor: tier.flavor,
+            regions: tier.regions,
+            price: tier.price,
+        });
+    }
+
+    HttpResponse::Ok().json(tiers)
+}
+
+#[get("/ownaiv1/{chain}/presets")]
+async fn get_presets() -> impl Responder {
+    HttpResponse::Ok().json(preset::get_presets())
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct PresetSwitch {
+    pub preset: String,
+    pub preset_parameters: Option<BTreeMap<String, String>>,
+    pub owner_signature: String,
+    #[serde(flatten)]
+    pub freshness: Freshness,
+}
+#[post("/ownaiv1/{chain}/{token_id}/preset")]
+async fn post_preset(
+    database: web::Data<Database>,
+    provider: web::Data<DynProvider>,
+    path: web::Path<(String, String)>,
+    data: web::Json<PresetSwitch>,
+) -> impl Responder {
+    let (chain, token_id) = path.into_inner();
+    let collection = Collection::OwnAIv1.to_string();
+
+    let mut server = match DatabaseTokenizedServer::get_by_collection_token_id(
+        &database,
+        &collection,
+        &chain,
+        &token_id,
+    )
+    .await
+    {
+        Ok(server) => match server {
+            Some(server) => server,
+            None => {
+                return HttpResponse::BadRequest().finish();
+            }
+        },
+        Err(_e) => {
+            return HttpResponse::BadRequest().finish();
+        }
+    };
+
+    let preset = match get_preset(Some(&data.preset)) {
+        Some(preset) => preset,
+        None => {
+            return HttpResponse::BadRequest().finish();
+        }
+    };
+    let preset_parameters = data.preset_parameters.clone().unwrap_or_default();
+    let message = format!(
+        "Switch {collection}@{chain}@{token_id} to preset {preset} with {parameters}",
+        preset = preset.id,
+        parameters = serde_json::to_string(&preset_parameters).unwrap_or_default()
+    );
+    if !validate_fresh_signature(
+        provider.get_ref(),
+        &database,
+        &server.owner,
+        &message,
+        &data.owner_signature,
+        &data.freshness,
+    )
+    .await
+    {
+        return HttpResponse::Unauthorized().finish();
+    }
+
+    let preset_parameters = match preset.resolve_parameters(&preset_parameters) {
+        Ok(preset_parameters) => preset_parameters,
+        Err(_e) => {
+            return HttpResponse::BadRequest().finish();
+        }
+    };
+    let domain = server_domain(&server);
+    let app_config = match preset.config(&domain, &preset_parameters) {
+        Ok(app_config) => app_config,
+        Err(_e) => {
+            return HttpResponse::BadRequest().finish();
+        }
+    };
+
+    let xnode = match ControlledXnode::new(
+        database.get_ref().clone(),
+        collection.clone(),
+        chain.clone(),
+        token_id.clone(),
+    )
+    .await
+    {
+        Ok(xnode) => xnode,
+        Err(e) => {
+            log::error!("CREATE XNODE SESSION FOR {collection}@{chain}@{token_id} FAILED: {e:?}");
+            return HttpResponse::FailedDependency().finish();
+        }
+    };
+    if let Err(e) = xnode
+        .set_config_block(
+            "XNODE APP PRESET",
+            app_config,
+            Some(&legacy_app_config(&domain)),
+        )
+        .await
+    {
+        log::error!(
+            "XNODE MANAGER UPDATE OF APP PRESET {preset} FOR {collection}@{chain}@{token_id} FAILED: {e:?}",
+            preset = preset.id
+        );
+        return HttpResponse::FailedDependency().finish();
+    }
+
+    if let Err(e) = server
+        .update_preset(&database, preset.id.clone(), Json(preset_parameters))
+        .await
+    {
+        log::error!(
+            "DATABASE UPDATE OF APP PRESET {preset} FOR {collection}@{chain}@{token_id} FAILED: {e}",
+            preset = preset.id
+        );
+        return HttpResponse::InternalServerError().finish();
+    }
+
+    // Custom domains forward to the app of the preset
+    if let Err(e) = apply_custom_domains(&database, &xnode, &server).await {
+        log::error!(
+            "XNODE MANAGER UPDATE OF CUSTOM DOMAINS FOR {collection}@{chain}@{token_id} FAILED: {e:?}"
+        );
+    }
+
+    HttpResponse::Ok().finish()
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct Mint {
+    pub to: String,
+    pub tier: Option<String>,
+    pub preset: Option<String>,
+    pub preset_parameters: Option<BTreeMap<String, String>>,
+    pub payer_address: String,
+    pub payer_signature: String,
+    #[serde(flatten)]
+    pub freshness: Freshness,
+}
+#[post("/ownaiv1/{chain}/mint")]
+async fn post_mint(
+    database: web::Data<Database>,
+    provider: web::Data<DynProvider>,
+    counter: web::Data<OwnAIV1TokenCounter>,
+    stock: web::Data<StockClient>,
+    path: web::Path<String>,
+    data: web::Json<Mint>,
+) -> impl Responder {
+    let to = match Address::parse_checksummed(&data.to, None) {
+        Ok(account) => account,
+        Err(_e) => {
+            return HttpResponse::BadRequest().finish();
+        }
+    };
+
+    let tier = match get_tier(data.tier.as_deref()) {
+        Some(tier) => tier,
+        None => {
+            return HttpResponse::BadRequest().finish();
+        }
+    };
+
+    let preset = match get_preset(data.preset.as_deref()) {
+        Some(preset) => preset,
+        None => {
+            return HttpResponse::BadRequest().finish();
+        }
+    };
+    let preset_parameters =
+        match preset.resolve_parameters(&data.preset_parameters.clone().unwrap_or_default()) {
+            Ok(preset_parameters) => preset_parameters,
+            Err(_e) => {
+                return HttpResponse::BadRequest().finish();
+            }
+        };
+
+    let chain = path.into_inner();
+    if chain != Chain::Base.to_string() {
+        return HttpResponse::BadRequest().finish();
+    }
+    let collection = Collection::OwnAIv1.to_string();
+
+    let available = match tier.available(&database, &stock).await {
+        Ok(available) => available,
+        Err(_e) => {
+            return HttpResponse::ServiceUnavailable().finish();
+        }
+    };
+    let waiting = match DatabaseReservation::get_waiting_count_by_tier(
+        &database,
+        &collection,
+        &chain,
+        &tier.id,
+    )
+    .await
+    {
+        Ok(waiting) => waiting,
+        Err(e) => {
+            log::error!(
+                "Fetching waiting reservations for {tier}: {e}",
+                tier = tier.id
+            );
+            return HttpResponse::InternalServerError().finish();
+        }
+    };
+    // Reservations in the waitlist go first
+    if available <= waiting.unsigned_abs() {
+        return HttpResponse::FailedDependency().finish();
+    }
+
+    // Preset parameters end up in the OS config, so they are part of the signature
+    let parameters = serde_json::to_string(&data.preset_parameters.clone().unwrap_or_default())
+        .unwrap_or_default();
+    let message = match &data.tier {
+        Some(tier) => format!(
+            "Mint new {tier} {collection}@{chain} to {to} with preset {preset} with {parameters}",
+            to = data.to,
+            preset = preset.id
+        ),
+        None => format!(
+            "Mint new {collection}@{chain} to {to} with preset {preset} with {parameters}",
+            to = data.to,
+            preset = preset.id
+        ),
+    };
+    let typed = MintServer {
+        collection: collection.clone(),
+        chain: chain.clone(),
+        tier: data.tier.clone().unwrap_or_default(),
+        to: data.to.clone(),
+        preset: preset.id.clone(),
+        presetParameters: parameters,
+        nonce: data.freshness.nonce.clone(),
+        timestamp: data.freshness.timestamp,
+    };
+    if !validate_fresh_typed_signature(
+        provider.get_ref(),
+        &database,
+        &data.payer_address,
+        &typed,
+        Some(&message),
+        &data.payer_signature,
+        &data.freshness,
+    )
+    .await
+    {
+        return HttpResponse::Unauthorized().finish();
+    }
+
+    if let Err(response) =
+        ensure_not_denied(&database, &data.payer_address, DenylistScope::Credits).await
+    {
+        return response;
+    }
+
+    if let Err(_e) = (DatabaseCredits {
+        account: data.payer_address.clone(),
+        credits: -tier.price,
+        description: format!(
+            "Mint of {tier} {collection}@{chain} to {to}",
+            tier = tier.id,
+            to = data.to
+        ),
+        date: get_time_i64(),
+    })
+    .insert(&database)
+    .await
+    {
+        return HttpResponse::PaymentRequired().finish();
+    }
+
+    match mint_v1(
+        &database,
+        provider.get_ref(),
+        &stock,
+        &counter,
+        to,
+        tier,
+        (preset, preset_parameters),
+    )
+    .await
+    {
+        Ok(token_id) => HttpResponse::Ok().json(token_id),
+        Err(_e) => HttpResponse::InternalServerError().finish(),
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct Reserve {
+    pub to: String,
+    pub tier: String,
+    pub payer_address: String,
+    pub payer_signature: String,
+    #[serde(flatten)]
+    pub freshness: Freshness,
+}
+#[post("/ownaiv1/{chain}/reserve")]
+async fn post_reserve(
+    database: web::Data<Database>,
+    provider: web::Data<DynProvider>,
+    path: web::Path<String>,
+    data: web::Json<Reserve>,
+) -> impl Responder {
+    if Address::parse_checksummed(&data.to, None).is_err() {
+        return HttpResponse::BadRequest().finish();
+    }
+
+    let tier = match get_tier(Some(&data.tier)) {
+        Some(tier) => tier,
+        None => {
+            return HttpResponse::BadRequest().finish();
+        }
+    };
+
+    let chain = path.into_inner();
+    if chain != Chain::Base.to_string() {
+        return HttpResponse::BadRequest().finish();
+    }
+    let collection = Collection::OwnAIv1.to_string();
+
+    let message = format!(
+        "Reserve {tier} {collection}@{chain} for {to}",
+        tier = tier.id,
+        to = data.to
+    );
+    if !validate_fresh_signature(
+        provider.get_ref(),
+        &database,
+        &data.payer_address,
+        &message,
+        &data.payer_signature,
+        &data.freshness,
+    )
+    .await
+    {
+        return HttpResponse::Unauthorized().finish();
+    }
+
+    if let Err(response) =
+        ensure_not_denied(&database, &data.payer_address, DenylistScope::Credits).await
+    {
+        return response;
+    }
+
+    // Hold is taken before the reservation exists, so it can never be fulfilled unpaid
+    let hold = DatabaseCredits {
+        account: data.payer_address.clone(),
+        credits: -tier.price,
+        description: format!(
+            "Hold for reservation of {tier} {collection}@{chain} to {to}",
+            tier = tier.id,
+            to = data.to
+        ),
+        date: get_time_i64(),
+    };
+    if let Err(_e) = hold.insert(&database).await {
+        return HttpResponse::PaymentRequired().finish();
+    }
+
+    let mut reservation = DatabaseReservation {
+        id: 0,
+        collection,
+        chain,
+        tier: tier.id,
+        to_account: data.to.clone(),
+        payer: data.payer_address.clone(),
+        payer_signature: data.payer_signature.clone(),
+        hold: tier.price,
+        status: ReservationStatus::Waiting.to_string(),
+        token_id: None,
+        created_at: get_time_i64(),
+    };
+    if let Err(e) = reservation.insert(&database).await {
+        log::error!("COULD NOT INSERT RESERVATION {reservation:?}: {e}");
+        let release = DatabaseCredits {
+            account: hold.account,
+            credits: -hold.credits,
+            description: format!("Release of {description}", description = hold.description),
+            date: get_time_i64(),
+        };
+        if let Err(e) = release.insert(&database).await {
+            log::error!("COULD NOT INSERT CREDITS {release:?}: {e}");
+        }
+        return HttpResponse::InternalServerError().finish();
+    }
+
+    HttpResponse::Ok().json(reservation.id)
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct Reservation {
+    pub id: i32,
+    pub tier: String,
+    pub to: String,
+    pub payer: String,
+    pub hold: i64,
+    pub status: String,
+    pub token_id: Option<String>,
+    pub created_at: i64,
+    /// Only set while waiting
+    pub position: Option<i64>,
+}
+#[get("/ownaiv1/{chain}/reservation/{id}")]
+async fn get_reservation(
+    database: web::Data<Database>,
+    path: web::Path<(String, i32)>,
+) -> impl Responder {
+    let (_chain, id) = path.into_inner();
+
+    let reservation = match DatabaseReservation::get_by_id(&database, id).await {
+        Ok(reservation) => match reservation {
+            Some(reservation) => reservation,
+            None => {
+                return HttpResponse::NotFound().finish();
+            }
+        },
+        Err(e) => {
+            log::error!("Fetching reservation {id}: {e}");
+            return HttpResponse::InternalServerError().finish();
+        }
+    };
+
+    let position = if reservation.status == ReservationStatus::Waiting.to_string() {
+        match reservation.get_position(&database).await {
+            Ok(position) => Some(position),
+            Err(e) => {
+                log::error!("Fetching position of reservation {id}: {e}");
+                return HttpResponse::InternalServerError().finish();
+            }
+        }
+    } else {
+        None
+    };
+
+    HttpResponse::Ok().json(Reservation {
+        id: reservation.id,
+        tier: reservation.tier,
+        to: reservation.to_account,
+        payer: reservation.payer,
+        hold: reservation.hold,
+        status: reservation.status,
+        token_id: reservation.token_id,
+        created_at: reservation.created_at,
+        position,
+    })
+}
+
+#[get("/ownaiv1/{to}/reservations")]
+async fn get_reservations(
+    database: web::Data<Database>,
+    path: web::Path<String>,
+) -> impl Responder {
+    let to = path.into_inner();
+    match DatabaseReservation::get_all_by_to_account(&database, &to).await {
+        Ok(reservations) => HttpResponse::Ok().json(reservations),
+        Err(e) => {
+            log::error!("Fetching reservations for {to}: {e}");
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ReservationCancel {
+    pub payer_signature: String,
+    #[serde(flatten)]
+    pub freshness: Freshness,
+}
+#[post("/ownaiv1/{chain}/reservation/{id}/cancel")]
+async fn post_reservation_cancel(
+    database: web::Data<Database>,
+    provider: web::Data<DynProvider>,
+    path: web::Path<(String, i32)>,
+    data: web::Json<ReservationCancel>,
+) -> impl Responder {
+    let (_chain, id) = path.into_inner();
+
+    let mut reservation = match DatabaseReservation::get_by_id(&database, id).await {
+        Ok(reservation) => match reservation {
+            Some(reservation) => reservation,
+            None => {
+                return HttpResponse::NotFound().finish();
+            }
+        },
+        Err(e) => {
+            log::error!("Fetching reservation {id}: {e}");
+            return HttpResponse::InternalServerError().finish();
+        }
+    };
+
+    let message = format!("Cancel reservation {id}");
+    if !validate_fresh_signature(
+        provider.get_ref(),
+        &database,
+        &reservation.payer,
+        &message,
+        &data.payer_signature,
+        &data.freshness,
+    )
+    .await
+    {
+        return HttpResponse::Unauthorized().finish();
+    }
+
+    // Cancellation and hold release are written together, a failed release keeps the reservation waiting
+    let mut transaction = match database.begin().await {
+        Ok(transaction) => transaction,
+        Err(e) => {
+            log::error!("COULD NOT START CANCELLATION OF RESERVATION {id}: {e}");
+            return HttpResponse::InternalServerError().finish();
+        }
+    };
+    match reservation.cancel_transaction(&mut transaction).await {
+        Ok(true) => (),
+        Ok(false) => {
+            // Already minted or cancelled
+            return HttpResponse::BadRequest().finish();
+        }
+        Err(e) => {
+            log::error!("COULD NOT CANCEL RESERVATION {id}: {e}");
+            return HttpResponse::InternalServerError().finish();
+        }
+    }
+
+    let credits = DatabaseCredits {
+        account: reservation.payer.clone(),
+        credits: reservation.hold,
+        description: format!("Release of hold for cancelled reservation {id}"),
+        date: get_time_i64(),
+    };
+    if let Err(e) = credits.insert_transaction(&mut transaction).await {
+        log::error!("COULD NOT INSERT CREDITS {credits:?}: {e}");
+        return HttpResponse::InternalServerError().finish();
+    }
+
+    if let Err(e) = transaction.commit().await {
+        log::error!("COULD NOT COMMIT CANCELLATION OF RESERVATION {id}: {e}");
+        return HttpResponse::InternalServerError().finish();
+    }
+
+    HttpResponse::Ok().finish()
 }
 
 #[get("/ownaiv1/{chain}/active")]
@@ -3,20 +3,65 @@ use alloy::providers::DynProvider;
 use serde::{Deserialize, Serialize};
 
 use crate::{
+    api::denylist::ensure_not_denied,
     database::{Database, credits::DatabaseCredits, promo_code::DatabasePromoCode},
-    utils::{env::promocodesigner, signature_validator::validate_signature},
+    utils::{
+        admin_signer::{AdminRole, validate_admin_signature},
+        denylist::DenylistScope,
+        promo_code::{PromoCode, add_promo_codes},
+        proposal::proposal_threshold,
+        replay::{Freshness, legacy_signatures_accepted, validate_fresh_typed_signature},
+        typed_data::RedeemPromoCode,
+    },
 };
 
 #[derive(Serialize, Deserialize)]
 pub struct PromoCodeRedeem {
     pub code: String,
     pub account: String,
+    /// EIP-712 signature of the account, required once legacy signatures are no longer accepted
+    pub signature: Option<String>,
+    #[serde(flatten)]
+    pub freshness: Option<Freshness>,
 }
 #[post("/promo_code/redeem")]
 async fn post_redeem(
     database: web::Data<Database>,
+    provider: web::Data<DynProvider>,
     data: web::Json<PromoCodeRedeem>,
 ) -> impl Responder {
+    match (&data.signature, &data.freshness) {
+        (Some(signature), Some(freshness)) => {
+            let typed = RedeemPromoCode {
+                code: data.code.clone(),
+                account: data.account.clone(),
+                nonce: freshness.nonce.clone(),
+                timestamp: freshness.timestamp,
+            };
+            if !validate_fresh_typed_signature(
+                provider.get_ref(),
+                &database,
+                &data.account,
+                &typed,
+                None,
+                signature,
+                freshness,
+            )
+            .await
+            {
+                return HttpResponse::Unauthorized().finish();
+            }
+        }
+        (None, None) if legacy_signatures_accepted() => (),
+        _ => {
+            return HttpResponse::BadRequest().finish();
+        }
+    }
+
+    if let Err(response) = ensure_not_denied(&database, &data.account, DenylistScope::Promo).await {
+        return response;
+    }
+
     let mut code = match DatabasePromoCode::get_unredeemed_by_code(&database, &data.code).await {
         Ok(code) => match code {
             Some(code) => code,
@@ -51,16 +96,12 @@ async fn post_redeem(
     HttpResponse::Ok().finish()
 }
 
-#[derive(Serialize, Deserialize)]
-pub struct PromoCode {
-    pub code: String,
-    pub credits: i64,
-    pub description: String,
-}
 #[derive(Serialize, Deserialize)]
 pub struct PromoCodessSignature {
     pub promo_codes: String,
     pub signature: String,
+    #[serde(flatten)]
+    pub freshness: Freshness,
 }
 #[post("/promo_code/add")]
 async fn post_add(
@@ -68,13 +109,21 @@ async fn post_add(
     provider: web::Data<DynProvider>,
     data: web::Json<PromoCodessSignature>,
 ) -> impl Responder {
-    if !validate_signature(
+    if proposal_threshold(AdminRole::PromoCode) > 1 {
+        // Needs approval from multiple signers through a proposal
+        return HttpResponse::Forbidden().finish();
+    }
+
+    if validate_admin_signature(
         provider.get_ref(),
-        &promocodesigner(),
+        &database,
+        AdminRole::PromoCode,
         &data.promo_codes,
         &data.signature,
+        &data.freshness,
     )
     .await
+    .is_none()
     {
         return HttpResponse::Unauthorized().finish();
     }
@@ -86,17 +135,7 @@ async fn post_add(
         }
     };
 
-    for code in &promo_codes {
-        let code = DatabasePromoCode {
-            code: code.code.clone(),
-            credits: code.credits,
-            description: code.description.clone(),
-            redeemed_by: None,
-        };
-        if let Err(e) = code.insert(&database).await {
-            log::error!("COULD NOT INSERT PROMO CODE {code:?}: {e}");
-        }
-    }
+    add_promo_codes(&database, &promo_codes).await;
 
     HttpResponse::Ok().finish()
 }
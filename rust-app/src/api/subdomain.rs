@@ -0,0 +1,14 @@
+use actix_web::{HttpResponse, Responder, get, web};
+
+use crate::database::{Database, subdomain::DatabaseSubdomain};
+
+#[get("/subdomains")]
+async fn get_subdomains(database: web::Data<Database>) -> impl Responder {
+    match DatabaseSubdomain::get_all(&database).await {
+        Ok(subdomains) => HttpResponse::Ok().json(subdomains),
+        Err(e) => {
+            log::error!("Fetching subdomains: {e}");
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
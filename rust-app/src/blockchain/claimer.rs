@@ -12,10 +12,18 @@ sol! {
         event TokensClaimed(address indexed account, uint256 total, uint256 released);
     }
 
+    #[derive(serde::Serialize)]
     struct Claim {
         address claimer;
         uint256 total;
     }
+
+    #[derive(serde::Serialize)]
+    struct ClaimWithDeadline {
+        address claimer;
+        uint256 total;
+        uint256 deadline;
+    }
 }
 
 pub async fn event_listeners<P: Provider>(provider: P, database: Database) {
@@ -4,6 +4,9 @@ use futures_util::StreamExt;
 use crate::{
     database::{
         Database,
+        auto_renew::DatabaseAutoRenew,
+        custom_domain::DatabaseCustomDomain,
+        server_controller::DatabaseServerController,
         tokenized_server::{Chain, Collection, DatabaseTokenizedServer},
     },
     utils::{
@@ -71,7 +74,21 @@ pub async fn event_listeners<P: Provider>(provider: P, database: Database) {
                         log::error!("COULD NOT UPDATE TOKENIZED SERVER OWNER {collection}@{chain}@{token_id} to {to}: {e}", collection = tokenized_server.collection, token_id = tokenized_server.token_id);
                         return;
                     };
+                    // Custom domains are removed from the node together with the controller update
+                    if let Err(e) = DatabaseCustomDomain::delete_by_collection_token_id(&database, &collection, &chain, &token_id.to_string()).await
+                    {
+                        log::error!("COULD NOT DELETE CUSTOM DOMAINS OF TRANSFERRED TOKENIZED SERVER {collection}@{chain}@{token_id}: {e}");
+                    }
+                    // The new owner decides who else controls the server
+                    if let Err(e) = DatabaseServerController::delete_by_collection_token_id(&database, &collection, &chain, &token_id.to_string()).await
+                    {
+                        log::error!("COULD NOT DELETE CONTROLLERS OF TRANSFERRED TOKENIZED SERVER {collection}@{chain}@{token_id}: {e}");
+                    }
                     update_controller(&database, &mut tokenized_server, address_to_xnode_user(event.to)).await;
+                    if let Err(e) = DatabaseAutoRenew::delete(&database, &collection, &chain, &token_id.to_string()).await
+                    {
+                        log::error!("COULD NOT DELETE AUTO RENEW OF TRANSFERRED TOKENIZED SERVER {collection}@{chain}@{token_id}: {e}");
+                    }
                 }
             }
             Err(e) => {
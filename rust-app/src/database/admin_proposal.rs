@@ -0,0 +1,184 @@
+use std::{fmt::Display, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+use sqlx::{Error, FromRow, query, query_as, query_scalar};
+
+use crate::database::{Database, DatabaseConnection, DatabaseTransaction};
+
+pub async fn create_table(connection: &DatabaseConnection) {
+    sqlx::raw_sql(
+        "CREATE TABLE IF NOT EXISTS admin_proposal(id SERIAL PRIMARY KEY, role TEXT NOT NULL, payload TEXT NOT NULL, proposer TEXT NOT NULL, threshold INT4 NOT NULL, status TEXT NOT NULL, error TEXT, created_at INT8 NOT NULL, expires INT8 NOT NULL, applied_at INT8); CREATE TABLE IF NOT EXISTS admin_proposal_approval(proposal_id INT4 NOT NULL REFERENCES admin_proposal(id), signer TEXT NOT NULL, signature TEXT NOT NULL, created_at INT8 NOT NULL, PRIMARY KEY (proposal_id, signer))"
+    )
+    .execute(connection)
+    .await
+    .unwrap_or_else(|e| panic!("Could not create admin_proposal tables: {e}"));
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ProposalStatus {
+    Pending,
+    Applied,
+    Failed,
+    Expired,
+}
+
+impl Display for ProposalStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ProposalStatus::Pending => f.write_str("pending"),
+            ProposalStatus::Applied => f.write_str("applied"),
+            ProposalStatus::Failed => f.write_str("failed"),
+            ProposalStatus::Expired => f.write_str("expired"),
+        }
+    }
+}
+
+impl FromStr for ProposalStatus {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "pending" => Ok(ProposalStatus::Pending),
+            "applied" => Ok(ProposalStatus::Applied),
+            "failed" => Ok(ProposalStatus::Failed),
+            "expired" => Ok(ProposalStatus::Expired),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Admin payload applied once enough signers of its role approved it.
+#[derive(Debug, FromRow, Serialize, Deserialize)]
+pub struct DatabaseAdminProposal {
+    pub id: i32,
+    pub role: String,
+    pub payload: String,
+    pub proposer: String,
+    /// Approvals required, fixed when proposed
+    pub threshold: i32,
+    pub status: String,
+    pub error: Option<String>,
+    pub created_at: i64,
+    pub expires: i64,
+    pub applied_at: Option<i64>,
+}
+
+#[derive(Debug, FromRow, Serialize, Deserialize)]
+pub struct DatabaseAdminProposalApproval {
+    pub proposal_id: i32,
+    pub signer: String,
+    pub signature: String,
+    pub created_at: i64,
+}
+
+impl DatabaseAdminProposal {
+    pub async fn get_all(database: &Database) -> Result<Vec<Self>, Error> {
+        query_as("SELECT id, role, payload, proposer, threshold, status, error, created_at, expires, applied_at FROM admin_proposal ORDER BY id DESC")
+            .fetch_all(&database.connection)
+            .await
+    }
+
+    pub async fn get_by_id(database: &Database, id: i32) -> Result<Option<Self>, Error> {
+        query_as("SELECT id, role, payload, proposer, threshold, status, error, created_at, expires, applied_at FROM admin_proposal WHERE id = $1")
+            .bind(id)
+            .fetch_optional(&database.connection)
+            .await
+    }
+
+    /// Returns the new id.
+    pub async fn insert(&self, database: &Database) -> Result<i32, Error> {
+        query_scalar("INSERT INTO admin_proposal(role, payload, proposer, threshold, status, error, created_at, expires, applied_at) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9) RETURNING id;")
+            .bind(&self.role)
+            .bind(&self.payload)
+            .bind(&self.proposer)
+            .bind(self.threshold)
+            .bind(&self.status)
+            .bind(&self.error)
+            .bind(self.created_at)
+            .bind(self.expires)
+            .bind(self.applied_at)
+            .fetch_one(&database.connection)
+            .await
+    }
+
+    /// Marks pending proposals past their expiry as expired.
+    pub async fn expire(database: &Database, now: i64) -> Result<(), Error> {
+        query("UPDATE admin_proposal SET status = $1 WHERE status = $2 AND expires <= $3;")
+            .bind(ProposalStatus::Expired.to_string())
+            .bind(ProposalStatus::Pending.to_string())
+            .bind(now)
+            .execute(&database.connection)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Moves a pending proposal to the new status, returns false when it was no longer pending (applied by another request).
+    pub async fn finish(
+        &mut self,
+        database: &Database,
+        status: ProposalStatus,
+        error: Option<String>,
+        now: i64,
+    ) -> Result<bool, Error> {
+        let finished = query("UPDATE admin_proposal SET status = $2, error = $3, applied_at = $4 WHERE id = $1 AND status = $5;")
+            .bind(self.id)
+            .bind(status.to_string())
+            .bind(&error)
+            .bind(now)
+            .bind(ProposalStatus::Pending.to_string())
+            .execute(&database.connection)
+            .await?
+            .rows_affected()
+            > 0;
+
+        if finished {
+            self.status = status.to_string();
+            self.error = error;
+            self.applied_at = Some(now);
+        }
+        Ok(finished)
+    }
+
+    /// Moves a pending proposal to applied, the row stays locked until the transaction ends.
+    pub async fn apply_transaction(
+        &self,
+        transaction: &mut DatabaseTransaction,
+        now: i64,
+    ) -> Result<bool, Error> {
+        Ok(query("UPDATE admin_proposal SET status = $2, error = NULL, applied_at = $3 WHERE id = $1 AND status = $4;")
+            .bind(self.id)
+            .bind(ProposalStatus::Applied.to_string())
+            .bind(now)
+            .bind(ProposalStatus::Pending.to_string())
+            .execute(&mut **transaction)
+            .await?
+            .rows_affected()
+            > 0)
+    }
+}
+
+impl DatabaseAdminProposalApproval {
+    pub async fn get_all_by_proposal_id(
+        database: &Database,
+        proposal_id: i32,
+    ) -> Result<Vec<Self>, Error> {
+        query_as("SELECT proposal_id, signer, signature, created_at FROM admin_proposal_approval WHERE proposal_id = $1 ORDER BY created_at ASC")
+            .bind(proposal_id)
+            .fetch_all(&database.connection)
+            .await
+    }
+
+    /// Approving twice keeps the first approval. Returns false when the signer already approved.
+    pub async fn insert(&self, database: &Database) -> Result<bool, Error> {
+        query("INSERT INTO admin_proposal_approval(proposal_id, signer, signature, created_at) VALUES ($1, $2, $3, $4) ON CONFLICT (proposal_id, signer) DO NOTHING;")
+            .bind(self.proposal_id)
+            .bind(&self.signer)
+            .bind(&self.signature)
+            .bind(self.created_at)
+            .execute(&database.connection)
+            .await
+            .map(|result| result.rows_affected() > 0)
+    }
+}
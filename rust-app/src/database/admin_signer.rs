@@ -0,0 +1,85 @@
+use serde::{Deserialize, Serialize};
+use sqlx::{Error, FromRow, query, query_as, query_scalar};
+
+use crate::database::{Database, DatabaseConnection};
+
+pub async fn create_table(connection: &DatabaseConnection) {
+    sqlx::raw_sql(
+        "CREATE TABLE IF NOT EXISTS admin_signer(role TEXT NOT NULL, signer TEXT NOT NULL, valid_from INT8 NOT NULL, valid_until INT8, revoked_at INT8, added_by TEXT NOT NULL, created_at INT8 NOT NULL, PRIMARY KEY (role, signer))"
+    )
+    .execute(connection)
+    .await
+    .unwrap_or_else(|e| panic!("Could not create admin_signer table: {e}"));
+}
+
+/// Address authorized to sign admin actions of a role during its validity window, unless revoked.
+#[derive(Debug, FromRow, Serialize, Deserialize)]
+pub struct DatabaseAdminSigner {
+    pub role: String,
+    pub signer: String,
+    pub valid_from: i64,
+    pub valid_until: Option<i64>,
+    pub revoked_at: Option<i64>,
+    pub added_by: String,
+    pub created_at: i64,
+}
+
+impl DatabaseAdminSigner {
+    pub async fn get_all(database: &Database) -> Result<Vec<Self>, Error> {
+        query_as("SELECT role, signer, valid_from, valid_until, revoked_at, added_by, created_at FROM admin_signer ORDER BY role ASC, created_at DESC")
+            .fetch_all(&database.connection)
+            .await
+    }
+
+    pub async fn get_all_active_by_role(
+        database: &Database,
+        role: &str,
+        now: i64,
+    ) -> Result<Vec<Self>, Error> {
+        query_as("SELECT role, signer, valid_from, valid_until, revoked_at, added_by, created_at FROM admin_signer WHERE role = $1 AND valid_from <= $2 AND (valid_until IS NULL OR valid_until > $2) AND revoked_at IS NULL")
+            .bind(role)
+            .bind(now)
+            .fetch_all(&database.connection)
+            .await
+    }
+
+    /// Whether the role is managed in the database, including revoked and expired signers.
+    pub async fn has_role(database: &Database, role: &str) -> Result<bool, Error> {
+        query_scalar("SELECT EXISTS(SELECT 1 FROM admin_signer WHERE role = $1)")
+            .bind(role)
+            .fetch_one(&database.connection)
+            .await
+    }
+
+    /// Inserts the signer or replaces its validity window, clearing any revocation.
+    pub async fn upsert(&self, database: &Database) -> Result<(), Error> {
+        query("INSERT INTO admin_signer(role, signer, valid_from, valid_until, revoked_at, added_by, created_at) VALUES ($1, $2, $3, $4, $5, $6, $7) ON CONFLICT (role, signer) DO UPDATE SET valid_from = EXCLUDED.valid_from, valid_until = EXCLUDED.valid_until, revoked_at = EXCLUDED.revoked_at, added_by = EXCLUDED.added_by, created_at = EXCLUDED.created_at;")
+            .bind(&self.role)
+            .bind(&self.signer)
+            .bind(self.valid_from)
+            .bind(self.valid_until)
+            .bind(self.revoked_at)
+            .bind(&self.added_by)
+            .bind(self.created_at)
+            .execute(&database.connection)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Returns whether an unrevoked signer existed.
+    pub async fn revoke(
+        database: &Database,
+        role: &str,
+        signer: &str,
+        revoked_at: i64,
+    ) -> Result<bool, Error> {
+        query("UPDATE admin_signer SET revoked_at = $3 WHERE role = $1 AND signer = $2 AND revoked_at IS NULL;")
+            .bind(role)
+            .bind(signer)
+            .bind(revoked_at)
+            .execute(&database.connection)
+            .await
+            .map(|result| result.rows_affected() > 0)
+    }
+}
@@ -0,0 +1,121 @@
+use serde::{Deserialize, Serialize};
+use sqlx::{Error, FromRow, query, query_as};
+
+use crate::database::{Database, DatabaseConnection, DatabaseTransaction};
+
+pub async fn create_table(connection: &DatabaseConnection) {
+    sqlx::raw_sql(
+        "CREATE TABLE IF NOT EXISTS auto_renew(collection TEXT NOT NULL, chain TEXT NOT NULL, token_id TEXT NOT NULL, payer TEXT NOT NULL, max_months INT8 NOT NULL, renewed_months INT8 NOT NULL, owner_signature TEXT NOT NULL, payer_signature TEXT NOT NULL, failed_expires INT8, PRIMARY KEY (collection, chain, token_id))"
+    )
+    .execute(connection)
+    .await
+    .unwrap_or_else(|e| panic!("Could not create auto_renew table: {e}"));
+}
+
+#[derive(Debug, FromRow, Serialize, Deserialize)]
+pub struct DatabaseAutoRenew {
+    pub collection: String,
+    pub chain: String,
+    pub token_id: String,
+    pub payer: String,
+    pub max_months: i64,
+    pub renewed_months: i64,
+    pub owner_signature: String,
+    pub payer_signature: String,
+    pub failed_expires: Option<i64>,
+}
+
+impl DatabaseAutoRenew {
+    pub async fn get_by_collection_token_id(
+        database: &Database,
+        collection: &str,
+        chain: &str,
+        token_id: &str,
+    ) -> Result<Option<Self>, Error> {
+        query_as("SELECT collection, chain, token_id, payer, max_months, renewed_months, owner_signature, payer_signature, failed_expires FROM auto_renew WHERE collection = $1 AND chain = $2 AND token_id = $3")
+            .bind(collection)
+            .bind(chain)
+            .bind(token_id)
+            .fetch_optional(&database.connection)
+            .await
+    }
+
+    pub async fn get_all_due(database: &Database, renew_before: i64) -> Result<Vec<Self>, Error> {
+        query_as("SELECT auto_renew.collection, auto_renew.chain, auto_renew.token_id, auto_renew.payer, auto_renew.max_months, auto_renew.renewed_months, auto_renew.owner_signature, auto_renew.payer_signature, auto_renew.failed_expires FROM auto_renew INNER JOIN tokenized_server ON auto_renew.collection = tokenized_server.collection AND auto_renew.chain = tokenized_server.chain AND auto_renew.token_id = tokenized_server.token_id WHERE auto_renew.renewed_months < auto_renew.max_months AND tokenized_server.deployment IS NOT NULL AND tokenized_server.expires < EXTRACT(EPOCH FROM CURRENT_TIMESTAMP) + $1")
+            .bind(renew_before)
+            .fetch_all(&database.connection)
+            .await
+    }
+
+    pub async fn upsert(&self, database: &Database) -> Result<(), Error> {
+        let Self {
+            collection,
+            chain,
+            token_id,
+            payer,
+            max_months,
+            renewed_months,
+            owner_signature,
+            payer_signature,
+            failed_expires,
+        } = self;
+
+        query("INSERT INTO auto_renew(collection, chain, token_id, payer, max_months, renewed_months, owner_signature, payer_signature, failed_expires) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9) ON CONFLICT (collection, chain, token_id) DO UPDATE SET payer = EXCLUDED.payer, max_months = EXCLUDED.max_months, renewed_months = EXCLUDED.renewed_months, owner_signature = EXCLUDED.owner_signature, payer_signature = EXCLUDED.payer_signature, failed_expires = EXCLUDED.failed_expires;")
+        .bind(collection)
+        .bind(chain)
+        .bind(token_id)
+        .bind(payer)
+        .bind(max_months)
+        .bind(renewed_months)
+        .bind(owner_signature)
+        .bind(payer_signature)
+        .bind(failed_expires)
+        .execute(&database.connection)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Recorded together with the payment and extended expiry.
+    pub async fn renewed(&mut self, transaction: &mut DatabaseTransaction) -> Result<(), Error> {
+        query("UPDATE auto_renew SET renewed_months = renewed_months + 1, failed_expires = NULL WHERE collection = $1 AND chain = $2 AND token_id = $3;")
+            .bind(&self.collection)
+            .bind(&self.chain)
+            .bind(&self.token_id)
+            .execute(&mut **transaction)
+            .await?;
+
+        self.renewed_months += 1;
+        self.failed_expires = None;
+        Ok(())
+    }
+
+    pub async fn failed(&mut self, database: &Database, expires: i64) -> Result<(), Error> {
+        query("UPDATE auto_renew SET failed_expires = $1 WHERE collection = $2 AND chain = $3 AND token_id = $4;")
+            .bind(expires)
+            .bind(&self.collection)
+            .bind(&self.chain)
+            .bind(&self.token_id)
+            .execute(&database.connection)
+            .await?;
+
+        self.failed_expires = Some(expires);
+        Ok(())
+    }
+
+    pub async fn delete(
+        database: &Database,
+        collection: &str,
+        chain: &str,
+        token_id: &str,
+    ) -> Result<(), Error> {
+        query("DELETE FROM auto_renew WHERE collection = $1 AND chain = $2 AND token_id = $3;")
+            .bind(collection)
+            .bind(chain)
+            .bind(token_id)
+            .execute(&database.connection)
+            .await?;
+
+        Ok(())
+    }
+}
@@ -3,7 +3,7 @@ use sqlx::{Error, FromRow, query, query_as, query_scalar};
 
 use crate::{
     database::{
-        Database, DatabaseConnection, manual_tokens::DatabaseManualTokens,
+        Database, DatabaseConnection, DatabaseTransaction, manual_tokens::DatabaseManualTokens,
         nft_staking::DatabaseNFTStaking, participated::DatabaseParticipated,
     },
     utils::time::get_time_i64,
@@ -18,6 +18,18 @@ pub async fn create_table(connection: &DatabaseConnection) {
     .unwrap_or_else(|e| panic!("Could not create claim table: {e}"));
 }
 
+/// Earned amounts per source, derived from the claim descriptions.
+#[derive(Debug, FromRow, Serialize, Deserialize)]
+pub struct DatabaseClaimSources {
+    pub genesis: i64,
+    pub staking: i64,
+    pub manual: i64,
+    pub other: i64,
+    pub total: i64,
+}
+
+const CLAIM_SOURCES: &str = "COALESCE(SUM(amount) FILTER (WHERE description LIKE 'Genesis participation %'), 0)::INT8 AS genesis, COALESCE(SUM(amount) FILTER (WHERE description LIKE 'Staking rewards for %'), 0)::INT8 AS staking, COALESCE(SUM(amount) FILTER (WHERE description LIKE 'Manual token reward for %'), 0)::INT8 AS manual, COALESCE(SUM(amount) FILTER (WHERE description NOT LIKE 'Genesis participation %' AND description NOT LIKE 'Staking rewards for %' AND description NOT LIKE 'Manual token reward for %'), 0)::INT8 AS other, COALESCE(SUM(amount), 0)::INT8 AS total";
+
 #[derive(Debug, FromRow, Serialize, Deserialize)]
 pub struct DatabaseClaim {
     pub account: String,
@@ -54,6 +66,38 @@ impl DatabaseClaim {
             .await
     }
 
+    /// Total amount per account.
+    pub async fn get_totals(database: &Database) -> Result<Vec<(String, i64)>, Error> {
+        query_as("SELECT account, SUM(amount)::INT8 FROM claim GROUP BY account")
+            .fetch_all(&database.connection)
+            .await
+    }
+
+    pub async fn get_sources_by_account(
+        database: &Database,
+        account: &str,
+    ) -> Result<DatabaseClaimSources, Error> {
+        query_as(&format!(
+            "SELECT {CLAIM_SOURCES} FROM claim WHERE account = $1"
+        ))
+        .bind(account)
+        .fetch_one(&database.connection)
+        .await
+    }
+
+    pub async fn get_sources(database: &Database) -> Result<DatabaseClaimSources, Error> {
+        query_as(&format!("SELECT {CLAIM_SOURCES} FROM claim"))
+            .fetch_one(&database.connection)
+            .await
+    }
+
+    /// Sum over all accounts of what each can still claim, accounts that claimed more than earned do not offset others.
+    pub async fn get_total_outstanding(database: &Database) -> Result<i64, Error> {
+        query_scalar("SELECT COALESCE(SUM(GREATEST(earned.amount - COALESCE(claimed.total, 0), 0)), 0)::INT8 FROM (SELECT account, SUM(amount) AS amount FROM claim GROUP BY account) earned LEFT JOIN (SELECT account, MAX(total) AS total FROM tokens_claimed GROUP BY account) claimed ON earned.account = claimed.account")
+            .fetch_one(&database.connection)
+            .await
+    }
+
     pub async fn insert(&self, database: &Database) -> Result<(), Error> {
         let Self {
             account,
@@ -72,6 +116,22 @@ impl DatabaseClaim {
 
         Ok(())
     }
+
+    /// Insert as part of a larger write, such as a staking distribution.
+    pub async fn insert_transaction(
+        &self,
+        transaction: &mut DatabaseTransaction,
+    ) -> Result<(), Error> {
+        query("INSERT INTO claim(account, amount, description, date) VALUES ($1, $2, $3, $4);")
+            .bind(&self.account)
+            .bind(self.amount)
+            .bind(&self.description)
+            .bind(self.date)
+            .execute(&mut **transaction)
+            .await?;
+
+        Ok(())
+    }
 }
 
 impl From<&DatabaseParticipated> for DatabaseClaim {
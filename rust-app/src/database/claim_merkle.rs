@@ -0,0 +1,99 @@
+use serde::{Deserialize, Serialize};
+use sqlx::{Error, FromRow, query, query_as, query_scalar};
+
+use crate::database::{Database, DatabaseConnection, DatabaseTransaction};
+
+pub async fn create_table(connection: &DatabaseConnection) {
+    sqlx::raw_sql(
+        "CREATE TABLE IF NOT EXISTS claim_merkle_root(id SERIAL PRIMARY KEY, root TEXT NOT NULL, accounts INT4 NOT NULL, total INT8 NOT NULL, date INT8 NOT NULL); CREATE TABLE IF NOT EXISTS claim_merkle_leaf(root_id INT4 NOT NULL REFERENCES claim_merkle_root(id), account TEXT NOT NULL, total INT8 NOT NULL, proof TEXT[] NOT NULL, PRIMARY KEY (root_id, account))"
+    )
+    .execute(connection)
+    .await
+    .unwrap_or_else(|e| panic!("Could not create claim_merkle tables: {e}"));
+}
+
+/// Snapshot of the claim totals per account, totals in 6 decimals like the claim table.
+#[derive(Debug, FromRow, Serialize, Deserialize)]
+pub struct DatabaseClaimMerkleRoot {
+    pub id: i32,
+    pub root: String,
+    pub accounts: i32,
+    pub total: i64,
+    pub date: i64,
+}
+
+#[derive(Debug, FromRow, Serialize, Deserialize)]
+pub struct DatabaseClaimMerkleLeaf {
+    pub account: String,
+    pub total: i64,
+    pub proof: Vec<String>,
+}
+
+impl DatabaseClaimMerkleRoot {
+    pub async fn get_all(database: &Database) -> Result<Vec<Self>, Error> {
+        query_as("SELECT id, root, accounts, total, date FROM claim_merkle_root ORDER BY id DESC")
+            .fetch_all(&database.connection)
+            .await
+    }
+
+    pub async fn get_latest(database: &Database) -> Result<Option<Self>, Error> {
+        query_as("SELECT id, root, accounts, total, date FROM claim_merkle_root ORDER BY id DESC LIMIT 1")
+            .fetch_optional(&database.connection)
+            .await
+    }
+
+    pub async fn get_by_root(database: &Database, root: &str) -> Result<Option<Self>, Error> {
+        query_as("SELECT id, root, accounts, total, date FROM claim_merkle_root WHERE root = $1 ORDER BY id DESC LIMIT 1")
+            .bind(root)
+            .fetch_optional(&database.connection)
+            .await
+    }
+
+    /// Inserts the root and all its leaves, returning the new id.
+    pub async fn insert(
+        &self,
+        transaction: &mut DatabaseTransaction,
+        leaves: &[DatabaseClaimMerkleLeaf],
+    ) -> Result<i32, Error> {
+        let id: i32 = query_scalar("INSERT INTO claim_merkle_root(root, accounts, total, date) VALUES ($1, $2, $3, $4) RETURNING id;")
+            .bind(&self.root)
+            .bind(self.accounts)
+            .bind(self.total)
+            .bind(self.date)
+            .fetch_one(&mut **transaction)
+            .await?;
+
+        for leaf in leaves {
+            query("INSERT INTO claim_merkle_leaf(root_id, account, total, proof) VALUES ($1, $2, $3, $4);")
+                .bind(id)
+                .bind(&leaf.account)
+                .bind(leaf.total)
+                .bind(&leaf.proof)
+                .execute(&mut **transaction)
+                .await?;
+        }
+
+        Ok(id)
+    }
+}
+
+impl DatabaseClaimMerkleLeaf {
+    pub async fn get_all_by_root_id(database: &Database, root_id: i32) -> Result<Vec<Self>, Error> {
+        query_as("SELECT account, total, proof FROM claim_merkle_leaf WHERE root_id = $1 ORDER BY account ASC")
+            .bind(root_id)
+            .fetch_all(&database.connection)
+            .await
+    }
+
+    pub async fn get_by_root_id_account(
+        database: &Database,
+        root_id: i32,
+        account: &str,
+    ) -> Result<Option<Self>, Error> {
+        query_as("SELECT account, total, proof FROM claim_merkle_leaf WHERE root_id = $1 AND account = $2")
+            .bind(root_id)
+            .bind(account)
+            .fetch_optional(&database.connection)
+            .await
+    }
+}
@@ -0,0 +1,75 @@
+use serde::{Deserialize, Serialize};
+use sqlx::{Error, FromRow, query, query_as};
+
+use crate::database::{Database, DatabaseConnection};
+
+pub async fn create_table(connection: &DatabaseConnection) {
+    sqlx::raw_sql(
+        "CREATE TABLE IF NOT EXISTS claim_signature(id SERIAL PRIMARY KEY, account TEXT NOT NULL, total INT8 NOT NULL, deadline INT8, signature TEXT NOT NULL, requester_ip TEXT, date INT8 NOT NULL); CREATE INDEX IF NOT EXISTS claim_signature_account ON claim_signature(account)"
+    )
+    .execute(connection)
+    .await
+    .unwrap_or_else(|e| panic!("Could not create claim_signature table: {e}"));
+}
+
+/// Claim signature handed out, total in 6 decimals like the claim table. Legacy claims have no deadline.
+#[derive(Debug, FromRow, Serialize, Deserialize)]
+pub struct DatabaseClaimSignature {
+    pub account: String,
+    pub total: i64,
+    pub deadline: Option<i64>,
+    pub signature: String,
+    /// Kept for abuse investigations, never part of the public listings
+    #[serde(skip_serializing)]
+    pub requester_ip: Option<String>,
+    pub date: i64,
+}
+
+/// Account that received a signature for more than it earned.
+#[derive(Debug, FromRow, Serialize, Deserialize)]
+pub struct DatabaseClaimSignatureExcess {
+    pub account: String,
+    pub issued: i64,
+    pub earned: i64,
+}
+
+impl DatabaseClaimSignature {
+    pub async fn get_latest(database: &Database, limit: i64) -> Result<Vec<Self>, Error> {
+        query_as("SELECT account, total, deadline, signature, requester_ip, date FROM claim_signature ORDER BY id DESC LIMIT $1")
+            .bind(limit)
+            .fetch_all(&database.connection)
+            .await
+    }
+
+    pub async fn get_all_by_account(
+        database: &Database,
+        account: &str,
+    ) -> Result<Vec<Self>, Error> {
+        query_as("SELECT account, total, deadline, signature, requester_ip, date FROM claim_signature WHERE account = $1 ORDER BY id DESC")
+            .bind(account)
+            .fetch_all(&database.connection)
+            .await
+    }
+
+    pub async fn get_all_excess(
+        database: &Database,
+    ) -> Result<Vec<DatabaseClaimSignatureExcess>, Error> {
+        query_as("SELECT issued.account, issued.total AS issued, COALESCE(earned.amount, 0)::INT8 AS earned FROM (SELECT account, MAX(total) AS total FROM claim_signature GROUP BY account) issued LEFT JOIN (SELECT account, SUM(amount) AS amount FROM claim GROUP BY account) earned ON issued.account = earned.account WHERE issued.total > COALESCE(earned.amount, 0) ORDER BY issued.total - COALESCE(earned.amount, 0) DESC")
+            .fetch_all(&database.connection)
+            .await
+    }
+
+    pub async fn insert(&self, database: &Database) -> Result<(), Error> {
+        query("INSERT INTO claim_signature(account, total, deadline, signature, requester_ip, date) VALUES ($1, $2, $3, $4, $5, $6);")
+            .bind(&self.account)
+            .bind(self.total)
+            .bind(self.deadline)
+            .bind(&self.signature)
+            .bind(&self.requester_ip)
+            .bind(self.date)
+            .execute(&database.connection)
+            .await?;
+
+        Ok(())
+    }
+}
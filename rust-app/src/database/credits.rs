@@ -3,7 +3,7 @@ use sqlx::{Error, FromRow, query, query_as, query_scalar};
 
 use crate::{
     database::{
-        Database, DatabaseConnection, participated::DatabaseParticipated,
+        Database, DatabaseConnection, DatabaseTransaction, participated::DatabaseParticipated,
         promo_code::DatabasePromoCode,
     },
     utils::time::get_time_i64,
@@ -105,6 +105,22 @@ impl DatabaseCredits {
 
         Ok(())
     }
+
+    /// Insert as part of a larger write, such as an auto renewal.
+    pub async fn insert_transaction(
+        &self,
+        transaction: &mut DatabaseTransaction,
+    ) -> Result<(), Error> {
+        query("INSERT INTO credits(account, credits, description, date) VALUES ($1, $2, $3, $4);")
+            .bind(&self.account)
+            .bind(self.credits)
+            .bind(&self.description)
+            .bind(self.date)
+            .execute(&mut **transaction)
+            .await?;
+
+        Ok(())
+    }
 }
 
 impl From<&DatabaseParticipated> for DatabaseCredits {
@@ -161,3 +177,11 @@ impl TryFrom<&DatabasePromoCode> for DatabaseCredits {
         }
     }
 }
+
+/// Whether the insert was refused by trg_check_sum_credits, rather than failing for another reason.
+pub fn is_insufficient_credits(e: &Error) -> bool {
+    // RAISE EXCEPTION without an explicit code uses raise_exception
+    e.as_database_error()
+        .and_then(|e| e.code())
+        .is_some_and(|code| code == "P0001")
+}
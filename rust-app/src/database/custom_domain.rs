@@ -0,0 +1,97 @@
+use serde::{Deserialize, Serialize};
+use sqlx::{Error, FromRow, query, query_as};
+
+use crate::database::{Database, DatabaseConnection};
+
+pub async fn create_table(connection: &DatabaseConnection) {
+    sqlx::raw_sql(
+        "CREATE TABLE IF NOT EXISTS custom_domain(domain TEXT NOT NULL, collection TEXT NOT NULL, chain TEXT NOT NULL, token_id TEXT NOT NULL, challenge TEXT NOT NULL, verified BOOLEAN NOT NULL, created_at INT8 NOT NULL, PRIMARY KEY (domain))"
+    )
+    .execute(connection)
+    .await
+    .unwrap_or_else(|e| panic!("Could not create custom_domain table: {e}"));
+}
+
+#[derive(Debug, FromRow, Serialize, Deserialize)]
+pub struct DatabaseCustomDomain {
+    pub domain: String,
+    pub collection: String,
+    pub chain: String,
+    pub token_id: String,
+    pub challenge: String,
+    pub verified: bool,
+    pub created_at: i64,
+}
+
+impl DatabaseCustomDomain {
+    pub async fn get_by_domain(database: &Database, domain: &str) -> Result<Option<Self>, Error> {
+        query_as("SELECT domain, collection, chain, token_id, challenge, verified, created_at FROM custom_domain WHERE domain = $1")
+            .bind(domain)
+            .fetch_optional(&database.connection)
+            .await
+    }
+
+    pub async fn get_all_by_collection_token_id(
+        database: &Database,
+        collection: &str,
+        chain: &str,
+        token_id: &str,
+    ) -> Result<Vec<Self>, Error> {
+        query_as("SELECT domain, collection, chain, token_id, challenge, verified, created_at FROM custom_domain WHERE collection = $1 AND chain = $2 AND token_id = $3 ORDER BY created_at ASC")
+            .bind(collection)
+            .bind(chain)
+            .bind(token_id)
+            .fetch_all(&database.connection)
+            .await
+    }
+
+    pub async fn insert(&self, database: &Database) -> Result<(), Error> {
+        query("INSERT INTO custom_domain(domain, collection, chain, token_id, challenge, verified, created_at) VALUES ($1, $2, $3, $4, $5, $6, $7);")
+            .bind(&self.domain)
+            .bind(&self.collection)
+            .bind(&self.chain)
+            .bind(&self.token_id)
+            .bind(&self.challenge)
+            .bind(self.verified)
+            .bind(self.created_at)
+            .execute(&database.connection)
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn verify(&mut self, database: &Database) -> Result<(), Error> {
+        query("UPDATE custom_domain SET verified = TRUE WHERE domain = $1;")
+            .bind(&self.domain)
+            .execute(&database.connection)
+            .await?;
+
+        self.verified = true;
+        Ok(())
+    }
+
+    pub async fn delete(&self, database: &Database) -> Result<(), Error> {
+        query("DELETE FROM custom_domain WHERE domain = $1;")
+            .bind(&self.domain)
+            .execute(&database.connection)
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn delete_by_collection_token_id(
+        database: &Database,
+        collection: &str,
+        chain: &str,
+        token_id: &str,
+    ) -> Result<(), Error> {
+        query("DELETE FROM custom_domain WHERE collection = $1 AND chain = $2 AND token_id = $3;")
+            .bind(collection)
+            .bind(chain)
+            .bind(token_id)
+            .execute(&database.connection)
+            .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,43 @@
+use serde::{Deserialize, Serialize};
+use sqlx::{Error, FromRow, query, query_as};
+
+use crate::database::{Database, DatabaseConnection};
+
+pub async fn create_table(connection: &DatabaseConnection) {
+    sqlx::raw_sql(
+        "CREATE TABLE IF NOT EXISTS denial(id SERIAL PRIMARY KEY, account TEXT NOT NULL, scope TEXT NOT NULL, reason TEXT NOT NULL, date INT8 NOT NULL)"
+    )
+    .execute(connection)
+    .await
+    .unwrap_or_else(|e| panic!("Could not create denial table: {e}"));
+}
+
+/// Audit log of actions refused because of the denylist.
+#[derive(Debug, FromRow, Serialize, Deserialize)]
+pub struct DatabaseDenial {
+    pub account: String,
+    pub scope: String,
+    pub reason: String,
+    pub date: i64,
+}
+
+impl DatabaseDenial {
+    pub async fn get_latest(database: &Database, limit: i64) -> Result<Vec<Self>, Error> {
+        query_as("SELECT account, scope, reason, date FROM denial ORDER BY id DESC LIMIT $1")
+            .bind(limit)
+            .fetch_all(&database.connection)
+            .await
+    }
+
+    pub async fn insert(&self, database: &Database) -> Result<(), Error> {
+        query("INSERT INTO denial(account, scope, reason, date) VALUES ($1, $2, $3, $4);")
+            .bind(&self.account)
+            .bind(&self.scope)
+            .bind(&self.reason)
+            .bind(self.date)
+            .execute(&database.connection)
+            .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,86 @@
+use serde::{Deserialize, Serialize};
+use sqlx::{Error, FromRow, query, query_as};
+
+use crate::database::{Database, DatabaseConnection};
+
+pub async fn create_table(connection: &DatabaseConnection) {
+    // Accounts that were hardcoded in claim signing are carried over once, when the table is created
+    sqlx::raw_sql(
+        "DO $$ BEGIN IF NOT EXISTS (SELECT FROM pg_tables WHERE tablename = 'denylist') THEN CREATE TABLE denylist(account TEXT NOT NULL, scope TEXT NOT NULL, reason TEXT NOT NULL, added_by TEXT NOT NULL, created_at INT8 NOT NULL, expires INT8, PRIMARY KEY (account, scope)); INSERT INTO denylist(account, scope, reason, added_by, created_at, expires) VALUES ('0x0FF350487269Fda1aE176620D42c8ab9958493E2', 'claim', 'Previously hardcoded', 'migration', EXTRACT(EPOCH FROM now())::INT8, NULL), ('0xE87C55363A51845352a9eD15521ab6AB6AA33Dc4', 'claim', 'Previously hardcoded', 'migration', EXTRACT(EPOCH FROM now())::INT8, NULL), ('0x03f0c4A0652B2E02Ab476D647E34F1F4CfbFA724', 'claim', 'Previously hardcoded', 'migration', EXTRACT(EPOCH FROM now())::INT8, NULL), ('0xd438C6D4a1450b55284847f06E0ed291fb053238', 'claim', 'Previously hardcoded', 'migration', EXTRACT(EPOCH FROM now())::INT8, NULL); END IF; END $$;"
+    )
+    .execute(connection)
+    .await
+    .unwrap_or_else(|e| panic!("Could not create denylist table: {e}"));
+}
+
+/// Account denied an action (scope) until it expires, forever without expiry.
+#[derive(Debug, FromRow, Serialize, Deserialize)]
+pub struct DatabaseDenylist {
+    pub account: String,
+    pub scope: String,
+    pub reason: String,
+    pub added_by: String,
+    pub created_at: i64,
+    pub expires: Option<i64>,
+}
+
+impl DatabaseDenylist {
+    pub async fn get_all(database: &Database) -> Result<Vec<Self>, Error> {
+        query_as("SELECT account, scope, reason, added_by, created_at, expires FROM denylist ORDER BY created_at DESC")
+            .fetch_all(&database.connection)
+            .await
+    }
+
+    /// Entries denying the scope at the given time.
+    pub async fn get_all_active_by_scope(
+        database: &Database,
+        scope: &str,
+        now: i64,
+    ) -> Result<Vec<Self>, Error> {
+        query_as("SELECT account, scope, reason, added_by, created_at, expires FROM denylist WHERE scope = $1 AND (expires IS NULL OR expires > $2)")
+            .bind(scope)
+            .bind(now)
+            .fetch_all(&database.connection)
+            .await
+    }
+
+    /// Entry denying the account the scope at the given time, addresses are compared case insensitive.
+    pub async fn get_active_by_account_scope(
+        database: &Database,
+        account: &str,
+        scope: &str,
+        now: i64,
+    ) -> Result<Option<Self>, Error> {
+        query_as("SELECT account, scope, reason, added_by, created_at, expires FROM denylist WHERE LOWER(account) = LOWER($1) AND scope = $2 AND (expires IS NULL OR expires > $3)")
+            .bind(account)
+            .bind(scope)
+            .bind(now)
+            .fetch_optional(&database.connection)
+            .await
+    }
+
+    /// Inserts the entry or replaces its reason and expiry.
+    pub async fn upsert(&self, database: &Database) -> Result<(), Error> {
+        query("INSERT INTO denylist(account, scope, reason, added_by, created_at, expires) VALUES ($1, $2, $3, $4, $5, $6) ON CONFLICT (account, scope) DO UPDATE SET reason = EXCLUDED.reason, added_by = EXCLUDED.added_by, created_at = EXCLUDED.created_at, expires = EXCLUDED.expires;")
+            .bind(&self.account)
+            .bind(&self.scope)
+            .bind(&self.reason)
+            .bind(&self.added_by)
+            .bind(self.created_at)
+            .bind(self.expires)
+            .execute(&database.connection)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Returns whether the entry existed.
+    pub async fn delete(database: &Database, account: &str, scope: &str) -> Result<bool, Error> {
+        query("DELETE FROM denylist WHERE LOWER(account) = LOWER($1) AND scope = $2;")
+            .bind(account)
+            .bind(scope)
+            .execute(&database.connection)
+            .await
+            .map(|result| result.rows_affected() > 0)
+    }
+}
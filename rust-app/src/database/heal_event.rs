@@ -0,0 +1,95 @@
+use serde::{Deserialize, Serialize};
+use sqlx::{Error, FromRow, query, query_as, query_scalar, types::Json};
+
+use crate::database::{Database, DatabaseConnection, tokenized_server::TokenizedServerDeployment};
+
+pub async fn create_table(connection: &DatabaseConnection) {
+    sqlx::raw_sql(
+        "CREATE TABLE IF NOT EXISTS heal_event(id SERIAL PRIMARY KEY, collection TEXT NOT NULL, chain TEXT NOT NULL, token_id TEXT NOT NULL, reason TEXT NOT NULL, old_deployment JSON NOT NULL, new_deployment JSON, downtime INT8 NOT NULL, date INT8 NOT NULL)"
+    )
+    .execute(connection)
+    .await
+    .unwrap_or_else(|e| panic!("Could not create heal_event table: {e}"));
+}
+
+#[derive(Debug, FromRow, Serialize, Deserialize)]
+pub struct DatabaseHealEvent {
+    pub id: i32,
+    pub collection: String,
+    pub chain: String,
+    pub token_id: String,
+    pub reason: String,
+    pub old_deployment: Json<TokenizedServerDeployment>,
+    pub new_deployment: Option<Json<TokenizedServerDeployment>>,
+    /// Seconds the expiry got extended by
+    pub downtime: i64,
+    pub date: i64,
+}
+
+impl DatabaseHealEvent {
+    pub async fn get_all_by_collection_token_id(
+        database: &Database,
+        collection: &str,
+        chain: &str,
+        token_id: &str,
+    ) -> Result<Vec<Self>, Error> {
+        query_as("SELECT id, collection, chain, token_id, reason, old_deployment, new_deployment, downtime, date FROM heal_event WHERE collection = $1 AND chain = $2 AND token_id = $3 ORDER BY id DESC")
+            .bind(collection)
+            .bind(chain)
+            .bind(token_id)
+            .fetch_all(&database.connection)
+            .await
+    }
+
+    pub async fn get_last_date_by_collection_token_id(
+        database: &Database,
+        collection: &str,
+        chain: &str,
+        token_id: &str,
+    ) -> Result<Option<i64>, Error> {
+        query_scalar("SELECT MAX(date) FROM heal_event WHERE collection = $1 AND chain = $2 AND token_id = $3")
+            .bind(collection)
+            .bind(chain)
+            .bind(token_id)
+            .fetch_one(&database.connection)
+            .await
+    }
+
+    /// Heals whose redeployment failed, of servers that are still not deployed and not expired.
+    pub async fn get_all_failed(database: &Database) -> Result<Vec<Self>, Error> {
+        query_as("SELECT h.id, h.collection, h.chain, h.token_id, h.reason, h.old_deployment, h.new_deployment, h.downtime, h.date FROM heal_event h JOIN tokenized_server t ON t.collection = h.collection AND t.chain = h.chain AND t.token_id = h.token_id WHERE h.new_deployment IS NULL AND t.deployment IS NULL AND t.expires > EXTRACT(EPOCH FROM CURRENT_TIMESTAMP) ORDER BY h.id ASC")
+            .fetch_all(&database.connection)
+            .await
+    }
+
+    pub async fn redeployed(
+        &mut self,
+        database: &Database,
+        new_deployment: Json<TokenizedServerDeployment>,
+    ) -> Result<(), Error> {
+        query("UPDATE heal_event SET new_deployment = $1 WHERE id = $2;")
+            .bind(&new_deployment)
+            .bind(self.id)
+            .execute(&database.connection)
+            .await?;
+
+        self.new_deployment = Some(new_deployment);
+        Ok(())
+    }
+
+    pub async fn insert(&self, database: &Database) -> Result<(), Error> {
+        query("INSERT INTO heal_event(collection, chain, token_id, reason, old_deployment, new_deployment, downtime, date) VALUES ($1, $2, $3, $4, $5, $6, $7, $8);")
+            .bind(&self.collection)
+            .bind(&self.chain)
+            .bind(&self.token_id)
+            .bind(&self.reason)
+            .bind(&self.old_deployment)
+            .bind(&self.new_deployment)
+            .bind(self.downtime)
+            .bind(self.date)
+            .execute(&database.connection)
+            .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,139 @@
+use serde::{Deserialize, Serialize};
+use sqlx::{Error, FromRow, query, query_as, query_scalar};
+
+use crate::database::{Database, DatabaseConnection};
+
+pub async fn create_table(connection: &DatabaseConnection) {
+    sqlx::raw_sql(
+        "CREATE TABLE IF NOT EXISTS health_sample(id SERIAL PRIMARY KEY, collection TEXT NOT NULL, chain TEXT NOT NULL, token_id TEXT NOT NULL, healthy BOOLEAN NOT NULL, cpu FLOAT4, memory_used INT8, memory_total INT8, disk_used INT8, disk_total INT8, error TEXT, date INT8 NOT NULL)"
+    )
+    .execute(connection)
+    .await
+    .unwrap_or_else(|e| panic!("Could not create health_sample table: {e}"));
+
+    sqlx::raw_sql(
+        "CREATE INDEX IF NOT EXISTS health_sample_token_date ON health_sample(collection, chain, token_id, date)",
+    )
+    .execute(connection)
+    .await
+    .unwrap_or_else(|e| panic!("Could not create health_sample index: {e}"));
+}
+
+#[derive(Debug, FromRow, Serialize, Deserialize)]
+pub struct DatabaseHealthSample {
+    pub id: i32,
+    pub collection: String,
+    pub chain: String,
+    pub token_id: String,
+    pub healthy: bool,
+    /// Average usage percentage over all cores
+    pub cpu: Option<f32>,
+    pub memory_used: Option<i64>,
+    pub memory_total: Option<i64>,
+    pub disk_used: Option<i64>,
+    pub disk_total: Option<i64>,
+    pub error: Option<String>,
+    pub date: i64,
+}
+
+#[derive(Debug, FromRow, Serialize, Deserialize)]
+pub struct DatabaseUptime {
+    pub healthy: i64,
+    pub total: i64,
+}
+
+impl DatabaseHealthSample {
+    /// Most recent samples first.
+    pub async fn get_latest_by_collection_token_id(
+        database: &Database,
+        collection: &str,
+        chain: &str,
+        token_id: &str,
+        limit: i64,
+    ) -> Result<Vec<Self>, Error> {
+        query_as("SELECT id, collection, chain, token_id, healthy, cpu, memory_used, memory_total, disk_used, disk_total, error, date FROM health_sample WHERE collection = $1 AND chain = $2 AND token_id = $3 ORDER BY date DESC, id DESC LIMIT $4")
+            .bind(collection)
+            .bind(chain)
+            .bind(token_id)
+            .bind(limit)
+            .fetch_all(&database.connection)
+            .await
+    }
+
+    pub async fn get_uptime_by_collection_token_id(
+        database: &Database,
+        collection: &str,
+        chain: &str,
+        token_id: &str,
+        since: i64,
+        until: i64,
+    ) -> Result<DatabaseUptime, Error> {
+        query_as("SELECT COUNT(*) FILTER (WHERE healthy) AS healthy, COUNT(*) AS total FROM health_sample WHERE collection = $1 AND chain = $2 AND token_id = $3 AND date >= $4 AND date < $5")
+            .bind(collection)
+            .bind(chain)
+            .bind(token_id)
+            .bind(since)
+            .bind(until)
+            .fetch_one(&database.connection)
+            .await
+    }
+
+    /// Date of the first sample after the given time, healthy or not.
+    pub async fn get_first_date_since_by_collection_token_id(
+        database: &Database,
+        collection: &str,
+        chain: &str,
+        token_id: &str,
+        since: i64,
+    ) -> Result<Option<i64>, Error> {
+        query_scalar("SELECT MIN(date) FROM health_sample WHERE collection = $1 AND chain = $2 AND token_id = $3 AND date >= $4")
+            .bind(collection)
+            .bind(chain)
+            .bind(token_id)
+            .bind(since)
+            .fetch_one(&database.connection)
+            .await
+    }
+
+    pub async fn get_last_healthy_date_by_collection_token_id(
+        database: &Database,
+        collection: &str,
+        chain: &str,
+        token_id: &str,
+    ) -> Result<Option<i64>, Error> {
+        query_scalar("SELECT MAX(date) FROM health_sample WHERE collection = $1 AND chain = $2 AND token_id = $3 AND healthy")
+            .bind(collection)
+            .bind(chain)
+            .bind(token_id)
+            .fetch_one(&database.connection)
+            .await
+    }
+
+    pub async fn insert(&self, database: &Database) -> Result<(), Error> {
+        query("INSERT INTO health_sample(collection, chain, token_id, healthy, cpu, memory_used, memory_total, disk_used, disk_total, error, date) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11);")
+            .bind(&self.collection)
+            .bind(&self.chain)
+            .bind(&self.token_id)
+            .bind(self.healthy)
+            .bind(self.cpu)
+            .bind(self.memory_used)
+            .bind(self.memory_total)
+            .bind(self.disk_used)
+            .bind(self.disk_total)
+            .bind(&self.error)
+            .bind(self.date)
+            .execute(&database.connection)
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn delete_before(database: &Database, date: i64) -> Result<(), Error> {
+        query("DELETE FROM health_sample WHERE date < $1;")
+            .bind(date)
+            .execute(&database.connection)
+            .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,94 @@
+use std::fmt::Display;
+
+use serde::{Deserialize, Serialize};
+use sqlx::{Error, FromRow, query, query_as, query_scalar, types::Json};
+
+use crate::database::{Database, DatabaseConnection, tokenized_server::TokenizedServerDeployment};
+
+pub async fn create_table(connection: &DatabaseConnection) {
+    sqlx::raw_sql(
+        "CREATE TABLE IF NOT EXISTS inventory_issue(kind TEXT NOT NULL, deployment JSONB NOT NULL, name TEXT NOT NULL, collection TEXT, chain TEXT, token_id TEXT, first_seen INT8 NOT NULL, last_seen INT8 NOT NULL, PRIMARY KEY (kind, deployment))"
+    )
+    .execute(connection)
+    .await
+    .unwrap_or_else(|e| panic!("Could not create inventory_issue table: {e}"));
+}
+
+pub enum InventoryIssueKind {
+    /// Provider VM not referenced by any tokenized server
+    Orphan,
+    /// Tokenized server deployment that does not exist at the provider
+    Ghost,
+}
+
+impl Display for InventoryIssueKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InventoryIssueKind::Orphan => f.write_str("orphan"),
+            InventoryIssueKind::Ghost => f.write_str("ghost"),
+        }
+    }
+}
+
+#[derive(Debug, FromRow, Serialize, Deserialize)]
+pub struct DatabaseInventoryIssue {
+    pub kind: String,
+    pub deployment: Json<TokenizedServerDeployment>,
+    pub name: String,
+    pub collection: Option<String>,
+    pub chain: Option<String>,
+    pub token_id: Option<String>,
+    pub first_seen: i64,
+    pub last_seen: i64,
+}
+
+impl DatabaseInventoryIssue {
+    pub async fn get_all(database: &Database) -> Result<Vec<Self>, Error> {
+        query_as("SELECT kind, deployment, name, collection, chain, token_id, first_seen, last_seen FROM inventory_issue ORDER BY first_seen ASC")
+            .fetch_all(&database.connection)
+            .await
+    }
+
+    /// Inserts a newly detected issue, or marks an existing one as seen again (keeping first_seen).
+    pub async fn upsert(&mut self, database: &Database) -> Result<(), Error> {
+        let first_seen: i64 = query_scalar("INSERT INTO inventory_issue(kind, deployment, name, collection, chain, token_id, first_seen, last_seen) VALUES ($1, $2, $3, $4, $5, $6, $7, $8) ON CONFLICT (kind, deployment) DO UPDATE SET name = EXCLUDED.name, collection = EXCLUDED.collection, chain = EXCLUDED.chain, token_id = EXCLUDED.token_id, last_seen = EXCLUDED.last_seen RETURNING first_seen")
+            .bind(&self.kind)
+            .bind(&self.deployment)
+            .bind(&self.name)
+            .bind(&self.collection)
+            .bind(&self.chain)
+            .bind(&self.token_id)
+            .bind(self.first_seen)
+            .bind(self.last_seen)
+            .fetch_one(&database.connection)
+            .await?;
+
+        self.first_seen = first_seen;
+        Ok(())
+    }
+
+    pub async fn delete(&self, database: &Database) -> Result<(), Error> {
+        query("DELETE FROM inventory_issue WHERE kind = $1 AND deployment = $2;")
+            .bind(&self.kind)
+            .bind(&self.deployment)
+            .execute(&database.connection)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Removes issues of a kind that were not seen since the given time, as they are resolved.
+    pub async fn delete_resolved(
+        database: &Database,
+        kind: InventoryIssueKind,
+        seen_before: i64,
+    ) -> Result<(), Error> {
+        query("DELETE FROM inventory_issue WHERE kind = $1 AND last_seen < $2;")
+            .bind(kind.to_string())
+            .bind(seen_before)
+            .execute(&database.connection)
+            .await?;
+
+        Ok(())
+    }
+}
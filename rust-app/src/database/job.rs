@@ -0,0 +1,100 @@
+use serde::{Deserialize, Serialize};
+use sqlx::{Error, FromRow, query, query_as};
+
+use crate::database::{Database, DatabaseConnection};
+
+pub async fn create_table(connection: &DatabaseConnection) {
+    sqlx::raw_sql(
+        "CREATE TABLE IF NOT EXISTS job(name TEXT NOT NULL, schedule TEXT NOT NULL, next_run INT8 NOT NULL, last_run INT8, last_success BOOLEAN, attempts INT4 NOT NULL, triggered BOOLEAN NOT NULL, PRIMARY KEY (name))"
+    )
+    .execute(connection)
+    .await
+    .unwrap_or_else(|e| panic!("Could not create job table: {e}"));
+}
+
+#[derive(Debug, FromRow, Serialize, Deserialize)]
+pub struct DatabaseJob {
+    pub name: String,
+    /// Cron expression (with seconds)
+    pub schedule: String,
+    pub next_run: i64,
+    pub last_run: Option<i64>,
+    pub last_success: Option<bool>,
+    /// Consecutive failed attempts of the current run
+    pub attempts: i32,
+    /// Manual run requested
+    pub triggered: bool,
+}
+
+impl DatabaseJob {
+    pub async fn get_all(database: &Database) -> Result<Vec<Self>, Error> {
+        query_as("SELECT name, schedule, next_run, last_run, last_success, attempts, triggered FROM job ORDER BY name ASC")
+            .fetch_all(&database.connection)
+            .await
+    }
+
+    pub async fn get_by_name(database: &Database, name: &str) -> Result<Option<Self>, Error> {
+        query_as("SELECT name, schedule, next_run, last_run, last_success, attempts, triggered FROM job WHERE name = $1")
+            .bind(name)
+            .fetch_optional(&database.connection)
+            .await
+    }
+
+    pub async fn get_all_due(database: &Database, now: i64) -> Result<Vec<Self>, Error> {
+        query_as("SELECT name, schedule, next_run, last_run, last_success, attempts, triggered FROM job WHERE next_run <= $1")
+            .bind(now)
+            .fetch_all(&database.connection)
+            .await
+    }
+
+    /// Keeps the persisted next run unless the schedule changed.
+    pub async fn register(&self, database: &Database) -> Result<(), Error> {
+        query("INSERT INTO job(name, schedule, next_run, last_run, last_success, attempts, triggered) VALUES ($1, $2, $3, $4, $5, $6, $7) ON CONFLICT (name) DO UPDATE SET schedule = EXCLUDED.schedule, next_run = CASE WHEN job.schedule = EXCLUDED.schedule THEN job.next_run ELSE EXCLUDED.next_run END;")
+            .bind(&self.name)
+            .bind(&self.schedule)
+            .bind(self.next_run)
+            .bind(self.last_run)
+            .bind(self.last_success)
+            .bind(self.attempts)
+            .bind(self.triggered)
+            .execute(&database.connection)
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn update_run(
+        &mut self,
+        database: &Database,
+        last_run: i64,
+        last_success: bool,
+        attempts: i32,
+        next_run: i64,
+    ) -> Result<(), Error> {
+        query("UPDATE job SET last_run = $1, last_success = $2, attempts = $3, next_run = $4, triggered = FALSE WHERE name = $5;")
+            .bind(last_run)
+            .bind(last_success)
+            .bind(attempts)
+            .bind(next_run)
+            .bind(&self.name)
+            .execute(&database.connection)
+            .await?;
+
+        self.last_run = Some(last_run);
+        self.last_success = Some(last_success);
+        self.attempts = attempts;
+        self.next_run = next_run;
+        self.triggered = false;
+        Ok(())
+    }
+
+    /// Returns false for unknown jobs.
+    pub async fn trigger(database: &Database, name: &str, now: i64) -> Result<bool, Error> {
+        query("UPDATE job SET triggered = TRUE, next_run = LEAST(next_run, $1) WHERE name = $2;")
+            .bind(now)
+            .bind(name)
+            .execute(&database.connection)
+            .await
+            .map(|result| result.rows_affected() > 0)
+    }
+}
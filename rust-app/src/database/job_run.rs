@@ -0,0 +1,70 @@
+use serde::{Deserialize, Serialize};
+use sqlx::{Error, FromRow, query, query_as};
+
+use crate::database::{Database, DatabaseConnection};
+
+pub async fn create_table(connection: &DatabaseConnection) {
+    sqlx::raw_sql(
+        "CREATE TABLE IF NOT EXISTS job_run(id SERIAL PRIMARY KEY, name TEXT NOT NULL, started INT8 NOT NULL, finished INT8 NOT NULL, success BOOLEAN NOT NULL, error TEXT, attempt INT4 NOT NULL, manual BOOLEAN NOT NULL)"
+    )
+    .execute(connection)
+    .await
+    .unwrap_or_else(|e| panic!("Could not create job_run table: {e}"));
+
+    sqlx::raw_sql("CREATE INDEX IF NOT EXISTS job_run_name_started ON job_run(name, started)")
+        .execute(connection)
+        .await
+        .unwrap_or_else(|e| panic!("Could not create job_run index: {e}"));
+}
+
+#[derive(Debug, FromRow, Serialize, Deserialize)]
+pub struct DatabaseJobRun {
+    pub id: i32,
+    pub name: String,
+    pub started: i64,
+    pub finished: i64,
+    pub success: bool,
+    pub error: Option<String>,
+    pub attempt: i32,
+    pub manual: bool,
+}
+
+impl DatabaseJobRun {
+    /// Most recent runs first.
+    pub async fn get_latest_by_name(
+        database: &Database,
+        name: &str,
+        limit: i64,
+    ) -> Result<Vec<Self>, Error> {
+        query_as("SELECT id, name, started, finished, success, error, attempt, manual FROM job_run WHERE name = $1 ORDER BY started DESC, id DESC LIMIT $2")
+            .bind(name)
+            .bind(limit)
+            .fetch_all(&database.connection)
+            .await
+    }
+
+    pub async fn insert(&self, database: &Database) -> Result<(), Error> {
+        query("INSERT INTO job_run(name, started, finished, success, error, attempt, manual) VALUES ($1, $2, $3, $4, $5, $6, $7);")
+            .bind(&self.name)
+            .bind(self.started)
+            .bind(self.finished)
+            .bind(self.success)
+            .bind(&self.error)
+            .bind(self.attempt)
+            .bind(self.manual)
+            .execute(&database.connection)
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn delete_before(database: &Database, name: &str, date: i64) -> Result<(), Error> {
+        query("DELETE FROM job_run WHERE name = $1 AND started < $2;")
+            .bind(name)
+            .bind(date)
+            .execute(&database.connection)
+            .await?;
+
+        Ok(())
+    }
+}
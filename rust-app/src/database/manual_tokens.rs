@@ -1,7 +1,7 @@
 use serde::{Deserialize, Serialize};
 use sqlx::{Error, FromRow, query, query_as};
 
-use crate::database::{Database, DatabaseConnection};
+use crate::database::{Database, DatabaseConnection, DatabaseTransaction};
 
 pub async fn create_table(connection: &DatabaseConnection) {
     sqlx::raw_sql(
@@ -69,6 +69,23 @@ impl DatabaseManualTokens {
         Ok(())
     }
 
+    pub async fn insert_transaction(
+        &self,
+        transaction: &mut DatabaseTransaction,
+    ) -> Result<(), Error> {
+        query("INSERT INTO manual_tokens(account, amount, description, release_after, approval_signature, released) VALUES ($1, $2, $3, $4, $5, $6);")
+            .bind(&self.account)
+            .bind(self.amount)
+            .bind(&self.description)
+            .bind(self.release_after)
+            .bind(&self.approval_signature)
+            .bind(self.released)
+            .execute(&mut **transaction)
+            .await?;
+
+        Ok(())
+    }
+
     pub async fn release(&mut self, database: &Database) -> Result<(), Error> {
         query(
             "UPDATE manual_tokens SET released = $1 WHERE account = $2 AND amount = $3 AND description = $4 AND release_after = $5;",
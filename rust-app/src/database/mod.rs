@@ -1,19 +1,40 @@
-use sqlx::{Pool, Postgres, postgres::PgPoolOptions};
+use sqlx::{Pool, Postgres, Transaction, pool::PoolConnection, postgres::PgPoolOptions, query};
 
 use crate::utils::env::database;
 
+pub mod admin_proposal;
+pub mod admin_signer;
 pub mod agreement;
+pub mod auto_renew;
 pub mod claim;
+pub mod claim_merkle;
+pub mod claim_signature;
 pub mod credits;
+pub mod custom_domain;
+pub mod denial;
+pub mod denylist;
 pub mod deployment_signature;
+pub mod heal_event;
+pub mod health_sample;
+pub mod inventory_issue;
+pub mod job;
+pub mod job_run;
 pub mod manual_tokens;
 pub mod nft_staking;
+pub mod notification;
 pub mod participated;
+pub mod power_action;
 pub mod promo_code;
+pub mod reservation;
+pub mod server_controller;
+pub mod staking_run;
+pub mod subdomain;
 pub mod tokenized_server;
 pub mod tokens_claimed;
+pub mod used_signature;
 
 pub type DatabaseConnection = Pool<Postgres>;
+pub type DatabaseTransaction = Transaction<'static, Postgres>;
 
 #[derive(Clone)]
 pub struct Database {
@@ -26,6 +47,46 @@ impl Database {
             connection: create_connection().await,
         }
     }
+
+    /// Writes made through the transaction only persist once it is committed.
+    pub async fn begin(&self) -> Result<DatabaseTransaction, sqlx::Error> {
+        self.connection.begin().await
+    }
+
+    /// Postgres advisory lock shared by all instances on this database, None when held elsewhere.
+    pub async fn try_lock(&self, key: &str) -> Result<Option<DatabaseLock>, sqlx::Error> {
+        // Advisory locks belong to the session, so the connection is kept until unlocked
+        let mut connection = self.connection.acquire().await?;
+        let locked: bool = sqlx::query_scalar("SELECT pg_try_advisory_lock(hashtext($1))")
+            .bind(key)
+            .fetch_one(&mut *connection)
+            .await?;
+        Ok(locked.then(|| DatabaseLock {
+            connection,
+            key: key.to_string(),
+        }))
+    }
+}
+
+pub struct DatabaseLock {
+    connection: PoolConnection<Postgres>,
+    key: String,
+}
+
+impl DatabaseLock {
+    pub async fn unlock(mut self) -> Result<(), sqlx::Error> {
+        if let Err(e) = query("SELECT pg_advisory_unlock(hashtext($1))")
+            .bind(&self.key)
+            .execute(&mut *self.connection)
+            .await
+        {
+            // Closing the session releases the lock as well
+            self.connection.close_on_drop();
+            return Err(e);
+        }
+
+        Ok(())
+    }
 }
 
 pub async fn create_connection() -> DatabaseConnection {
@@ -35,16 +96,36 @@ pub async fn create_connection() -> DatabaseConnection {
         .await
         .unwrap_or_else(|e| panic!("Could not establish database connection: {e}"));
 
+    admin_proposal::create_table(&connection).await;
+    admin_signer::create_table(&connection).await;
     agreement::create_table(&connection).await;
+    auto_renew::create_table(&connection).await;
     claim::create_table(&connection).await;
+    claim_merkle::create_table(&connection).await;
+    claim_signature::create_table(&connection).await;
     credits::create_table(&connection).await;
+    custom_domain::create_table(&connection).await;
+    denial::create_table(&connection).await;
+    denylist::create_table(&connection).await;
     deployment_signature::create_table(&connection).await;
+    heal_event::create_table(&connection).await;
+    health_sample::create_table(&connection).await;
+    inventory_issue::create_table(&connection).await;
+    job::create_table(&connection).await;
+    job_run::create_table(&connection).await;
     manual_tokens::create_table(&connection).await;
     participated::create_table(&connection).await;
+    power_action::create_table(&connection).await;
     promo_code::create_table(&connection).await;
+    reservation::create_table(&connection).await;
+    server_controller::create_table(&connection).await;
+    staking_run::create_table(&connection).await;
+    subdomain::create_table(&connection).await;
     nft_staking::create_table(&connection).await;
+    notification::create_table(&connection).await;
     tokenized_server::create_table(&connection).await;
     tokens_claimed::create_table(&connection).await;
+    used_signature::create_table(&connection).await;
 
     connection
 }
@@ -1,7 +1,7 @@
 use serde::{Deserialize, Serialize};
 use sqlx::{Error, FromRow, query, query_as, query_scalar};
 
-use crate::database::{Database, DatabaseConnection};
+use crate::database::{Database, DatabaseConnection, DatabaseTransaction};
 
 pub async fn create_table(connection: &DatabaseConnection) {
     sqlx::raw_sql(
@@ -10,6 +10,19 @@ pub async fn create_table(connection: &DatabaseConnection) {
     .execute(connection)
     .await
     .unwrap_or_else(|e| panic!("Could not create nft_staking table: {e}"));
+
+    sqlx::raw_sql("ALTER TABLE nft_staking ADD COLUMN IF NOT EXISTS day INT8")
+        .execute(connection)
+        .await
+        .unwrap_or_else(|e| panic!("Could not add day to nft_staking table: {e}"));
+
+    // Each server is rewarded at most once per day
+    sqlx::raw_sql(
+        "CREATE UNIQUE INDEX IF NOT EXISTS nft_staking_token_day ON nft_staking(collection, chain, token_id, day)",
+    )
+    .execute(connection)
+    .await
+    .unwrap_or_else(|e| panic!("Could not create nft_staking index: {e}"));
 }
 
 #[derive(Debug, FromRow, Serialize, Deserialize)]
@@ -20,12 +33,14 @@ pub struct DatabaseNFTStaking {
     pub chain: String,
     pub token_id: String,
     pub date: i64,
+    /// Distribution day (days since unix epoch), missing on rewards from before distribution runs
+    pub day: Option<i64>,
 }
 
 impl DatabaseNFTStaking {
     #[allow(dead_code)]
     pub async fn get_all(database: &Database) -> Result<Vec<Self>, Error> {
-        query_as("SELECT account, amount, collection, chain, token_id, date FROM nft_staking")
+        query_as("SELECT account, amount, collection, chain, token_id, date, day FROM nft_staking")
             .fetch_all(&database.connection)
             .await
     }
@@ -34,7 +49,7 @@ impl DatabaseNFTStaking {
         database: &Database,
         account: &str,
     ) -> Result<Vec<Self>, Error> {
-        query_as("SELECT account, amount, collection, chain, token_id, date FROM nft_staking WHERE account = $1")
+        query_as("SELECT account, amount, collection, chain, token_id, date, day FROM nft_staking WHERE account = $1")
             .bind(account)
             .fetch_all(&database.connection)
             .await
@@ -46,7 +61,7 @@ impl DatabaseNFTStaking {
         chain: &str,
         token_id: &str,
     ) -> Result<Vec<Self>, Error> {
-        query_as("SELECT account, amount, collection, chain, token_id, date FROM nft_staking WHERE collection = $1 AND chain = $2 AND token_id = $3")
+        query_as("SELECT account, amount, collection, chain, token_id, date, day FROM nft_staking WHERE collection = $1 AND chain = $2 AND token_id = $3")
             .bind(collection)
             .bind(chain)
             .bind(token_id)
@@ -64,7 +79,7 @@ impl DatabaseNFTStaking {
             .await
     }
 
-    pub async fn insert(&self, database: &Database) -> Result<(), Error> {
+    pub async fn insert(&self, transaction: &mut DatabaseTransaction) -> Result<(), Error> {
         let Self {
             account,
             amount,
@@ -72,16 +87,18 @@ impl DatabaseNFTStaking {
             chain,
             token_id,
             date,
+            day,
         } = self;
 
-        query("INSERT INTO nft_staking(account, amount, collection, chain, token_id, date) VALUES ($1, $2, $3, $4, $5, $6);")
+        query("INSERT INTO nft_staking(account, amount, collection, chain, token_id, date, day) VALUES ($1, $2, $3, $4, $5, $6, $7);")
             .bind(account)
             .bind(amount)
             .bind(collection)
             .bind(chain)
             .bind(token_id)
             .bind(date)
-            .execute(&database.connection)
+            .bind(day)
+            .execute(&mut **transaction)
             .await?;
 
         Ok(())
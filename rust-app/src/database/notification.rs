@@ -0,0 +1,51 @@
+use serde::{Deserialize, Serialize};
+use sqlx::{Error, FromRow, query, query_as};
+
+use crate::database::{Database, DatabaseConnection};
+
+pub async fn create_table(connection: &DatabaseConnection) {
+    sqlx::raw_sql(
+        "CREATE TABLE IF NOT EXISTS notification(id SERIAL PRIMARY KEY, account TEXT NOT NULL, message TEXT NOT NULL, date INT8 NOT NULL)",
+    )
+    .execute(connection)
+    .await
+    .unwrap_or_else(|e| panic!("Could not create notification table: {e}"));
+}
+
+#[derive(Debug, FromRow, Serialize, Deserialize)]
+pub struct DatabaseNotification {
+    pub account: String,
+    pub message: String,
+    pub date: i64,
+}
+
+impl DatabaseNotification {
+    pub async fn get_all_by_account(
+        database: &Database,
+        account: &str,
+    ) -> Result<Vec<Self>, Error> {
+        query_as(
+            "SELECT account, message, date FROM notification WHERE account = $1 ORDER BY date DESC",
+        )
+        .bind(account)
+        .fetch_all(&database.connection)
+        .await
+    }
+
+    pub async fn insert(&self, database: &Database) -> Result<(), Error> {
+        let Self {
+            account,
+            message,
+            date,
+        } = self;
+
+        query("INSERT INTO notification(account, message, date) VALUES ($1, $2, $3);")
+            .bind(account)
+            .bind(message)
+            .bind(date)
+            .execute(&database.connection)
+            .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,97 @@
+use serde::{Deserialize, Serialize};
+use sqlx::{Error, FromRow, query, query_as, query_scalar};
+
+use crate::database::{Database, DatabaseConnection};
+
+pub async fn create_table(connection: &DatabaseConnection) {
+    sqlx::raw_sql(
+        "CREATE TABLE IF NOT EXISTS power_action(id SERIAL PRIMARY KEY, collection TEXT NOT NULL, chain TEXT NOT NULL, token_id TEXT NOT NULL, action TEXT NOT NULL, account TEXT NOT NULL, success BOOLEAN, error TEXT, date INT8 NOT NULL)"
+    )
+    .execute(connection)
+    .await
+    .unwrap_or_else(|e| panic!("Could not create power_action table: {e}"));
+}
+
+#[derive(Debug, FromRow, Serialize, Deserialize)]
+pub struct DatabasePowerAction {
+    pub id: i32,
+    pub collection: String,
+    pub chain: String,
+    pub token_id: String,
+    pub action: String,
+    pub account: String,
+    /// None while the action is still running
+    pub success: Option<bool>,
+    pub error: Option<String>,
+    pub date: i64,
+}
+
+impl DatabasePowerAction {
+    pub async fn get_all_by_collection_token_id(
+        database: &Database,
+        collection: &str,
+        chain: &str,
+        token_id: &str,
+    ) -> Result<Vec<Self>, Error> {
+        query_as("SELECT id, collection, chain, token_id, action, account, success, error, date FROM power_action WHERE collection = $1 AND chain = $2 AND token_id = $3 ORDER BY id DESC")
+            .bind(collection)
+            .bind(chain)
+            .bind(token_id)
+            .fetch_all(&database.connection)
+            .await
+    }
+
+    /// Running actions count as well, they have not failed (yet).
+    pub async fn get_last_success_by_collection_token_id(
+        database: &Database,
+        collection: &str,
+        chain: &str,
+        token_id: &str,
+    ) -> Result<Option<Self>, Error> {
+        query_as("SELECT id, collection, chain, token_id, action, account, success, error, date FROM power_action WHERE collection = $1 AND chain = $2 AND token_id = $3 AND success IS NOT FALSE ORDER BY id DESC LIMIT 1")
+            .bind(collection)
+            .bind(chain)
+            .bind(token_id)
+            .fetch_optional(&database.connection)
+            .await
+    }
+
+    /// Inserts the action as running unless the server had an action since the given date, returning its id.
+    pub async fn claim(&self, database: &Database, since: i64) -> Result<Option<i32>, Error> {
+        let mut transaction = database.begin().await?;
+        // Concurrent claims of the server wait here, so the next one sees the inserted action
+        query("SELECT pg_advisory_xact_lock(hashtext($1))")
+            .bind(format!(
+                "power_action {collection}@{chain}@{token_id}",
+                collection = self.collection,
+                chain = self.chain,
+                token_id = self.token_id
+            ))
+            .execute(&mut *transaction)
+            .await?;
+        let id = query_scalar("INSERT INTO power_action(collection, chain, token_id, action, account, success, error, date) SELECT $1, $2, $3, $4, $5, NULL, NULL, $6 WHERE NOT EXISTS (SELECT 1 FROM power_action WHERE collection = $1 AND chain = $2 AND token_id = $3 AND date > $7) RETURNING id")
+            .bind(&self.collection)
+            .bind(&self.chain)
+            .bind(&self.token_id)
+            .bind(&self.action)
+            .bind(&self.account)
+            .bind(self.date)
+            .bind(since)
+            .fetch_optional(&mut *transaction)
+            .await?;
+        transaction.commit().await?;
+
+        Ok(id)
+    }
+
+    pub async fn finish(database: &Database, id: i32, error: Option<String>) -> Result<(), Error> {
+        query("UPDATE power_action SET success = $2, error = $3 WHERE id = $1;")
+            .bind(id)
+            .bind(error.is_none())
+            .bind(error)
+            .execute(&database.connection)
+            .await?;
+
+        Ok(())
+    }
+}
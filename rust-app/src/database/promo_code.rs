@@ -1,7 +1,7 @@
 use serde::{Deserialize, Serialize};
 use sqlx::{Error, FromRow, query, query_as};
 
-use crate::database::{Database, DatabaseConnection};
+use crate::database::{Database, DatabaseConnection, DatabaseTransaction};
 
 pub async fn create_table(connection: &DatabaseConnection) {
     sqlx::raw_sql(
@@ -70,6 +70,21 @@ impl DatabasePromoCode {
         Ok(())
     }
 
+    pub async fn insert_transaction(
+        &self,
+        transaction: &mut DatabaseTransaction,
+    ) -> Result<(), Error> {
+        query("INSERT INTO promo_code(code, credits, description, redeemed_by) VALUES ($1, $2, $3, $4);")
+            .bind(&self.code)
+            .bind(self.credits)
+            .bind(&self.description)
+            .bind(&self.redeemed_by)
+            .execute(&mut **transaction)
+            .await?;
+
+        Ok(())
+    }
+
     pub async fn redeem(&mut self, database: &Database, redeemed_by: &str) -> Result<(), Error> {
         query("UPDATE promo_code SET redeemed_by = $1 WHERE code = $2 AND redeemed_by IS NULL;")
             .bind(redeemed_by)
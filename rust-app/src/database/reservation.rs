@@ -0,0 +1,197 @@
+use std::fmt::Display;
+
+use serde::{Deserialize, Serialize};
+use sqlx::{Error, FromRow, query, query_as, query_scalar};
+
+use crate::database::{Database, DatabaseConnection, DatabaseTransaction};
+
+pub async fn create_table(connection: &DatabaseConnection) {
+    sqlx::raw_sql(
+        "CREATE TABLE IF NOT EXISTS reservation(id SERIAL PRIMARY KEY, collection TEXT NOT NULL, chain TEXT NOT NULL, tier TEXT NOT NULL, to_account TEXT NOT NULL, payer TEXT NOT NULL, payer_signature TEXT NOT NULL, hold INT8 NOT NULL, status TEXT NOT NULL, token_id TEXT, created_at INT8 NOT NULL)"
+    )
+    .execute(connection)
+    .await
+    .unwrap_or_else(|e| panic!("Could not create reservation table: {e}"));
+}
+
+pub enum ReservationStatus {
+    Waiting,
+    Minted,
+    Cancelled,
+}
+
+impl Display for ReservationStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReservationStatus::Waiting => f.write_str("waiting"),
+            ReservationStatus::Minted => f.write_str("minted"),
+            ReservationStatus::Cancelled => f.write_str("cancelled"),
+        }
+    }
+}
+
+#[derive(Debug, FromRow, Serialize, Deserialize)]
+pub struct DatabaseReservation {
+    pub id: i32,
+    pub collection: String,
+    pub chain: String,
+    pub tier: String,
+    pub to_account: String,
+    pub payer: String,
+    pub payer_signature: String,
+    pub hold: i64,
+    pub status: String,
+    pub token_id: Option<String>,
+    pub created_at: i64,
+}
+
+impl DatabaseReservation {
+    pub async fn get_by_id(database: &Database, id: i32) -> Result<Option<Self>, Error> {
+        query_as("SELECT id, collection, chain, tier, to_account, payer, payer_signature, hold, status, token_id, created_at FROM reservation WHERE id = $1")
+            .bind(id)
+            .fetch_optional(&database.connection)
+            .await
+    }
+
+    pub async fn get_all_waiting(database: &Database) -> Result<Vec<Self>, Error> {
+        query_as("SELECT id, collection, chain, tier, to_account, payer, payer_signature, hold, status, token_id, created_at FROM reservation WHERE status = $1 ORDER BY id ASC")
+            .bind(ReservationStatus::Waiting.to_string())
+            .fetch_all(&database.connection)
+            .await
+    }
+
+    pub async fn get_all_by_to_account(
+        database: &Database,
+        to_account: &str,
+    ) -> Result<Vec<Self>, Error> {
+        query_as("SELECT id, collection, chain, tier, to_account, payer, payer_signature, hold, status, token_id, created_at FROM reservation WHERE to_account = $1 ORDER BY id DESC")
+            .bind(to_account)
+            .fetch_all(&database.connection)
+            .await
+    }
+
+    pub async fn get_waiting_count_by_tier(
+        database: &Database,
+        collection: &str,
+        chain: &str,
+        tier: &str,
+    ) -> Result<i64, Error> {
+        query_scalar("SELECT COUNT(*) FROM reservation WHERE collection = $1 AND chain = $2 AND tier = $3 AND status = $4")
+            .bind(collection)
+            .bind(chain)
+            .bind(tier)
+            .bind(ReservationStatus::Waiting.to_string())
+            .fetch_one(&database.connection)
+            .await
+    }
+
+    /// Position in the queue of the reservation tier, starting at 1.
+    pub async fn get_position(&self, database: &Database) -> Result<i64, Error> {
+        query_scalar("SELECT COUNT(*) FROM reservation WHERE collection = $1 AND chain = $2 AND tier = $3 AND status = $4 AND id <= $5")
+            .bind(&self.collection)
+            .bind(&self.chain)
+            .bind(&self.tier)
+            .bind(ReservationStatus::Waiting.to_string())
+            .bind(self.id)
+            .fetch_one(&database.connection)
+            .await
+    }
+
+    pub async fn insert(&mut self, database: &Database) -> Result<(), Error> {
+        let id: i32 = query_scalar("INSERT INTO reservation(collection, chain, tier, to_account, payer, payer_signature, hold, status, token_id, created_at) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10) RETURNING id")
+            .bind(&self.collection)
+            .bind(&self.chain)
+            .bind(&self.tier)
+            .bind(&self.to_account)
+            .bind(&self.payer)
+            .bind(&self.payer_signature)
+            .bind(self.hold)
+            .bind(&self.status)
+            .bind(&self.token_id)
+            .bind(self.created_at)
+            .fetch_one(&database.connection)
+            .await?;
+
+        self.id = id;
+
+        Ok(())
+    }
+
+    /// Returns false when the reservation was no longer waiting.
+    pub async fn fulfill(&mut self, database: &Database) -> Result<bool, Error> {
+        self.update_status(
+            database,
+            ReservationStatus::Waiting,
+            ReservationStatus::Minted,
+        )
+        .await
+    }
+
+    /// Puts a reservation whose mint failed back in the queue, keeping its hold.
+    pub async fn unfulfill(&mut self, database: &Database) -> Result<bool, Error> {
+        self.update_status(
+            database,
+            ReservationStatus::Minted,
+            ReservationStatus::Waiting,
+        )
+        .await
+    }
+
+    pub async fn update_token_id(
+        &mut self,
+        database: &Database,
+        token_id: String,
+    ) -> Result<(), Error> {
+        query("UPDATE reservation SET token_id = $1 WHERE id = $2;")
+            .bind(&token_id)
+            .bind(self.id)
+            .execute(&database.connection)
+            .await?;
+
+        self.token_id = Some(token_id);
+        Ok(())
+    }
+
+    /// Cancel as part of releasing its hold. Returns false when the reservation was no longer waiting.
+    pub async fn cancel_transaction(
+        &mut self,
+        transaction: &mut DatabaseTransaction,
+    ) -> Result<bool, Error> {
+        let status = ReservationStatus::Cancelled.to_string();
+        let result = query("UPDATE reservation SET status = $1 WHERE id = $2 AND status = $3;")
+            .bind(&status)
+            .bind(self.id)
+            .bind(ReservationStatus::Waiting.to_string())
+            .execute(&mut **transaction)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Ok(false);
+        }
+
+        self.status = status;
+        Ok(true)
+    }
+
+    async fn update_status(
+        &mut self,
+        database: &Database,
+        from: ReservationStatus,
+        status: ReservationStatus,
+    ) -> Result<bool, Error> {
+        let status = status.to_string();
+        let result = query("UPDATE reservation SET status = $1 WHERE id = $2 AND status = $3;")
+            .bind(&status)
+            .bind(self.id)
+            .bind(from.to_string())
+            .execute(&database.connection)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Ok(false);
+        }
+
+        self.status = status;
+        Ok(true)
+    }
+}
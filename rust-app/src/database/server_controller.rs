@@ -0,0 +1,91 @@
+use serde::{Deserialize, Serialize};
+use sqlx::{Error, FromRow, query, query_as};
+
+use crate::database::{Database, DatabaseConnection};
+
+pub async fn create_table(connection: &DatabaseConnection) {
+    sqlx::raw_sql(
+        "CREATE TABLE IF NOT EXISTS server_controller(collection TEXT NOT NULL, chain TEXT NOT NULL, token_id TEXT NOT NULL, controller TEXT NOT NULL, role TEXT NOT NULL, created_at INT8 NOT NULL, PRIMARY KEY (collection, chain, token_id, controller))"
+    )
+    .execute(connection)
+    .await
+    .unwrap_or_else(|e| panic!("Could not create server_controller table: {e}"));
+}
+
+/// Additional controller of a tokenized server, next to the main controller in tokenized_server.
+#[derive(Debug, FromRow, Serialize, Deserialize)]
+pub struct DatabaseServerController {
+    pub collection: String,
+    pub chain: String,
+    pub token_id: String,
+    pub controller: String,
+    pub role: String,
+    pub created_at: i64,
+}
+
+impl DatabaseServerController {
+    pub async fn get_all_by_collection_token_id(
+        database: &Database,
+        collection: &str,
+        chain: &str,
+        token_id: &str,
+    ) -> Result<Vec<Self>, Error> {
+        query_as("SELECT collection, chain, token_id, controller, role, created_at FROM server_controller WHERE collection = $1 AND chain = $2 AND token_id = $3 ORDER BY created_at ASC")
+            .bind(collection)
+            .bind(chain)
+            .bind(token_id)
+            .fetch_all(&database.connection)
+            .await
+    }
+
+    /// Inserts the controller or updates its role.
+    pub async fn upsert(&self, database: &Database) -> Result<(), Error> {
+        query("INSERT INTO server_controller(collection, chain, token_id, controller, role, created_at) VALUES ($1, $2, $3, $4, $5, $6) ON CONFLICT (collection, chain, token_id, controller) DO UPDATE SET role = EXCLUDED.role;")
+            .bind(&self.collection)
+            .bind(&self.chain)
+            .bind(&self.token_id)
+            .bind(&self.controller)
+            .bind(&self.role)
+            .bind(self.created_at)
+            .execute(&database.connection)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Returns whether the controller existed.
+    pub async fn delete(
+        database: &Database,
+        collection: &str,
+        chain: &str,
+        token_id: &str,
+        controller: &str,
+    ) -> Result<bool, Error> {
+        query("DELETE FROM server_controller WHERE collection = $1 AND chain = $2 AND token_id = $3 AND controller = $4;")
+            .bind(collection)
+            .bind(chain)
+            .bind(token_id)
+            .bind(controller)
+            .execute(&database.connection)
+            .await
+            .map(|result| result.rows_affected() > 0)
+    }
+
+    pub async fn delete_by_collection_token_id(
+        database: &Database,
+        collection: &str,
+        chain: &str,
+        token_id: &str,
+    ) -> Result<(), Error> {
+        query(
+            "DELETE FROM server_controller WHERE collection = $1 AND chain = $2 AND token_id = $3;",
+        )
+        .bind(collection)
+        .bind(chain)
+        .bind(token_id)
+        .execute(&database.connection)
+        .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,60 @@
+use serde::{Deserialize, Serialize};
+use sqlx::{Error, FromRow, query, query_as, query_scalar};
+
+use crate::database::{Database, DatabaseConnection, DatabaseTransaction};
+
+pub async fn create_table(connection: &DatabaseConnection) {
+    sqlx::raw_sql(
+        "CREATE TABLE IF NOT EXISTS staking_run(collection TEXT NOT NULL, day INT8 NOT NULL, version TEXT NOT NULL, emission INT8 NOT NULL, servers INT4 NOT NULL, date INT8 NOT NULL, PRIMARY KEY (collection, day))"
+    )
+    .execute(connection)
+    .await
+    .unwrap_or_else(|e| panic!("Could not create staking_run table: {e}"));
+}
+
+/// Completed staking distribution of a collection for a day (days since unix epoch).
+#[derive(Debug, FromRow, Serialize, Deserialize)]
+pub struct DatabaseStakingRun {
+    pub collection: String,
+    pub day: i64,
+    pub version: String,
+    pub emission: i64,
+    pub servers: i32,
+    pub date: i64,
+}
+
+impl DatabaseStakingRun {
+    pub async fn get_all_by_collection(
+        database: &Database,
+        collection: &str,
+    ) -> Result<Vec<Self>, Error> {
+        query_as("SELECT collection, day, version, emission, servers, date FROM staking_run WHERE collection = $1 ORDER BY day DESC")
+            .bind(collection)
+            .fetch_all(&database.connection)
+            .await
+    }
+
+    pub async fn get_last_day_by_collection(
+        database: &Database,
+        collection: &str,
+    ) -> Result<Option<i64>, Error> {
+        query_scalar("SELECT MAX(day) FROM staking_run WHERE collection = $1")
+            .bind(collection)
+            .fetch_one(&database.connection)
+            .await
+    }
+
+    /// Returns false when the day was already distributed (by another instance).
+    pub async fn insert(&self, transaction: &mut DatabaseTransaction) -> Result<bool, Error> {
+        query("INSERT INTO staking_run(collection, day, version, emission, servers, date) VALUES ($1, $2, $3, $4, $5, $6) ON CONFLICT (collection, day) DO NOTHING;")
+            .bind(&self.collection)
+            .bind(self.day)
+            .bind(&self.version)
+            .bind(self.emission)
+            .bind(self.servers)
+            .bind(self.date)
+            .execute(&mut **transaction)
+            .await
+            .map(|result| result.rows_affected() > 0)
+    }
+}
@@ -0,0 +1,102 @@
+use std::fmt::Display;
+
+use serde::{Deserialize, Serialize};
+use sqlx::{Error, FromRow, query, query_as};
+
+use crate::database::{Database, DatabaseConnection};
+
+pub async fn create_table(connection: &DatabaseConnection) {
+    sqlx::raw_sql(
+        "CREATE TABLE IF NOT EXISTS subdomain(collection TEXT NOT NULL, chain TEXT NOT NULL, token_id TEXT NOT NULL, subdomain TEXT NOT NULL, ipv4 TEXT, status TEXT NOT NULL, error TEXT, updated_at INT8 NOT NULL, PRIMARY KEY (collection, chain, token_id))"
+    )
+    .execute(connection)
+    .await
+    .unwrap_or_else(|e| panic!("Could not create subdomain table: {e}"));
+}
+
+pub enum SubdomainStatus {
+    Reserved,
+    ReserveFailed,
+    Released,
+    ReleaseFailed,
+}
+
+impl Display for SubdomainStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SubdomainStatus::Reserved => f.write_str("reserved"),
+            SubdomainStatus::ReserveFailed => f.write_str("reserve_failed"),
+            SubdomainStatus::Released => f.write_str("released"),
+            SubdomainStatus::ReleaseFailed => f.write_str("release_failed"),
+        }
+    }
+}
+
+#[derive(Debug, FromRow, Serialize, Deserialize)]
+pub struct DatabaseSubdomain {
+    pub collection: String,
+    pub chain: String,
+    pub token_id: String,
+    pub subdomain: String,
+    pub ipv4: Option<String>,
+    pub status: String,
+    pub error: Option<String>,
+    pub updated_at: i64,
+}
+
+impl DatabaseSubdomain {
+    pub async fn get_all(database: &Database) -> Result<Vec<Self>, Error> {
+        query_as("SELECT collection, chain, token_id, subdomain, ipv4, status, error, updated_at FROM subdomain")
+            .fetch_all(&database.connection)
+            .await
+    }
+
+    pub async fn get_all_failed(database: &Database) -> Result<Vec<Self>, Error> {
+        query_as("SELECT collection, chain, token_id, subdomain, ipv4, status, error, updated_at FROM subdomain WHERE status = $1 OR status = $2")
+            .bind(SubdomainStatus::ReserveFailed.to_string())
+            .bind(SubdomainStatus::ReleaseFailed.to_string())
+            .fetch_all(&database.connection)
+            .await
+    }
+
+    pub async fn get_by_collection_token_id(
+        database: &Database,
+        collection: &str,
+        chain: &str,
+        token_id: &str,
+    ) -> Result<Option<Self>, Error> {
+        query_as("SELECT collection, chain, token_id, subdomain, ipv4, status, error, updated_at FROM subdomain WHERE collection = $1 AND chain = $2 AND token_id = $3")
+            .bind(collection)
+            .bind(chain)
+            .bind(token_id)
+            .fetch_optional(&database.connection)
+            .await
+    }
+
+    pub async fn upsert(&self, database: &Database) -> Result<(), Error> {
+        let Self {
+            collection,
+            chain,
+            token_id,
+            subdomain,
+            ipv4,
+            status,
+            error,
+            updated_at,
+        } = self;
+
+        query("INSERT INTO subdomain(collection, chain, token_id, subdomain, ipv4, status, error, updated_at) VALUES ($1, $2, $3, $4, $5, $6, $7, $8) ON CONFLICT (collection, chain, token_id) DO UPDATE SET subdomain = EXCLUDED.subdomain, ipv4 = EXCLUDED.ipv4, status = EXCLUDED.status, error = EXCLUDED.error, updated_at = EXCLUDED.updated_at;")
+        .bind(collection)
+        .bind(chain)
+        .bind(token_id)
+        .bind(subdomain)
+        .bind(ipv4)
+        .bind(status)
+        .bind(error)
+        .bind(updated_at)
+        .execute(&database.connection)
+        .await?;
+
+        Ok(())
+    }
+}
@@ -1,9 +1,12 @@
-use std::fmt::Display;
+use std::{collections::BTreeMap, fmt::Display};
 
 use serde::{Deserialize, Serialize};
 use sqlx::{Error, FromRow, query, query_as, query_scalar, types::Json};
 
-use crate::database::{Database, DatabaseConnection};
+use crate::{
+    database::{Database, DatabaseConnection, DatabaseTransaction},
+    utils::time::get_time_i64,
+};
 
 pub async fn create_table(connection: &DatabaseConnection) {
     sqlx::raw_sql(
@@ -12,6 +15,22 @@ pub async fn create_table(connection: &DatabaseConnection) {
     .execute(connection)
     .await
     .unwrap_or_else(|e| panic!("Could not create tokenized_server table: {e}"));
+
+    sqlx::raw_sql("ALTER TABLE tokenized_server ADD COLUMN IF NOT EXISTS tier TEXT")
+        .execute(connection)
+        .await
+        .unwrap_or_else(|e| panic!("Could not add tier to tokenized_server table: {e}"));
+
+    sqlx::raw_sql("ALTER TABLE tokenized_server ADD COLUMN IF NOT EXISTS preset TEXT, ADD COLUMN IF NOT EXISTS preset_parameters JSON")
+        .execute(connection)
+        .await
+        .unwrap_or_else(|e| panic!("Could not add preset to tokenized_server table: {e}"));
+
+    // Existing servers count as held since the column was added
+    sqlx::raw_sql("ALTER TABLE tokenized_server ADD COLUMN IF NOT EXISTS owner_since INT8 NOT NULL DEFAULT EXTRACT(EPOCH FROM CURRENT_TIMESTAMP)::INT8")
+        .execute(connection)
+        .await
+        .unwrap_or_else(|e| panic!("Could not add owner_since to tokenized_server table: {e}"));
 }
 
 pub enum Collection {
@@ -41,6 +60,7 @@ impl Display for Chain {
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub enum TokenizedServerDeployment {
     Hyperstack { id: u64 },
+    Mock { id: u64 },
 }
 
 #[derive(Debug, FromRow, Serialize, Deserialize)]
@@ -52,41 +72,66 @@ pub struct DatabaseTokenizedServer {
     pub controller: String,
     pub deployment: Option<Json<TokenizedServerDeployment>>,
     pub expires: i64,
+    pub tier: Option<String>,
+    pub preset: Option<String>,
+    pub preset_parameters: Option<Json<BTreeMap<String, String>>>,
+    /// When the current owner received the server
+    pub owner_since: i64,
 }
 
 impl DatabaseTokenizedServer {
     #[allow(dead_code)]
     pub async fn get_all(database: &Database) -> Result<Vec<Self>, Error> {
-        query_as("SELECT collection, chain, token_id, owner, controller, deployment, expires FROM tokenized_server")
+        query_as("SELECT collection, chain, token_id, owner, controller, deployment, expires, tier, preset, preset_parameters, owner_since FROM tokenized_server")
             .fetch_all(&database.connection)
             .await
     }
 
     pub async fn get_all_by_owner(database: &Database, owner: &str) -> Result<Vec<Self>, Error> {
-        query_as("SELECT collection, chain, token_id, owner, controller, deployment, expires FROM tokenized_server WHERE owner = $1")
+        query_as("SELECT collection, chain, token_id, owner, controller, deployment, expires, tier, preset, preset_parameters, owner_since FROM tokenized_server WHERE owner = $1")
             .bind(owner)
             .fetch_all(&database.connection)
             .await
     }
 
+    /// Servers with the main or an additional controller.
     pub async fn get_all_by_controller(
         database: &Database,
         controller: &str,
     ) -> Result<Vec<Self>, Error> {
-        query_as("SELECT collection, chain, token_id, owner, controller, deployment, expires FROM tokenized_server WHERE controller = $1")
+        query_as("SELECT collection, chain, token_id, owner, controller, deployment, expires, tier, preset, preset_parameters, owner_since FROM tokenized_server WHERE controller = $1 OR EXISTS (SELECT 1 FROM server_controller WHERE server_controller.collection = tokenized_server.collection AND server_controller.chain = tokenized_server.chain AND server_controller.token_id = tokenized_server.token_id AND server_controller.controller = $1)")
             .bind(controller)
             .fetch_all(&database.connection)
             .await
     }
 
+    pub async fn get_all_deployed(database: &Database) -> Result<Vec<Self>, Error> {
+        query_as("SELECT collection, chain, token_id, owner, controller, deployment, expires, tier, preset, preset_parameters, owner_since FROM tokenized_server WHERE deployment IS NOT NULL")
+            .fetch_all(&database.connection)
+            .await
+    }
+
     pub async fn get_all_deployed_expired(database: &Database) -> Result<Vec<Self>, Error> {
-        query_as("SELECT collection, chain, token_id, owner, controller, deployment, expires FROM tokenized_server WHERE deployment IS NOT NULL AND expires < EXTRACT(EPOCH FROM CURRENT_TIMESTAMP)")
+        query_as("SELECT collection, chain, token_id, owner, controller, deployment, expires, tier, preset, preset_parameters, owner_since FROM tokenized_server WHERE deployment IS NOT NULL AND expires < EXTRACT(EPOCH FROM CURRENT_TIMESTAMP)")
             .fetch_all(&database.connection)
             .await
     }
 
+    /// Servers without a stored tier count towards the default tier.
+    pub async fn get_not_expired_count_by_tier(
+        database: &Database,
+        tier: &str,
+        default: bool,
+    ) -> Result<i64, Error> {
+        query_scalar("SELECT COUNT(*) FROM tokenized_server WHERE expires > EXTRACT(EPOCH FROM CURRENT_TIMESTAMP) AND (tier = $1 OR ($2 AND tier IS NULL))")
+            .bind(tier)
+            .bind(default)
+            .fetch_one(&database.connection)
+            .await
+    }
+
     pub async fn get_all_not_expired(database: &Database) -> Result<Vec<Self>, Error> {
-        query_as("SELECT collection, chain, token_id, owner, controller, deployment, expires FROM tokenized_server WHERE expires > EXTRACT(EPOCH FROM CURRENT_TIMESTAMP)")
+        query_as("SELECT collection, chain, token_id, owner, controller, deployment, expires, tier, preset, preset_parameters, owner_since FROM tokenized_server WHERE expires > EXTRACT(EPOCH FROM CURRENT_TIMESTAMP)")
             .fetch_all(&database.connection)
             .await
     }
@@ -97,7 +142,7 @@ impl DatabaseTokenizedServer {
         chain: &str,
         token_id: &str,
     ) -> Result<Option<Self>, Error> {
-        query_as("SELECT collection, chain, token_id, owner, controller, deployment, expires FROM tokenized_server WHERE collection = $1 AND chain = $2 AND token_id = $3")
+        query_as("SELECT collection, chain, token_id, owner, controller, deployment, expires, tier, preset, preset_parameters, owner_since FROM tokenized_server WHERE collection = $1 AND chain = $2 AND token_id = $3")
             .bind(collection)
             .bind(chain)
             .bind(token_id)
@@ -142,9 +187,13 @@ impl DatabaseTokenizedServer {
             controller,
             deployment,
             expires,
+            tier,
+            preset,
+            preset_parameters,
+            owner_since,
         } = self;
 
-        query("INSERT INTO tokenized_server(collection, chain, token_id, owner, controller, deployment, expires) VALUES ($1, $2, $3, $4, $5, $6, $7);")
+        query("INSERT INTO tokenized_server(collection, chain, token_id, owner, controller, deployment, expires, tier, preset, preset_parameters, owner_since) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11);")
         .bind(collection)
         .bind(chain)
         .bind(token_id)
@@ -152,6 +201,10 @@ impl DatabaseTokenizedServer {
         .bind(controller)
         .bind(deployment)
         .bind(expires)
+        .bind(tier)
+        .bind(preset)
+        .bind(preset_parameters)
+        .bind(owner_since)
         .execute(&database.connection)
         .await?;
 
@@ -159,8 +212,10 @@ impl DatabaseTokenizedServer {
     }
 
     pub async fn update_owner(&mut self, database: &Database, owner: String) -> Result<(), Error> {
-        query("UPDATE tokenized_server SET owner = $1 WHERE collection = $2 AND chain = $3 AND token_id = $4;")
+        let owner_since = get_time_i64();
+        query("UPDATE tokenized_server SET owner = $1, owner_since = $2 WHERE collection = $3 AND chain = $4 AND token_id = $5;")
             .bind(&owner)
+            .bind(owner_since)
             .bind(&self.collection)
             .bind(&self.chain)
             .bind(&self.token_id)
@@ -168,6 +223,7 @@ impl DatabaseTokenizedServer {
             .await?;
 
         self.owner = owner;
+        self.owner_since = owner_since;
         Ok(())
     }
 
@@ -190,6 +246,26 @@ impl DatabaseTokenizedServer {
         Ok(())
     }
 
+    pub async fn update_preset(
+        &mut self,
+        database: &Database,
+        preset: String,
+        preset_parameters: Json<BTreeMap<String, String>>,
+    ) -> Result<(), Error> {
+        query("UPDATE tokenized_server SET preset = $1, preset_parameters = $2 WHERE collection = $3 AND chain = $4 AND token_id = $5;")
+            .bind(&preset)
+            .bind(&preset_parameters)
+            .bind(&self.collection)
+            .bind(&self.chain)
+            .bind(&self.token_id)
+            .execute(&database.connection)
+            .await?;
+
+        self.preset = Some(preset);
+        self.preset_parameters = Some(preset_parameters);
+        Ok(())
+    }
+
     pub async fn update_expires(&mut self, database: &Database, expires: i64) -> Result<(), Error> {
         query("UPDATE tokenized_server SET expires = $1 WHERE collection = $2 AND chain = $3 AND token_id = $4;")
             .bind(expires)
@@ -203,6 +279,24 @@ impl DatabaseTokenizedServer {
         Ok(())
     }
 
+    /// Update as part of a larger write, such as an auto renewal.
+    pub async fn update_expires_transaction(
+        &mut self,
+        transaction: &mut DatabaseTransaction,
+        expires: i64,
+    ) -> Result<(), Error> {
+        query("UPDATE tokenized_server SET expires = $1 WHERE collection = $2 AND chain = $3 AND token_id = $4;")
+            .bind(expires)
+            .bind(&self.collection)
+            .bind(&self.chain)
+            .bind(&self.token_id)
+            .execute(&mut **transaction)
+            .await?;
+
+        self.expires = expires;
+        Ok(())
+    }
+
     pub async fn deploy(
         &mut self,
         database: &Database,
@@ -1,5 +1,5 @@
 use serde::{Deserialize, Serialize};
-use sqlx::{Error, FromRow, query, query_as};
+use sqlx::{Error, FromRow, query, query_as, query_scalar};
 
 use crate::database::{Database, DatabaseConnection};
 
@@ -39,6 +39,24 @@ impl DatabaseTokensClaimed {
             .await
     }
 
+    /// Latest claim of the account, the on-chain total only increases.
+    pub async fn get_latest_by_account(
+        database: &Database,
+        account: &str,
+    ) -> Result<Option<Self>, Error> {
+        query_as("SELECT account, total, released, transaction_hash, log_index FROM tokens_claimed WHERE account = $1 ORDER BY total DESC LIMIT 1")
+            .bind(account)
+            .fetch_optional(&database.connection)
+            .await
+    }
+
+    /// Sum of the latest on-chain total of every account.
+    pub async fn get_total_claimed(database: &Database) -> Result<i64, Error> {
+        query_scalar("SELECT COALESCE(SUM(total), 0)::INT8 FROM (SELECT MAX(total) AS total FROM tokens_claimed GROUP BY account) latest")
+            .fetch_one(&database.connection)
+            .await
+    }
+
     pub async fn insert(&self, database: &Database) -> Result<(), Error> {
         let Self {
             account,
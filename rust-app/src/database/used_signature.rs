@@ -0,0 +1,47 @@
+use serde::{Deserialize, Serialize};
+use sqlx::{Error, FromRow, query};
+
+use crate::database::{Database, DatabaseConnection};
+
+pub async fn create_table(connection: &DatabaseConnection) {
+    sqlx::raw_sql(
+        "CREATE TABLE IF NOT EXISTS used_signature(hash TEXT NOT NULL PRIMARY KEY, signer TEXT NOT NULL, timestamp INT8 NOT NULL, used_at INT8 NOT NULL)"
+    )
+    .execute(connection)
+    .await
+    .unwrap_or_else(|e| panic!("Could not create used_signature table: {e}"));
+}
+
+/// Signed message that was accepted, identified by the hash of signer and message so malleated signatures count as used too.
+#[derive(Debug, FromRow, Serialize, Deserialize)]
+pub struct DatabaseUsedSignature {
+    pub hash: String,
+    pub signer: String,
+    /// Signing time claimed in the message
+    pub timestamp: i64,
+    pub used_at: i64,
+}
+
+impl DatabaseUsedSignature {
+    /// Returns false when the message was already used.
+    pub async fn insert(&self, database: &Database) -> Result<bool, Error> {
+        query("INSERT INTO used_signature(hash, signer, timestamp, used_at) VALUES ($1, $2, $3, $4) ON CONFLICT (hash) DO NOTHING;")
+            .bind(&self.hash)
+            .bind(&self.signer)
+            .bind(self.timestamp)
+            .bind(self.used_at)
+            .execute(&database.connection)
+            .await
+            .map(|result| result.rows_affected() > 0)
+    }
+
+    /// Messages signed before the given time are rejected by their timestamp already.
+    pub async fn delete_before(database: &Database, timestamp: i64) -> Result<(), Error> {
+        query("DELETE FROM used_signature WHERE timestamp < $1;")
+            .bind(timestamp)
+            .execute(&database.connection)
+            .await?;
+
+        Ok(())
+    }
+}
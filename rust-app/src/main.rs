@@ -7,8 +7,21 @@ use crate::{
     blockchain::start_event_listeners,
     database::Database,
     utils::{
+        auto_renew::renew_expiring_servers,
+        custom_domain::DohResolver,
         env::{hostname, httprpc, port},
+        heal::heal_dead_servers,
+        health::monitor_server_health,
+        inventory::reconcile_inventory,
         manual_tokens::distribute_manual_tokens,
+        merkle::snapshot_claim_merkle,
+        mint::OwnAIV1TokenCounter,
+        replay::prune_used_signatures,
+        reservation::fulfill_reservations,
+        scheduler::{Job, run_scheduler},
+        staking::distribute_staking_rewards,
+        stock::{StockClient, refresh_stocks},
+        subdomain::retry_failed_subdomains,
         xnode::undeploy_expired_servers,
     },
 };
@@ -18,6 +31,97 @@ mod blockchain;
 mod database;
 mod utils;
 
+/// Background jobs, run by a single instance at a time.
+fn jobs(
+    database: Database,
+    provider: DynProvider,
+    stock: StockClient,
+    counter: OwnAIV1TokenCounter,
+) -> Vec<Job> {
+    vec![
+        Job::new("undeploy-expired-servers", "0 * * * * *", {
+            let database = database.clone();
+            move || {
+                let database = database.clone();
+                async move { undeploy_expired_servers(&database).await }
+            }
+        }),
+        Job::new("renew-expiring-servers", "0 * * * * *", {
+            let database = database.clone();
+            move || {
+                let database = database.clone();
+                async move { renew_expiring_servers(&database).await }
+            }
+        }),
+        Job::new("distribute-staking-rewards", "0 0 0 * * *", {
+            let database = database.clone();
+            move || {
+                let database = database.clone();
+                async move { distribute_staking_rewards(&database).await }
+            }
+        }),
+        Job::new("distribute-manual-tokens", "0 0 * * * *", {
+            let database = database.clone();
+            move || {
+                let database = database.clone();
+                async move { distribute_manual_tokens(&database).await }
+            }
+        }),
+        Job::new("reconcile-inventory", "0 */10 * * * *", {
+            let database = database.clone();
+            move || {
+                let database = database.clone();
+                async move { reconcile_inventory(&database).await }
+            }
+        }),
+        Job::new("retry-failed-subdomains", "0 */5 * * * *", {
+            let database = database.clone();
+            move || {
+                let database = database.clone();
+                async move { retry_failed_subdomains(&database).await }
+            }
+        }),
+        Job::new("snapshot-claim-merkle", "0 15 * * * *", {
+            let database = database.clone();
+            move || {
+                let database = database.clone();
+                async move { snapshot_claim_merkle(&database).await }
+            }
+        }),
+        Job::new("prune-used-signatures", "0 30 * * * *", {
+            let database = database.clone();
+            move || {
+                let database = database.clone();
+                async move { prune_used_signatures(&database).await }
+            }
+        }),
+        Job::new("monitor-server-health", "0 * * * * *", {
+            let database = database.clone();
+            move || {
+                let database = database.clone();
+                async move { monitor_server_health(&database).await }
+            }
+        }),
+        // Halfway the minute, so it sees the latest health samples
+        Job::new("heal-dead-servers", "30 * * * * *", {
+            let database = database.clone();
+            let stock = stock.clone();
+            move || {
+                let database = database.clone();
+                let stock = stock.clone();
+                async move { heal_dead_servers(&database, &stock).await }
+            }
+        }),
+        Job::new("fulfill-reservations", "0 * * * * *", move || {
+            let database = database.clone();
+            let provider = provider.clone();
+            let stock = stock.clone();
+            let counter = counter.clone();
+            async move { fulfill_reservations(&database, &provider, &stock, &counter).await }
+        }),
+    ]
+}
+
 #[tokio::main]
 async fn main() {
     env_logger::init();
@@ -27,13 +131,22 @@ async fn main() {
         .connect(&httprpc())
         .await
         .unwrap_or_else(|e| panic!("Could not connect to HTTP rpc provider: {e}"));
-    let token_counter = api::ownai_v1::OwnAIV1TokenCounter::new(database.clone()).await;
+    let token_counter = OwnAIV1TokenCounter::new(database.clone()).await;
+    let stock = StockClient::new();
+    let resolver = DohResolver::new();
 
     if let Err(e) = try_join!(
         spawn(start_event_listeners(database.clone())),
-        spawn(undeploy_expired_servers(database.clone())),
-        // spawn(distribute_staking_rewards(database.clone())),
-        spawn(distribute_manual_tokens(database.clone())),
+        spawn(refresh_stocks(stock.clone())),
+        spawn(run_scheduler(
+            database.clone(),
+            jobs(
+                database.clone(),
+                DynProvider::new(provider.clone()),
+                stock.clone(),
+                token_counter.clone()
+            )
+        )),
         spawn(
             HttpServer::new(move || {
                 App::new()
@@ -41,6 +154,8 @@ async fn main() {
                     .app_data(web::Data::new(database.clone()))
                     .app_data(web::Data::new(DynProvider::new(provider.clone())))
                     .app_data(web::Data::new(token_counter.clone()))
+                    .app_data(web::Data::new(stock.clone()))
+                    .app_data(web::Data::new(resolver.clone()))
                     .service(web::scope("/api").configure(api::configure))
             })
             .bind(format!(
@@ -0,0 +1,112 @@
+use std::{fmt::Display, str::FromStr};
+
+use alloy::providers::Provider;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    database::{Database, admin_signer::DatabaseAdminSigner},
+    utils::{
+        env::{agreementsigner, denylistsigner, jobsigner, manualtokensigner, promocodesigner},
+        replay::{Freshness, use_signature},
+        signature_validator::validate_signature,
+        time::get_time_i64,
+    },
+};
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum AdminRole {
+    ManualTokens,
+    PromoCode,
+    Agreement,
+    Job,
+    Denylist,
+}
+
+impl Display for AdminRole {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AdminRole::ManualTokens => f.write_str("manual_tokens"),
+            AdminRole::PromoCode => f.write_str("promo_code"),
+            AdminRole::Agreement => f.write_str("agreement"),
+            AdminRole::Job => f.write_str("job"),
+            AdminRole::Denylist => f.write_str("denylist"),
+        }
+    }
+}
+
+impl FromStr for AdminRole {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "manual_tokens" => Ok(AdminRole::ManualTokens),
+            "promo_code" => Ok(AdminRole::PromoCode),
+            "agreement" => Ok(AdminRole::Agreement),
+            "job" => Ok(AdminRole::Job),
+            "denylist" => Ok(AdminRole::Denylist),
+            _ => Err(()),
+        }
+    }
+}
+
+impl AdminRole {
+    /// Signer configured through the environment, only used until the role has signers in the database.
+    fn env_signer(&self) -> String {
+        match self {
+            AdminRole::ManualTokens => manualtokensigner(),
+            AdminRole::PromoCode => promocodesigner(),
+            AdminRole::Agreement => agreementsigner(),
+            AdminRole::Job => jobsigner(),
+            AdminRole::Denylist => denylistsigner(),
+        }
+    }
+}
+
+/// Signers of the role active right now.
+pub async fn active_admin_signers(
+    database: &Database,
+    role: AdminRole,
+) -> Result<Vec<String>, sqlx::Error> {
+    let role_name = role.to_string();
+    if !DatabaseAdminSigner::has_role(database, &role_name).await? {
+        return Ok(vec![role.env_signer()]);
+    }
+
+    DatabaseAdminSigner::get_all_active_by_role(database, &role_name, get_time_i64())
+        .await
+        .map(|signers| signers.into_iter().map(|signer| signer.signer).collect())
+}
+
+/// The active signer of the role that signed the message with freshness, if any. Consumes the signature.
+pub async fn validate_admin_signature<P: Provider>(
+    provider: &P,
+    database: &Database,
+    role: AdminRole,
+    message: &str,
+    signature: &str,
+    freshness: &Freshness,
+) -> Option<String> {
+    if !freshness.is_fresh() {
+        return None;
+    }
+
+    let signers = match active_admin_signers(database, role).await {
+        Ok(signers) => signers,
+        Err(e) => {
+            log::error!("Fetching {role} signers: {e}");
+            return None;
+        }
+    };
+
+    let fresh_message = freshness.message(message);
+    for signer in signers {
+        if validate_signature(provider, &signer, &fresh_message, signature).await {
+            return use_signature(database, &signer, message, freshness)
+                .await
+                .then_some(signer);
+        }
+    }
+
+    None
+}
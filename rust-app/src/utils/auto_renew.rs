@@ -0,0 +1,178 @@
+use crate::{
+    database::{
+        Database,
+        auto_renew::DatabaseAutoRenew,
+        credits::{DatabaseCredits, is_insufficient_credits},
+        notification::DatabaseNotification,
+        tokenized_server::DatabaseTokenizedServer,
+    },
+    utils::{
+        denylist::{DenylistScope, is_denied},
+        env::autorenewbefore,
+        scheduler::JobResult,
+        tier::get_tier,
+        time::get_time_i64,
+    },
+};
+
+pub async fn renew_expiring_servers(database: &Database) -> JobResult {
+    let auto_renews = DatabaseAutoRenew::get_all_due(database, autorenewbefore())
+        .await
+        .map_err(|e| format!("COULD NOT GET DUE AUTO RENEWALS: {e}"))?;
+    for mut auto_renew in auto_renews {
+        renew(database, &mut auto_renew).await;
+    }
+
+    Ok(())
+}
+
+/// Notifies the owner once per expiry that the renewal did not go through.
+async fn renewal_failed(
+    database: &Database,
+    auto_renew: &mut DatabaseAutoRenew,
+    server: &DatabaseTokenizedServer,
+    reason: String,
+) {
+    if auto_renew.failed_expires == Some(server.expires) {
+        // Owner already notified of this failed renewal
+        return;
+    }
+
+    let (collection, chain, token_id) = (&server.collection, &server.chain, &server.token_id);
+    log::warn!(
+        "Auto renewal of {collection}@{chain}@{token_id} by {payer} failed: {reason}",
+        payer = auto_renew.payer
+    );
+    let notification = DatabaseNotification {
+        account: server.owner.clone(),
+        message: format!("Auto renewal of {collection}@{chain}@{token_id} failed: {reason}"),
+        date: get_time_i64(),
+    };
+    if let Err(e) = notification.insert(database).await {
+        log::error!("COULD NOT INSERT NOTIFICATION {notification:?}: {e}");
+    }
+    if let Err(e) = auto_renew.failed(database, server.expires).await {
+        log::error!(
+            "COULD NOT MARK AUTO RENEWAL OF {collection}@{chain}@{token_id} AS FAILED: {e}"
+        );
+    }
+}
+
+async fn renew(database: &Database, auto_renew: &mut DatabaseAutoRenew) {
+    let collection = auto_renew.collection.clone();
+    let chain = auto_renew.chain.clone();
+    let token_id = auto_renew.token_id.clone();
+
+    let mut server = match DatabaseTokenizedServer::get_by_collection_token_id(
+        database,
+        &collection,
+        &chain,
+        &token_id,
+    )
+    .await
+    {
+        Ok(Some(server)) => server,
+        Ok(None) => {
+            log::error!(
+                "AUTO RENEWAL OF NON-EXISTENT TOKENIZED SERVER {collection}@{chain}@{token_id}"
+            );
+            return;
+        }
+        Err(e) => {
+            log::error!(
+                "FETCHING AUTO RENEWED TOKENIZED SERVER {collection}@{chain}@{token_id}: {e}"
+            );
+            return;
+        }
+    };
+
+    let tier = match get_tier(server.tier.as_deref()) {
+        Some(tier) => tier,
+        None => {
+            log::error!(
+                "UNKNOWN TIER {tier:?} OF AUTO RENEWED {collection}@{chain}@{token_id}",
+                tier = server.tier
+            );
+            return;
+        }
+    };
+    match is_denied(database, &auto_renew.payer, DenylistScope::Credits).await {
+        Ok(false) => (),
+        Ok(true) => {
+            renewal_failed(
+                database,
+                auto_renew,
+                &server,
+                format!(
+                    "{payer} is not allowed to spend credits",
+                    payer = auto_renew.payer
+                ),
+            )
+            .await;
+            return;
+        }
+        Err(e) => {
+            log::error!(
+                "Fetching credits denylist for {payer}: {e}",
+                payer = auto_renew.payer
+            );
+            return;
+        }
+    }
+
+    // Payment, extended expiry and renewal count are written together, a failed write never charges twice
+    let mut transaction = match database.begin().await {
+        Ok(transaction) => transaction,
+        Err(e) => {
+            log::error!("COULD NOT START AUTO RENEWAL OF {collection}@{chain}@{token_id}: {e}");
+            return;
+        }
+    };
+    if let Err(e) = (DatabaseCredits {
+        account: auto_renew.payer.clone(),
+        credits: -tier.price,
+        description: format!("Auto renew of {collection}@{chain}@{token_id} by 1 month"),
+        date: get_time_i64(),
+    })
+    .insert_transaction(&mut transaction)
+    .await
+    {
+        if !is_insufficient_credits(&e) {
+            log::error!(
+                "COULD NOT CHARGE AUTO RENEWAL OF {collection}@{chain}@{token_id} TO {payer}: {e}",
+                payer = auto_renew.payer
+            );
+            return;
+        }
+        renewal_failed(
+            database,
+            auto_renew,
+            &server,
+            format!("insufficient credits of {payer}", payer = auto_renew.payer),
+        )
+        .await;
+        return;
+    }
+
+    let one_month = 30 * 24 * 60 * 60; // 1 month in seconds
+    if let Err(e) = server
+        .update_expires_transaction(&mut transaction, server.expires + one_month)
+        .await
+    {
+        log::error!(
+            "COULD NOT EXTEND TOKENIZED SERVER EXPIRES {collection}@{chain}@{token_id} BY AUTO RENEWAL: {e}"
+        );
+        return;
+    }
+
+    if let Err(e) = auto_renew.renewed(&mut transaction).await {
+        log::error!(
+            "COULD NOT MARK AUTO RENEWAL OF {collection}@{chain}@{token_id} AS RENEWED: {e}"
+        );
+        return;
+    }
+
+    if let Err(e) = transaction.commit().await {
+        log::error!("COULD NOT COMMIT AUTO RENEWAL OF {collection}@{chain}@{token_id}: {e}");
+    }
+}
@@ -0,0 +1,73 @@
+use alloy::primitives::U256;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    database::{
+        Database,
+        claim::{DatabaseClaim, DatabaseClaimSources},
+        tokens_claimed::DatabaseTokensClaimed,
+    },
+    utils::decimals::to_18_decimals,
+};
+
+/// Claim status with all amounts in 18 decimals, matching the claimer contract.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ClaimSummary {
+    pub genesis: U256,
+    pub staking: U256,
+    pub manual: U256,
+    pub other: U256,
+    pub earned: U256,
+    /// Latest total claimed on-chain
+    pub claimed: U256,
+    /// Released by the latest on-chain claim, None for the global summary
+    pub released: Option<U256>,
+    pub claimable: U256,
+}
+
+fn amount(amount: i64) -> U256 {
+    to_18_decimals(U256::from(amount.max(0)))
+}
+
+impl ClaimSummary {
+    fn new(
+        sources: DatabaseClaimSources,
+        claimed: i64,
+        released: Option<i64>,
+        claimable: i64,
+    ) -> Self {
+        Self {
+            genesis: amount(sources.genesis),
+            staking: amount(sources.staking),
+            manual: amount(sources.manual),
+            other: amount(sources.other),
+            earned: amount(sources.total),
+            claimed: amount(claimed),
+            released: released.map(amount),
+            claimable: amount(claimable),
+        }
+    }
+}
+
+pub async fn get_account_claim_summary(
+    database: &Database,
+    account: &str,
+) -> Result<ClaimSummary, sqlx::Error> {
+    let sources = DatabaseClaim::get_sources_by_account(database, account).await?;
+    let latest = DatabaseTokensClaimed::get_latest_by_account(database, account).await?;
+    let claimed = latest.as_ref().map(|latest| latest.total).unwrap_or(0);
+    let claimable = sources.total - claimed;
+    Ok(ClaimSummary::new(
+        sources,
+        claimed,
+        Some(latest.map(|latest| latest.released).unwrap_or(0)),
+        claimable,
+    ))
+}
+
+pub async fn get_claim_summary(database: &Database) -> Result<ClaimSummary, sqlx::Error> {
+    let sources = DatabaseClaim::get_sources(database).await?;
+    let claimed = DatabaseTokensClaimed::get_total_claimed(database).await?;
+    let claimable = DatabaseClaim::get_total_outstanding(database).await?;
+    Ok(ClaimSummary::new(sources, claimed, None, claimable))
+}
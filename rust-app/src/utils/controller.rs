@@ -1,17 +1,119 @@
-use alloy::signers::Signer;
+use std::{fmt::Display, str::FromStr};
+
+use serde::{Deserialize, Serialize};
 use xnode_controller::XnodeController;
 use xnode_manager_sdk::utils::Session;
 
 use crate::{
-    database::{Database, tokenized_server::DatabaseTokenizedServer},
+    database::{
+        Database, server_controller::DatabaseServerController,
+        tokenized_server::DatabaseTokenizedServer,
+    },
     utils::{time::get_time_u64, wallet::get_tokenized_server_owner, xnode::address_to_xnode_user},
 };
 
-#[allow(dead_code)]
+#[allow(dead_code, clippy::enum_variant_names)]
 #[derive(Debug)]
 pub enum Error {
     XnodeManagerSDKError(xnode_manager_sdk::utils::Error),
     AlloySignerError(alloy::signers::Error),
+    NoUserConfig,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ControllerRole {
+    /// Only access to the app
+    App,
+    /// App, processes (including logs) and usage
+    Operator,
+    /// App and the full manager
+    Admin,
+}
+
+impl Display for ControllerRole {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ControllerRole::App => f.write_str("app"),
+            ControllerRole::Operator => f.write_str("operator"),
+            ControllerRole::Admin => f.write_str("admin"),
+        }
+    }
+}
+
+impl FromStr for ControllerRole {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "app" => Ok(ControllerRole::App),
+            "operator" => Ok(ControllerRole::Operator),
+            "admin" => Ok(ControllerRole::Admin),
+            _ => Err(()),
+        }
+    }
+}
+
+impl ControllerRole {
+    /// Manager paths the role has access to.
+    fn manager_paths(&self) -> Option<&'static str> {
+        match self {
+            ControllerRole::App => None,
+            ControllerRole::Operator => {
+                Some("^(?:\\/info.*|\\/process\\/container:.*|\\/usage.*|\\/request.*)")
+            }
+            ControllerRole::Admin => Some(
+                "^(?:\\/config.*|\\/file\\/container:.*|\\/info.*|\\/process\\/container:.*|\\/usage.*|\\/request.*)",
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ServerController {
+    pub controller: String,
+    pub role: ControllerRole,
+}
+
+/// Controllers end up in the NixOS config, so only allow characters of Xnode users.
+pub fn is_valid_controller(controller: &str) -> bool {
+    !controller.is_empty()
+        && controller
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || [':', '.', '_', '-', '@'].contains(&c))
+}
+
+/// Main controller (admin) followed by the additional controllers of the server.
+pub async fn server_controllers(
+    database: &Database,
+    server: &DatabaseTokenizedServer,
+) -> Result<Vec<ServerController>, sqlx::Error> {
+    let mut controllers = vec![ServerController {
+        controller: server.controller.clone(),
+        role: ControllerRole::Admin,
+    }];
+    for controller in DatabaseServerController::get_all_by_collection_token_id(
+        database,
+        &server.collection,
+        &server.chain,
+        &server.token_id,
+    )
+    .await?
+    {
+        if controller.controller == server.controller {
+            continue;
+        }
+        match ControllerRole::from_str(&controller.role) {
+            Ok(role) => controllers.push(ServerController {
+                controller: controller.controller,
+                role,
+            }),
+            Err(()) => {
+                log::warn!("Skipping controller {controller:?} with unknown role");
+            }
+        }
+    }
+    Ok(controllers)
 }
 
 pub struct ControlledXnode {
@@ -44,6 +146,132 @@ impl ControlledXnode {
     }
 }
 
+impl ControlledXnode {
+    /// Replaces the config block with the given name in the OS config.
+    /// When missing, the legacy config it supersedes is replaced instead or the block is inserted.
+    pub async fn set_config_block(
+        &self,
+        block: &str,
+        config: String,
+        legacy: Option<&str>,
+    ) -> Result<xnode_manager_sdk::os::SetOutput, Error> {
+        let current_os =
+            xnode_manager_sdk::os::get(xnode_manager_sdk::os::GetInput::new(&self.session))
+                .await
+                .map_err(Error::XnodeManagerSDKError)?;
+
+        let base_url = &self.session.base_url;
+        let block_start = format!("# START {block} {base_url}");
+        let block_end = format!("# END {block} {base_url}");
+        let new_os_config = match current_os.flake.find(&block_start).and_then(|start_index| {
+            let start_end = start_index + block_start.len();
+            current_os.flake[start_end..]
+                .find(&block_end)
+                .map(|end_offset| (start_end, start_end + end_offset))
+        }) {
+            Some((start_end, end_index)) => [
+                &current_os.flake[..start_end],
+                &config,
+                &current_os.flake[end_index..],
+            ]
+            .join("\n"),
+            None if legacy.is_some_and(|legacy| current_os.flake.contains(legacy)) => {
+                current_os.flake.replacen(
+                    legacy.unwrap_or_default(),
+                    &format!("{block_start}\n{config}\n{block_end}"),
+                    1,
+                )
+            }
+            None => {
+                let user_config_start = "# START USER CONFIG";
+                let start_end = current_os
+                    .flake
+                    .find(user_config_start)
+                    .ok_or(Error::NoUserConfig)?
+                    + user_config_start.len();
+                format!(
+                    "{before}\n{block_start}\n{config}\n{block_end}\n{after}",
+                    before = &current_os.flake[..start_end],
+                    after = &current_os.flake[start_end..]
+                )
+            }
+        };
+
+        xnode_manager_sdk::os::set(xnode_manager_sdk::os::SetInput::new_with_data(
+            &self.session,
+            xnode_manager_sdk::os::OSChange {
+                acme_email: None,
+                domain: None,
+                flake: Some(new_os_config),
+                update_inputs: None,
+                user_passwd: None,
+                xnode_owner: None,
+            },
+        ))
+        .await
+        .map_err(Error::XnodeManagerSDKError)
+    }
+
+    /// Host cpu, memory and disk usage.
+    pub async fn usage(
+        &self,
+    ) -> Result<
+        (
+            xnode_manager_sdk::usage::CpuOutput,
+            xnode_manager_sdk::usage::MemoryOutput,
+            xnode_manager_sdk::usage::DiskOutput,
+        ),
+        Error,
+    > {
+        let scope = "host".to_string();
+        let cpu = xnode_manager_sdk::usage::cpu(xnode_manager_sdk::usage::CpuInput::new_with_path(
+            &self.session,
+            xnode_manager_sdk::usage::CpuPath {
+                scope: scope.clone(),
+            },
+        ))
+        .await
+        .map_err(Error::XnodeManagerSDKError)?;
+        let memory =
+            xnode_manager_sdk::usage::memory(xnode_manager_sdk::usage::MemoryInput::new_with_path(
+                &self.session,
+                xnode_manager_sdk::usage::MemoryPath {
+                    scope: scope.clone(),
+                },
+            ))
+            .await
+            .map_err(Error::XnodeManagerSDKError)?;
+        let disk =
+            xnode_manager_sdk::usage::disk(xnode_manager_sdk::usage::DiskInput::new_with_path(
+                &self.session,
+                xnode_manager_sdk::usage::DiskPath { scope },
+            ))
+            .await
+            .map_err(Error::XnodeManagerSDKError)?;
+
+        Ok((cpu, memory, disk))
+    }
+
+    /// Replaces the controller block with access for all given controllers.
+    pub async fn set_controllers(
+        &self,
+        controllers: &[ServerController],
+    ) -> Result<xnode_manager_sdk::os::SetOutput, Error> {
+        self.set_config_block(
+            "XNODE CONTROLLER",
+            get_controller_config(self.session.base_url.clone(), controllers),
+            None,
+        )
+        .await
+    }
+
+    pub async fn reboot(&self) -> Result<xnode_manager_sdk::os::RebootOutput, Error> {
+        xnode_manager_sdk::os::reboot(xnode_manager_sdk::os::RebootInput::new(&self.session))
+            .await
+            .map_err(Error::XnodeManagerSDKError)
+    }
+}
+
 impl XnodeController for ControlledXnode {
     fn get_session(&self) -> &Session {
         &self.session
@@ -63,19 +291,33 @@ impl XnodeController for ControlledXnode {
     }
 
     fn controller_config(&self, controller: String) -> String {
-        get_controller_config(self.session.base_url.clone(), controller)
+        get_controller_config(
+            self.session.base_url.clone(),
+            &[ServerController {
+                controller,
+                role: ControllerRole::Admin,
+            }],
+        )
     }
 }
 
-pub fn get_controller_config(base_url: String, controller: String) -> String {
+pub fn get_controller_config(base_url: String, controllers: &[ServerController]) -> String {
     let manager = base_url.replace("https://", "");
     let app = base_url.replace("https://manager.", "");
-    format!(
-            "\
-services.xnode-auth.domains.\"{manager}\".accessList.\"{controller}\" = {{ paths = \"^(?:\\/config.*|\\/file\\/container:.*|\\/info.*|\\/process\\/container:.*|\\/usage.*|\\/request.*)\"; }};
-services.xnode-auth.domains.\"{app}\".accessList.\"{controller}\" = {{ }};\
-"
-        )
+    controllers
+        .iter()
+        .map(|ServerController { controller, role }| {
+            let app_access =
+                format!("services.xnode-auth.domains.\"{app}\".accessList.\"{controller}\" = {{ }};");
+            match role.manager_paths() {
+                Some(paths) => format!(
+                    "services.xnode-auth.domains.\"{manager}\".accessList.\"{controller}\" = {{ paths = \"{paths}\"; }};\n{app_access}"
+                ),
+                None => app_access,
+            }
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
 }
 
 async fn get_session(xnode_id: &str) -> Result<Session, Error> {
@@ -0,0 +1,355 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    database::{
+        Database, custom_domain::DatabaseCustomDomain, tokenized_server::DatabaseTokenizedServer,
+    },
+    utils::{
+        controller::{self, ControlledXnode, ServerController, server_controllers},
+        env::dnsresolver,
+        http::HttpError,
+        preset::server_preset,
+        subdomain::server_domain,
+    },
+};
+
+/// Looks up TXT records, implemented separately so verification can run against any resolver.
+pub trait TxtResolver {
+    fn txt_records(&self, name: &str) -> impl Future<Output = Result<Vec<String>, HttpError>>;
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct DohAnswer {
+    data: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct DohResponse {
+    #[serde(rename = "Answer", default)]
+    answer: Vec<DohAnswer>,
+}
+
+/// DNS over HTTPS resolver using the JSON API (Cloudflare, Google).
+#[derive(Clone)]
+pub struct DohResolver {
+    client: reqwest::Client,
+    url: String,
+}
+
+impl DohResolver {
+    pub fn new() -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            url: dnsresolver(),
+        }
+    }
+}
+
+impl TxtResolver for DohResolver {
+    async fn txt_records(&self, name: &str) -> Result<Vec<String>, HttpError> {
+        self.client
+            .get(&self.url)
+            .query(&[("name", name), ("type", "TXT")])
+            .header("accept", "application/dns-json")
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| HttpError::Request(e.to_string()))?
+            .json::<DohResponse>()
+            .await
+            .map(|response| {
+                response
+                    .answer
+                    .into_iter()
+                    .map(|answer| answer.data.trim_matches('"').to_string())
+                    .collect()
+            })
+            .map_err(|e| HttpError::Response(e.to_string()))
+    }
+}
+
+pub fn is_valid_domain(domain: &str) -> bool {
+    domain.len() <= 253
+        && domain.contains(".")
+        && !domain.ends_with("openxai.network")
+        && domain.split(".").all(|label| {
+            !label.is_empty()
+                && label.len() <= 63
+                && !label.starts_with("-")
+                && !label.ends_with("-")
+                && label
+                    .chars()
+                    .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+        })
+}
+
+/// DNS name the challenge TXT record should be set on.
+pub fn challenge_name(domain: &str) -> String {
+    format!("_openxai-challenge.{domain}")
+}
+
+pub async fn verify_challenge<Resolver: TxtResolver>(
+    resolver: &Resolver,
+    custom_domain: &DatabaseCustomDomain,
+) -> Result<bool, HttpError> {
+    resolver
+        .txt_records(&challenge_name(&custom_domain.domain))
+        .await
+        .map(|records| records.contains(&custom_domain.challenge))
+}
+
+/// Name of the OS config block holding the custom domains.
+pub const CUSTOM_DOMAINS_BLOCK: &str = "XNODE CUSTOM DOMAINS";
+
+pub fn custom_domains_config(
+    domains: &[String],
+    controllers: &[ServerController],
+    forward: &str,
+) -> String {
+    domains
+        .iter()
+        .map(|domain| {
+            // Every role has access to the app
+            let access = controllers
+                .iter()
+                .map(|ServerController { controller, .. }| {
+                    format!(
+                        "services.xnode-auth.domains.\"{domain}\".accessList.\"{controller}\" = {{ }};"
+                    )
+                })
+                .collect::<Vec<String>>()
+                .join("\n");
+            format!(
+                "\
+services.xnode-reverse-proxy.rules.\"{domain}\" = [ {{ forward = \"{forward}\"; }} ];
+services.nginx.virtualHosts.\"{domain}\" = {{ enableACME = true; forceSSL = true; }};
+{access}\
+"
+            )
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+/// Config of the verified custom domains of the server, None when it could not be built.
+pub async fn server_custom_domains_config(
+    database: &Database,
+    server: &DatabaseTokenizedServer,
+) -> Option<String> {
+    let domains = match DatabaseCustomDomain::get_all_by_collection_token_id(
+        database,
+        &server.collection,
+        &server.chain,
+        &server.token_id,
+    )
+    .await
+    {
+        Ok(domains) => domains,
+        Err(e) => {
+            log::error!(
+                "Fetching custom domains of {collection}@{chain}@{token_id}: {e}",
+                collection = server.collection,
+                chain = server.chain,
+                token_id = server.token_id
+            );
+            return None;
+        }
+    };
+    let controllers = match server_controllers(database, server).await {
+        Ok(controllers) => controllers,
+        Err(e) => {
+            log::error!(
+                "Fetching controllers of {collection}@{chain}@{token_id}: {e}",
+                collection = server.collection,
+                chain = server.chain,
+                token_id = server.token_id
+            );
+            return None;
+        }
+    };
+    // Custom domains serve the same app as the server domain
+    let forward = match server_preset(server).and_then(|(preset, parameters)| {
+        preset
+            .forward(&server_domain(server), &parameters)
+            .inspect_err(|e| {
+                log::error!("Invalid preset parameters {parameters:?}: {e:?}");
+            })
+            .ok()
+    }) {
+        Some(forward) => forward,
+        None => {
+            log::error!(
+                "APP PRESET {preset:?} OF {collection}@{chain}@{token_id} COULD NOT BE RESOLVED",
+                preset = server.preset,
+                collection = server.collection,
+                chain = server.chain,
+                token_id = server.token_id
+            );
+            return None;
+        }
+    };
+    let domains: Vec<String> = domains
+        .into_iter()
+        .filter(|domain| domain.verified)
+        .map(|domain| domain.domain)
+        .collect();
+
+    Some(custom_domains_config(&domains, &controllers, &forward))
+}
+
+/// Pushes the verified custom domains of the server to its Xnode.
+pub async fn apply_custom_domains(
+    database: &Database,
+    xnode: &ControlledXnode,
+    server: &DatabaseTokenizedServer,
+) -> Result<(), controller::Error> {
+    let Some(config) = server_custom_domains_config(database, server).await else {
+        // Pushing an empty list would remove all domains
+        return Ok(());
+    };
+
+    xnode
+        .set_config_block(CUSTOM_DOMAINS_BLOCK, config, None)
+        .await
+        .map(|_| ())
+}
+
+pub async fn update_custom_domains(database: &Database, server: &DatabaseTokenizedServer) {
+    let xnode = match ControlledXnode::new(
+        database.clone(),
+        server.collection.clone(),
+        server.chain.clone(),
+        server.token_id.clone(),
+    )
+    .await
+    {
+        Ok(xnode) => xnode,
+        Err(e) => {
+            log::error!(
+                "CREATE XNODE SESSION FOR {collection}@{chain}@{token_id} FAILED: {e:?}",
+                collection = server.collection,
+                chain = server.chain,
+                token_id = server.token_id
+            );
+            return;
+        }
+    };
+
+    if let Err(e) = apply_custom_domains(database, &xnode, server).await {
+        log::error!(
+            "XNODE MANAGER UPDATE OF CUSTOM DOMAINS FOR {collection}@{chain}@{token_id} FAILED: {e:?}",
+            collection = server.collection,
+            chain = server.chain,
+            token_id = server.token_id
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    struct StubResolver {
+        records: HashMap<String, Vec<String>>,
+    }
+
+    impl TxtResolver for StubResolver {
+        async fn txt_records(&self, name: &str) -> Result<Vec<String>, HttpError> {
+            if name.contains("unreachable") {
+                return Err(HttpError::Request("unreachable".to_string()));
+            }
+            Ok(self.records.get(name).cloned().unwrap_or_default())
+        }
+    }
+
+    fn custom_domain(domain: &str) -> DatabaseCustomDomain {
+        DatabaseCustomDomain {
+            domain: domain.to_string(),
+            collection: "ownaiv1".to_string(),
+            chain: "base".to_string(),
+            token_id: "1".to_string(),
+            challenge: "challenge".to_string(),
+            verified: false,
+            created_at: 0,
+        }
+    }
+
+    #[test]
+    fn valid_domains() {
+        assert!(is_valid_domain("example.com"));
+        assert!(is_valid_domain("app.my-site.example.com"));
+        assert!(is_valid_domain("123.example"));
+        assert!(is_valid_domain(&format!("{}.com", "a".repeat(63))));
+    }
+
+    #[test]
+    fn invalid_domains() {
+        assert!(!is_valid_domain(""));
+        assert!(!is_valid_domain("localhost"));
+        assert!(!is_valid_domain("Example.com"));
+        assert!(!is_valid_domain("example..com"));
+        assert!(!is_valid_domain(".example.com"));
+        assert!(!is_valid_domain("example.com."));
+        assert!(!is_valid_domain("-example.com"));
+        assert!(!is_valid_domain("example-.com"));
+        assert!(!is_valid_domain("exa_mple.com"));
+        assert!(!is_valid_domain("example.com\"; services.x = 1; #"));
+        assert!(!is_valid_domain(&format!("{}.com", "a".repeat(64))));
+        assert!(!is_valid_domain(&format!("{}com", "a.".repeat(126))));
+        assert!(!is_valid_domain("openxai.network"));
+        assert!(!is_valid_domain("manager.1.base.ownaiv1.openxai.network"));
+    }
+
+    #[tokio::test]
+    async fn challenge_verified_by_matching_record() {
+        let resolver = StubResolver {
+            records: HashMap::from([(
+                "_openxai-challenge.example.com".to_string(),
+                vec!["other".to_string(), "challenge".to_string()],
+            )]),
+        };
+        assert!(
+            verify_challenge(&resolver, &custom_domain("example.com"))
+                .await
+                .unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn challenge_not_verified_without_matching_record() {
+        let resolver = StubResolver {
+            records: HashMap::from([
+                (
+                    "_openxai-challenge.example.com".to_string(),
+                    vec!["wrong".to_string()],
+                ),
+                // Record on the domain itself instead of the challenge name
+                ("other.com".to_string(), vec!["challenge".to_string()]),
+            ]),
+        };
+        assert!(
+            !verify_challenge(&resolver, &custom_domain("example.com"))
+                .await
+                .unwrap()
+        );
+        assert!(
+            !verify_challenge(&resolver, &custom_domain("other.com"))
+                .await
+                .unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn challenge_resolver_error() {
+        let resolver = StubResolver {
+            records: HashMap::new(),
+        };
+        assert!(
+            verify_challenge(&resolver, &custom_domain("unreachable.com"))
+                .await
+                .is_err()
+        );
+    }
+}
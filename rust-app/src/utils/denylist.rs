@@ -0,0 +1,73 @@
+use std::{fmt::Display, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    database::{Database, denial::DatabaseDenial, denylist::DatabaseDenylist},
+    utils::time::get_time_i64,
+};
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum DenylistScope {
+    /// Signing of token claims
+    Claim,
+    /// Spending credits on mints, reservations and renewals
+    Credits,
+    /// Redeeming promo codes
+    Promo,
+}
+
+impl Display for DenylistScope {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DenylistScope::Claim => f.write_str("claim"),
+            DenylistScope::Credits => f.write_str("credits"),
+            DenylistScope::Promo => f.write_str("promo"),
+        }
+    }
+}
+
+impl FromStr for DenylistScope {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "claim" => Ok(DenylistScope::Claim),
+            "credits" => Ok(DenylistScope::Credits),
+            "promo" => Ok(DenylistScope::Promo),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Whether the account is currently denied the scope, every denial is written to the audit log.
+pub async fn is_denied(
+    database: &Database,
+    account: &str,
+    scope: DenylistScope,
+) -> Result<bool, sqlx::Error> {
+    let now = get_time_i64();
+    let Some(entry) =
+        DatabaseDenylist::get_active_by_account_scope(database, account, &scope.to_string(), now)
+            .await?
+    else {
+        return Ok(false);
+    };
+
+    log::warn!(
+        "Denied {scope} for {account}: {reason}",
+        reason = entry.reason
+    );
+    let denial = DatabaseDenial {
+        account: account.to_string(),
+        scope: scope.to_string(),
+        reason: entry.reason,
+        date: now,
+    };
+    if let Err(e) = denial.insert(database).await {
+        log::error!("COULD NOT INSERT DENIAL {denial:?}: {e}");
+    }
+
+    Ok(true)
+}
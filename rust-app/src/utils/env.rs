@@ -1,3 +1,5 @@
+use std::collections::BTreeMap;
+
 use alloy::primitives::Address;
 
 fn env_var(id: &str) -> Option<String> {
@@ -16,16 +18,36 @@ pub fn port() -> String {
     env_var("PORT").unwrap_or(String::from("36092"))
 }
 
-pub fn claimerkey() -> String {
-    env_var("CLAIMERKEY").expect("No CLAIMERKEY provided.")
+/// JSON signer config, takes precedence over CLAIMERKEY.
+pub fn claimersigner() -> Option<String> {
+    env_var("CLAIMERSIGNER")
+}
+
+pub fn claimerkey() -> Option<String> {
+    env_var("CLAIMERKEY")
+}
+
+/// JSON signer config, takes precedence over TOKENOWNERKEY.
+pub fn tokenownersigner() -> Option<String> {
+    env_var("TOKENOWNERSIGNER")
+}
+
+pub fn tokenownerkey() -> Option<String> {
+    env_var("TOKENOWNERKEY")
+}
+
+/// JSON signer config, takes precedence over TOKENMINTERKEY.
+pub fn tokenmintersigner() -> Option<String> {
+    env_var("TOKENMINTERSIGNER")
 }
 
-pub fn tokenownerkey() -> String {
-    env_var("TOKENOWNERKEY").expect("No TOKENOWNERKEY provided.")
+pub fn tokenminterkey() -> Option<String> {
+    env_var("TOKENMINTERKEY")
 }
 
-pub fn tokenminterkey() -> String {
-    env_var("TOKENMINTERKEY").expect("No TOKENMINTERKEY provided.")
+/// Manages the admin signers of every role.
+pub fn rootsigner() -> String {
+    env_var("ROOTSIGNER").expect("No ROOTSIGNER provided.")
 }
 
 pub fn manualtokensigner() -> String {
@@ -36,6 +58,14 @@ pub fn promocodesigner() -> String {
     env_var("PROMOCODESIGNER").unwrap_or("0x3e166454c7781d3fD4ceaB18055cad87136970Ea".to_string())
 }
 
+pub fn jobsigner() -> String {
+    env_var("JOBSIGNER").unwrap_or("0x3e166454c7781d3fD4ceaB18055cad87136970Ea".to_string())
+}
+
+pub fn denylistsigner() -> String {
+    env_var("DENYLISTSIGNER").unwrap_or("0x3e166454c7781d3fD4ceaB18055cad87136970Ea".to_string())
+}
+
 pub fn agreementsigner() -> String {
     env_var("AGREEMENTSIGNER").unwrap_or("0x3e166454c7781d3fD4ceaB18055cad87136970Ea".to_string())
 }
@@ -49,6 +79,10 @@ pub fn subdomaindistributor() -> String {
         .unwrap_or("http://subdomain-distributor.local:42923".to_string())
 }
 
+pub fn dnsresolver() -> String {
+    env_var("DNSRESOLVER").unwrap_or("https://cloudflare-dns.com/dns-query".to_string())
+}
+
 pub fn httprpc() -> String {
     env_var("HTTPRPC").unwrap_or("https://base-rpc.publicnode.com".to_string())
 }
@@ -121,6 +155,198 @@ pub fn ownaiv1price() -> i64 {
         .unwrap_or(100_000_000)
 }
 
+/// JSON tiers, none for the single default tier.
+pub fn ownaiv1tiers() -> Option<String> {
+    env_var("OWNAIV1TIERS")
+}
+
+/// Approvals required per admin role, roles not listed need 1.
+pub fn adminthresholds() -> BTreeMap<String, i32> {
+    serde_json::from_str(&env_var("ADMINTHRESHOLDS").unwrap_or("{}".to_string()))
+        .unwrap_or_else(|e| panic!("Invalid ADMINTHRESHOLDS provided: {e}"))
+}
+
+pub fn adminproposalexpiry() -> i64 {
+    env_var("ADMINPROPOSALEXPIRY")
+        .and_then(|s| {
+            str::parse::<i64>(&s)
+                .inspect_err(|e| {
+                    log::error!("Could not parse ADMINPROPOSALEXPIRY to i64: {e}");
+                })
+                .ok()
+        })
+        .unwrap_or(604800)
+}
+
+/// JSON staking rate versions.
+pub fn stakingrates() -> String {
+    env_var("STAKINGRATES").unwrap_or("[]".to_string())
+}
+
+/// JSON presets, none for the default presets.
+pub fn ownaiv1presets() -> Option<String> {
+    env_var("OWNAIV1PRESETS")
+}
+
+pub fn autorenewbefore() -> i64 {
+    env_var("AUTORENEWBEFORE")
+        .and_then(|s| {
+            str::parse::<i64>(&s)
+                .inspect_err(|e| {
+                    log::error!("Could not parse AUTORENEWBEFORE to i64: {e}");
+                })
+                .ok()
+        })
+        .unwrap_or(24 * 60 * 60)
+}
+
+pub fn stockcachettl() -> u64 {
+    env_var("STOCKCACHETTL")
+        .and_then(|s| {
+            str::parse::<u64>(&s)
+                .inspect_err(|e| {
+                    log::error!("Could not parse STOCKCACHETTL to u64: {e}");
+                })
+                .ok()
+        })
+        .unwrap_or(30)
+}
+
+pub fn reconcileorphancleanup() -> bool {
+    env_var("RECONCILEORPHANCLEANUP")
+        .and_then(|s| {
+            str::parse::<bool>(&s)
+                .inspect_err(|e| {
+                    log::error!("Could not parse RECONCILEORPHANCLEANUP to bool: {e}");
+                })
+                .ok()
+        })
+        .unwrap_or(false)
+}
+
+pub fn reconcileorphanage() -> i64 {
+    env_var("RECONCILEORPHANAGE")
+        .and_then(|s| {
+            str::parse::<i64>(&s)
+                .inspect_err(|e| {
+                    log::error!("Could not parse RECONCILEORPHANAGE to i64: {e}");
+                })
+                .ok()
+        })
+        .unwrap_or(60 * 60)
+}
+
+pub fn poweractioncooldown() -> i64 {
+    env_var("POWERACTIONCOOLDOWN")
+        .and_then(|s| {
+            str::parse::<i64>(&s)
+                .inspect_err(|e| {
+                    log::error!("Could not parse POWERACTIONCOOLDOWN to i64: {e}");
+                })
+                .ok()
+        })
+        .unwrap_or(5 * 60)
+}
+
+pub fn healthalertfailures() -> i64 {
+    env_var("HEALTHALERTFAILURES")
+        .and_then(|s| {
+            str::parse::<i64>(&s)
+                .inspect_err(|e| {
+                    log::error!("Could not parse HEALTHALERTFAILURES to i64: {e}");
+                })
+                .ok()
+        })
+        .unwrap_or(3)
+}
+
+pub fn autoheal() -> bool {
+    env_var("AUTOHEAL")
+        .and_then(|s| {
+            str::parse::<bool>(&s)
+                .inspect_err(|e| {
+                    log::error!("Could not parse AUTOHEAL to bool: {e}");
+                })
+                .ok()
+        })
+        .unwrap_or(false)
+}
+
+pub fn autohealafter() -> i64 {
+    env_var("AUTOHEALAFTER")
+        .and_then(|s| {
+            str::parse::<i64>(&s)
+                .inspect_err(|e| {
+                    log::error!("Could not parse AUTOHEALAFTER to i64: {e}");
+                })
+                .ok()
+        })
+        .unwrap_or(1800)
+}
+
+/// Seconds after signing that a signed request is still accepted.
+pub fn signaturewindow() -> i64 {
+    env_var("SIGNATUREWINDOW")
+        .and_then(|s| {
+            str::parse::<i64>(&s)
+                .inspect_err(|e| {
+                    log::error!("Could not parse SIGNATUREWINDOW to i64: {e}");
+                })
+                .ok()
+        })
+        .unwrap_or(600)
+}
+
+/// EIP-712 domain name of user signatures.
+pub fn signaturedomain() -> String {
+    env_var("SIGNATUREDOMAIN").unwrap_or(String::from("OpenxAI"))
+}
+
+/// Unix timestamp until which plain text signatures of user actions are still accepted, none for no end yet.
+pub fn legacysignaturesuntil() -> Option<i64> {
+    // Invalid values must not keep unbound signatures accepted forever
+    env_var("LEGACYSIGNATURESUNTIL").map(|s| {
+        str::parse::<i64>(&s)
+            .unwrap_or_else(|e| panic!("Invalid LEGACYSIGNATURESUNTIL provided: {e}"))
+    })
+}
+
+pub fn claimmerkle() -> bool {
+    env_var("CLAIMMERKLE")
+        .and_then(|s| {
+            str::parse::<bool>(&s)
+                .inspect_err(|e| {
+                    log::error!("Could not parse CLAIMMERKLE to bool: {e}");
+                })
+                .ok()
+        })
+        .unwrap_or(false)
+}
+
+pub fn claimdeadline() -> i64 {
+    env_var("CLAIMDEADLINE")
+        .and_then(|s| {
+            str::parse::<i64>(&s)
+                .inspect_err(|e| {
+                    log::error!("Could not parse CLAIMDEADLINE to i64: {e}");
+                })
+                .ok()
+        })
+        .unwrap_or(3600)
+}
+
 pub fn hyperstackapikey() -> String {
     env_var("HYPERSTACKAPIKEY").expect("No HYPERSTACKAPIKEY provided.")
 }
+
+/// JSON deployment providers.
+pub fn deploymentproviders() -> String {
+    env_var("DEPLOYMENTPROVIDERS").unwrap_or(
+        r#"[{"provider":"Hyperstack","regions":[{"name":"NORWAY-1","environment":"default-NORWAY-1"}],"flavors":[{"name":"n3-RTX-A4000x1","model":"RTX-A4000","cost":0}]}]"#
+            .to_string(),
+    )
+}
+
+pub fn deploymentpolicy() -> String {
+    env_var("DEPLOYMENTPOLICY").unwrap_or("failover".to_string())
+}
@@ -0,0 +1,307 @@
+use futures_util::future::join_all;
+use sqlx::types::Json;
+
+use crate::{
+    database::{
+        Database,
+        heal_event::DatabaseHealEvent,
+        health_sample::DatabaseHealthSample,
+        notification::DatabaseNotification,
+        power_action::DatabasePowerAction,
+        tokenized_server::{DatabaseTokenizedServer, TokenizedServerDeployment},
+    },
+    utils::{
+        env::{autoheal, autohealafter},
+        inventory::hyperstack_virtual_machine_exists,
+        power::PowerAction,
+        provider::undeploy_deployment,
+        scheduler::JobResult,
+        stock::StockClient,
+        time::get_time_i64,
+        xnode::deploy_v1,
+    },
+};
+
+/// Why a server should be healed and since when it is down.
+async fn heal_reason(
+    database: &Database,
+    server: &DatabaseTokenizedServer,
+    deployment: &TokenizedServerDeployment,
+) -> Result<Option<(String, i64)>, sqlx::Error> {
+    let (collection, chain, token_id) = (&server.collection, &server.chain, &server.token_id);
+
+    // Servers stopped by their owner are supposed to be down
+    let last_action = DatabasePowerAction::get_last_success_by_collection_token_id(
+        database, collection, chain, token_id,
+    )
+    .await?;
+    if last_action
+        .as_ref()
+        .is_some_and(|action| action.action == PowerAction::Stop.to_string())
+    {
+        return Ok(None);
+    }
+
+    // Downtime counts from the last moment the server was known (or expected) to be fine
+    let since = [
+        DatabaseHealthSample::get_last_healthy_date_by_collection_token_id(
+            database, collection, chain, token_id,
+        )
+        .await?,
+        DatabaseHealEvent::get_last_date_by_collection_token_id(
+            database, collection, chain, token_id,
+        )
+        .await?,
+        last_action.map(|action| action.date),
+    ]
+    .into_iter()
+    .flatten()
+    .max()
+    .unwrap_or(0);
+    let down_since = match DatabaseHealthSample::get_first_date_since_by_collection_token_id(
+        database, collection, chain, token_id, since,
+    )
+    .await?
+    {
+        Some(down_since) => down_since,
+        None => {
+            // No samples yet since the server was last fine
+            return Ok(None);
+        }
+    };
+
+    let latest = DatabaseHealthSample::get_latest_by_collection_token_id(
+        database, collection, chain, token_id, 1,
+    )
+    .await?;
+    if latest.first().is_none_or(|sample| sample.healthy) {
+        return Ok(None);
+    }
+
+    if let TokenizedServerDeployment::Hyperstack { id } = deployment {
+        match hyperstack_virtual_machine_exists(*id).await {
+            Ok(false) => {
+                return Ok(Some(("terminated".to_string(), down_since)));
+            }
+            Ok(true) => (),
+            Err(e) => {
+                log::warn!("Checking existence of hyperstack virtual machine {id} failed: {e}");
+            }
+        }
+    }
+
+    if get_time_i64() - down_since >= autohealafter() {
+        return Ok(Some(("unreachable".to_string(), down_since)));
+    }
+
+    Ok(None)
+}
+
+/// Replaces the deployment of the server, extending its expiry by the downtime.
+async fn heal_server(
+    database: &Database,
+    stock: &StockClient,
+    mut server: DatabaseTokenizedServer,
+    deployment: TokenizedServerDeployment,
+    reason: String,
+    down_since: i64,
+) {
+    log::warn!(
+        "Healing {collection}@{chain}@{token_id} ({reason}, down since {down_since})",
+        collection = server.collection,
+        chain = server.chain,
+        token_id = server.token_id
+    );
+
+    if let Some(e) = undeploy_deployment(&deployment).await {
+        // Terminated VMs cannot be undeployed, anything else would leak the VM
+        let gone = match &deployment {
+            TokenizedServerDeployment::Hyperstack { id } => {
+                matches!(hyperstack_virtual_machine_exists(*id).await, Ok(false))
+            }
+            TokenizedServerDeployment::Mock { .. } => false,
+        };
+        if !gone {
+            log::error!(
+                "UNDEPLOYMENT OF DEAD {collection}@{chain}@{token_id} FAILED: {e:?}",
+                collection = server.collection,
+                chain = server.chain,
+                token_id = server.token_id
+            );
+            return;
+        }
+    }
+    if let Err(e) = server.undeploy(database).await {
+        log::error!(
+            "DATABASE UPDATE OF UNDEPLOYMENT {deployment:?} FOR {collection}@{chain}@{token_id} FAILED: {e}",
+            collection = server.collection,
+            chain = server.chain,
+            token_id = server.token_id
+        );
+        return;
+    }
+
+    // Controllers, custom domains and subdomain are restored as part of the deployment, a failed deployment is retried by retry_heal
+    deploy_v1(database, stock, &mut server).await;
+
+    let now = get_time_i64();
+    let downtime = now - down_since;
+    if let Err(e) = server
+        .update_expires(database, server.expires + downtime)
+        .await
+    {
+        log::error!(
+            "COULD NOT COMPENSATE {collection}@{chain}@{token_id} FOR DOWNTIME {downtime}: {e}",
+            collection = server.collection,
+            chain = server.chain,
+            token_id = server.token_id
+        );
+    }
+
+    let event = DatabaseHealEvent {
+        id: 0,
+        collection: server.collection.clone(),
+        chain: server.chain.clone(),
+        token_id: server.token_id.clone(),
+        reason,
+        old_deployment: Json(deployment),
+        new_deployment: server.deployment.clone(),
+        downtime,
+        date: now,
+    };
+    if let Err(e) = event.insert(database).await {
+        log::error!("COULD NOT INSERT HEAL EVENT {event:?}: {e}");
+    }
+
+    let notification = DatabaseNotification {
+        account: server.owner.clone(),
+        message: match &server.deployment {
+            Some(_) => format!(
+                "Server {collection}@{chain}@{token_id} was down and has been redeployed, expiry extended by {downtime} seconds",
+                collection = server.collection,
+                chain = server.chain,
+                token_id = server.token_id
+            ),
+            None => format!(
+                "Server {collection}@{chain}@{token_id} was down and could not be redeployed yet, expiry extended by {downtime} seconds",
+                collection = server.collection,
+                chain = server.chain,
+                token_id = server.token_id
+            ),
+        },
+        date: now,
+    };
+    if let Err(e) = notification.insert(database).await {
+        log::error!("COULD NOT INSERT NOTIFICATION {notification:?}: {e}");
+    }
+}
+
+async fn check_server(database: &Database, stock: &StockClient, server: DatabaseTokenizedServer) {
+    let Some(deployment) = server
+        .deployment
+        .as_ref()
+        .map(|deployment| deployment.0.clone())
+    else {
+        return;
+    };
+
+    match heal_reason(database, &server, &deployment).await {
+        Ok(Some((reason, down_since))) => {
+            heal_server(database, stock, server, deployment, reason, down_since).await;
+        }
+        Ok(None) => (),
+        Err(e) => {
+            log::error!(
+                "Checking health of {collection}@{chain}@{token_id}: {e}",
+                collection = server.collection,
+                chain = server.chain,
+                token_id = server.token_id
+            );
+        }
+    }
+}
+
+/// Redeploys a server whose heal left it without deployment.
+async fn retry_heal(database: &Database, stock: &StockClient, mut event: DatabaseHealEvent) {
+    let mut server = match DatabaseTokenizedServer::get_by_collection_token_id(
+        database,
+        &event.collection,
+        &event.chain,
+        &event.token_id,
+    )
+    .await
+    {
+        Ok(Some(server)) => server,
+        Ok(None) => {
+            return;
+        }
+        Err(e) => {
+            log::error!(
+                "Fetching tokenized server of heal event {id}: {e}",
+                id = event.id
+            );
+            return;
+        }
+    };
+
+    log::info!(
+        "Retrying redeployment of healed {collection}@{chain}@{token_id}",
+        collection = server.collection,
+        chain = server.chain,
+        token_id = server.token_id
+    );
+    deploy_v1(database, stock, &mut server).await;
+    let Some(deployment) = server.deployment.clone() else {
+        // Tried again next run
+        return;
+    };
+
+    if let Err(e) = event.redeployed(database, deployment).await {
+        log::error!(
+            "COULD NOT UPDATE HEAL EVENT {id} WITH NEW DEPLOYMENT: {e}",
+            id = event.id
+        );
+    }
+
+    let notification = DatabaseNotification {
+        account: server.owner.clone(),
+        message: format!(
+            "Server {collection}@{chain}@{token_id} has been redeployed",
+            collection = server.collection,
+            chain = server.chain,
+            token_id = server.token_id
+        ),
+        date: get_time_i64(),
+    };
+    if let Err(e) = notification.insert(database).await {
+        log::error!("COULD NOT INSERT NOTIFICATION {notification:?}: {e}");
+    }
+}
+
+pub async fn heal_dead_servers(database: &Database, stock: &StockClient) -> JobResult {
+    if !autoheal() {
+        return Ok(());
+    }
+
+    let failed = DatabaseHealEvent::get_all_failed(database)
+        .await
+        .map_err(|e| format!("COULD NOT GET FAILED HEAL EVENTS: {e}"))?;
+    join_all(
+        failed
+            .into_iter()
+            .map(|event| retry_heal(database, stock, event)),
+    )
+    .await;
+
+    let servers = DatabaseTokenizedServer::get_all_deployed(database)
+        .await
+        .map_err(|e| format!("COULD NOT GET DEPLOYED SERVERS: {e}"))?;
+    join_all(
+        servers
+            .into_iter()
+            .map(|server| check_server(database, stock, server)),
+    )
+    .await;
+
+    Ok(())
+}
@@ -0,0 +1,175 @@
+use std::time::Duration;
+
+use futures_util::future::join_all;
+
+use crate::{
+    database::{
+        Database, health_sample::DatabaseHealthSample, notification::DatabaseNotification,
+        tokenized_server::DatabaseTokenizedServer,
+    },
+    utils::{
+        controller::ControlledXnode, env::healthalertfailures, scheduler::JobResult,
+        subdomain::server_domain, time::get_time_i64,
+    },
+};
+
+pub const UPTIME_PERIOD: i64 = 30 * 24 * 60 * 60; // 30 days in seconds
+
+/// Samples the server through its Xnode manager, falling back to an HTTP probe on its domain.
+pub async fn sample_server_health(
+    database: &Database,
+    server: &DatabaseTokenizedServer,
+) -> DatabaseHealthSample {
+    let mut sample = DatabaseHealthSample {
+        id: 0,
+        collection: server.collection.clone(),
+        chain: server.chain.clone(),
+        token_id: server.token_id.clone(),
+        healthy: false,
+        cpu: None,
+        memory_used: None,
+        memory_total: None,
+        disk_used: None,
+        disk_total: None,
+        error: None,
+        date: get_time_i64(),
+    };
+
+    let usage = match ControlledXnode::new(
+        database.clone(),
+        server.collection.clone(),
+        server.chain.clone(),
+        server.token_id.clone(),
+    )
+    .await
+    {
+        Ok(xnode) => xnode.usage().await,
+        Err(e) => Err(e),
+    };
+    match usage {
+        Ok((cpu, memory, disk)) => {
+            sample.healthy = true;
+            if !cpu.is_empty() {
+                sample.cpu = Some(cpu.iter().map(|cpu| cpu.used).sum::<f32>() / cpu.len() as f32);
+            }
+            sample.memory_used = i64::try_from(memory.used).ok();
+            sample.memory_total = i64::try_from(memory.total).ok();
+            if let Some(root) = disk.iter().find(|disk| disk.mount_point == "/") {
+                sample.disk_used = i64::try_from(root.used).ok();
+                sample.disk_total = i64::try_from(root.total).ok();
+            }
+        }
+        Err(manager_error) => {
+            match reqwest::Client::new()
+                .get(format!("https://{domain}", domain = server_domain(server)))
+                .timeout(Duration::from_secs(10))
+                .send()
+                .await
+            {
+                // Any response means the server is up, only the manager is not reachable
+                Ok(response) if !response.status().is_server_error() => {
+                    sample.healthy = true;
+                    sample.error = Some(format!("{manager_error:?}"));
+                }
+                Ok(response) => {
+                    sample.error = Some(format!(
+                        "{manager_error:?}, probe status {status}",
+                        status = response.status()
+                    ));
+                }
+                Err(e) => {
+                    sample.error = Some(format!("{manager_error:?}, probe {e}"));
+                }
+            }
+        }
+    }
+
+    sample
+}
+
+/// Number of most recent samples that were unhealthy, checked up to limit.
+pub async fn consecutive_failures(
+    database: &Database,
+    server: &DatabaseTokenizedServer,
+    limit: i64,
+) -> Result<i64, sqlx::Error> {
+    DatabaseHealthSample::get_latest_by_collection_token_id(
+        database,
+        &server.collection,
+        &server.chain,
+        &server.token_id,
+        limit,
+    )
+    .await
+    .map(|samples| samples.iter().take_while(|sample| !sample.healthy).count() as i64)
+}
+
+async fn monitor_server(database: &Database, server: DatabaseTokenizedServer) {
+    let sample = sample_server_health(database, &server).await;
+    if let Err(e) = sample.insert(database).await {
+        log::error!("COULD NOT INSERT HEALTH SAMPLE {sample:?}: {e}");
+        return;
+    }
+    if sample.healthy {
+        return;
+    }
+
+    let threshold = healthalertfailures();
+    let failures = match consecutive_failures(database, &server, threshold + 1).await {
+        Ok(failures) => failures,
+        Err(e) => {
+            log::error!(
+                "Fetching health samples of {collection}@{chain}@{token_id}: {e}",
+                collection = server.collection,
+                chain = server.chain,
+                token_id = server.token_id
+            );
+            return;
+        }
+    };
+    // Alert once per outage
+    if failures != threshold {
+        return;
+    }
+
+    log::error!(
+        "SERVER {collection}@{chain}@{token_id} UNREACHABLE FOR {failures} CONSECUTIVE CHECKS: {error:?}",
+        collection = server.collection,
+        chain = server.chain,
+        token_id = server.token_id,
+        error = sample.error
+    );
+    let notification = DatabaseNotification {
+        account: server.owner.clone(),
+        message: format!(
+            "Server {collection}@{chain}@{token_id} is unreachable",
+            collection = server.collection,
+            chain = server.chain,
+            token_id = server.token_id
+        ),
+        date: get_time_i64(),
+    };
+    if let Err(e) = notification.insert(database).await {
+        log::error!("COULD NOT INSERT NOTIFICATION {notification:?}: {e}");
+    }
+}
+
+pub async fn monitor_server_health(database: &Database) -> JobResult {
+    let servers = DatabaseTokenizedServer::get_all_deployed(database)
+        .await
+        .map_err(|e| format!("COULD NOT GET DEPLOYED SERVERS: {e}"))?;
+    join_all(
+        servers
+            .into_iter()
+            .map(|server| monitor_server(database, server)),
+    )
+    .await;
+
+    if let Err(e) =
+        DatabaseHealthSample::delete_before(database, get_time_i64() - UPTIME_PERIOD).await
+    {
+        log::error!("COULD NOT DELETE OLD HEALTH SAMPLES: {e}");
+    }
+
+    Ok(())
+}
@@ -0,0 +1,19 @@
+use std::fmt::Display;
+
+/// Failed call to an external HTTP API.
+#[derive(Debug, Clone)]
+pub enum HttpError {
+    /// Sending the request failed or it was answered with an error status.
+    Request(String),
+    /// The response body could not be read.
+    Response(String),
+}
+
+impl Display for HttpError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HttpError::Request(e) => write!(f, "request failed: {e}"),
+            HttpError::Response(e) => write!(f, "invalid response: {e}"),
+        }
+    }
+}
@@ -0,0 +1,234 @@
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+use sqlx::types::Json;
+
+use crate::{
+    database::{
+        Database,
+        inventory_issue::{DatabaseInventoryIssue, InventoryIssueKind},
+        tokenized_server::{DatabaseTokenizedServer, TokenizedServerDeployment},
+    },
+    utils::{
+        env::{hyperstackapikey, reconcileorphanage, reconcileorphancleanup},
+        http::HttpError,
+        provider::{ProviderKind, get_providers, undeploy_deployment},
+        scheduler::JobResult,
+        time::get_time_i64,
+    },
+};
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct HyperstackVirtualMachine {
+    pub id: u64,
+    pub name: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct HyperstackVirtualMachines {
+    pub instances: Vec<HyperstackVirtualMachine>,
+}
+
+/// Virtual machines requested per page of the list.
+const PAGE_SIZE: usize = 100;
+
+/// All virtual machines of the account, following the pages of the list.
+pub async fn list_hyperstack_virtual_machines() -> Result<Vec<HyperstackVirtualMachine>, HttpError>
+{
+    let client = reqwest::Client::new();
+    let mut vms: Vec<HyperstackVirtualMachine> = vec![];
+    let mut seen: HashSet<u64> = HashSet::new();
+    for page in 1.. {
+        let instances = client
+            .get("https://infrahub-api.nexgencloud.com/v1/core/virtual-machines")
+            .query(&[
+                ("page", page.to_string()),
+                ("pageSize", PAGE_SIZE.to_string()),
+            ])
+            .header("api_key", hyperstackapikey())
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| HttpError::Request(e.to_string()))?
+            .json::<HyperstackVirtualMachines>()
+            .await
+            .map_err(|e| HttpError::Response(e.to_string()))?
+            .instances;
+
+        let full_page = instances.len() >= PAGE_SIZE;
+        let mut new_instances = false;
+        for vm in instances {
+            if seen.insert(vm.id) {
+                new_instances = true;
+                vms.push(vm);
+            }
+        }
+        // A page without unseen instances means pagination was ignored
+        if !full_page || !new_instances {
+            break;
+        }
+    }
+    Ok(vms)
+}
+
+pub async fn hyperstack_virtual_machine_exists(id: u64) -> Result<bool, HttpError> {
+    let response = reqwest::Client::new()
+        .get(format!(
+            "https://infrahub-api.nexgencloud.com/v1/core/virtual-machines/{id}"
+        ))
+        .header("api_key", hyperstackapikey())
+        .send()
+        .await
+        .map_err(|e| HttpError::Request(e.to_string()))?;
+    if response.status() == reqwest::StatusCode::NOT_FOUND {
+        return Ok(false);
+    }
+
+    response
+        .error_for_status()
+        .map(|_| true)
+        .map_err(|e| HttpError::Response(e.to_string()))
+}
+
+/// Collection, chain and token id of a deployment name ({token_id}-{chain}-{collection}, see deploy_v1).
+pub fn parse_deployment_name(name: &str) -> Option<(String, String, String)> {
+    let mut parts = name.splitn(3, "-");
+    let token_id = parts.next()?;
+    let chain = parts.next()?;
+    let collection = parts.next()?;
+    if token_id.is_empty()
+        || !token_id.chars().all(|c| c.is_ascii_digit())
+        || chain.is_empty()
+        || collection.is_empty()
+        || collection.contains("-")
+    {
+        return None;
+    }
+
+    Some((
+        collection.to_string(),
+        chain.to_string(),
+        token_id.to_string(),
+    ))
+}
+
+/// Compares provider VMs with tokenized server deployments, recording orphans and ghosts.
+pub async fn reconcile_inventory(database: &Database) -> JobResult {
+    if !get_providers()
+        .iter()
+        .any(|provider| provider.provider == ProviderKind::Hyperstack)
+    {
+        return Ok(());
+    }
+
+    let vms = list_hyperstack_virtual_machines()
+        .await
+        .map_err(|e| format!("Listing hyperstack virtual machines failed: {e}"))?;
+    let servers = DatabaseTokenizedServer::get_all(database)
+        .await
+        .map_err(|e| format!("COULD NOT GET TOKENIZED SERVERS FOR RECONCILIATION: {e}"))?;
+
+    let now = get_time_i64();
+    let deployed: HashSet<u64> = servers
+        .iter()
+        .filter_map(|server| match server.deployment.as_ref().map(|d| &d.0) {
+            Some(TokenizedServerDeployment::Hyperstack { id }) => Some(*id),
+            _ => None,
+        })
+        .collect();
+    let listed: HashSet<u64> = vms.iter().map(|vm| vm.id).collect();
+
+    for vm in vms {
+        if deployed.contains(&vm.id) {
+            continue;
+        }
+        // Only VMs following our naming convention are ours to manage
+        let Some((collection, chain, token_id)) = parse_deployment_name(&vm.name) else {
+            continue;
+        };
+
+        let mut issue = DatabaseInventoryIssue {
+            kind: InventoryIssueKind::Orphan.to_string(),
+            deployment: Json(TokenizedServerDeployment::Hyperstack { id: vm.id }),
+            name: vm.name,
+            collection: Some(collection),
+            chain: Some(chain),
+            token_id: Some(token_id),
+            first_seen: now,
+            last_seen: now,
+        };
+        if let Err(e) = issue.upsert(database).await {
+            log::error!("COULD NOT RECORD INVENTORY ISSUE {issue:?}: {e}");
+            continue;
+        }
+        log::warn!(
+            "Orphaned virtual machine {name} ({deployment:?}) since {first_seen}",
+            name = issue.name,
+            deployment = issue.deployment.0,
+            first_seen = issue.first_seen
+        );
+
+        if reconcileorphancleanup() && now - issue.first_seen >= reconcileorphanage() {
+            if let Some(e) = undeploy_deployment(&issue.deployment.0).await {
+                log::error!(
+                    "CLEANUP OF ORPHANED VIRTUAL MACHINE {name} FAILED: {e:?}",
+                    name = issue.name
+                );
+                continue;
+            }
+            log::info!(
+                "Cleaned up orphaned virtual machine {name}",
+                name = issue.name
+            );
+            if let Err(e) = issue.delete(database).await {
+                log::error!("COULD NOT DELETE INVENTORY ISSUE {issue:?}: {e}");
+            }
+        }
+    }
+
+    for server in servers {
+        let Some(deployment) = server.deployment else {
+            continue;
+        };
+        let TokenizedServerDeployment::Hyperstack { id } = deployment.0 else {
+            continue;
+        };
+        if listed.contains(&id) {
+            continue;
+        }
+
+        let mut issue = DatabaseInventoryIssue {
+            kind: InventoryIssueKind::Ghost.to_string(),
+            deployment,
+            name: format!(
+                "{token_id}-{chain}-{collection}",
+                token_id = server.token_id,
+                chain = server.chain,
+                collection = server.collection
+            ),
+            collection: Some(server.collection),
+            chain: Some(server.chain),
+            token_id: Some(server.token_id),
+            first_seen: now,
+            last_seen: now,
+        };
+        if let Err(e) = issue.upsert(database).await {
+            log::error!("COULD NOT RECORD INVENTORY ISSUE {issue:?}: {e}");
+            continue;
+        }
+        log::warn!(
+            "Ghost deployment {deployment:?} of {name} since {first_seen}",
+            deployment = issue.deployment.0,
+            name = issue.name,
+            first_seen = issue.first_seen
+        );
+    }
+
+    for kind in [InventoryIssueKind::Orphan, InventoryIssueKind::Ghost] {
+        if let Err(e) = DatabaseInventoryIssue::delete_resolved(database, kind, now).await {
+            log::error!("COULD NOT DELETE RESOLVED INVENTORY ISSUES: {e}");
+        }
+    }
+
+    Ok(())
+}
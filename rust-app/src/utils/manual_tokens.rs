@@ -1,47 +1,68 @@
-use std::time::Duration;
-
-use chrono::{Timelike, Utc};
-use tokio::time::{self, Instant};
-
-use crate::database::{Database, claim::DatabaseClaim, manual_tokens::DatabaseManualTokens};
-
-pub async fn distribute_manual_tokens(database: Database) {
-    let utc_now = Utc::now();
-    let utc_next_hour = (utc_now + chrono::Duration::hours(1))
-        .with_minute(0)
-        .expect("Could not set minutes to 0")
-        .with_second(0)
-        .expect("Could not set seconds to 0");
-    let until_next_hour = utc_next_hour
-        .signed_duration_since(utc_now)
-        .to_std()
-        .expect("Unable to convert until_utc_midnight into std duration");
-
-    let mut interval = time::interval_at(
-        Instant::now() + until_next_hour,
-        Duration::from_secs(60 * 60),
-    );
-
-    loop {
-        interval.tick().await;
-        log::info!("Distributing manual token rewards");
-        let tokens = match DatabaseManualTokens::get_all_releasable_not_released(&database).await {
-            Ok(tokens) => tokens,
-            Err(e) => {
-                log::error!("COULD NOT GET MANUAL TOKENS: {e}");
-                continue;
-            }
-        };
-        for mut token in tokens {
-            if let Err(e) = token.release(&database).await {
-                log::error!("COULD NOT MARK MANUAL TOKEN {token:?} AS RELEASED INTO DATABASE: {e}");
-                return;
-            }
-
-            let claim: DatabaseClaim = (&token).into();
-            if let Err(e) = claim.insert(&database).await {
-                log::error!("COULD NOT INSERT CLAIM {claim:?} INTO DATABASE: {e}");
-            }
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    database::{Database, claim::DatabaseClaim, manual_tokens::DatabaseManualTokens},
+    utils::scheduler::JobResult,
+};
+
+#[derive(Serialize, Deserialize)]
+pub struct ManualToken {
+    pub account: String,
+    pub amount: i64,
+    pub description: String,
+    pub release_after: i64,
+}
+
+impl ManualToken {
+    pub fn to_database(&self, approval_signature: &str) -> DatabaseManualTokens {
+        DatabaseManualTokens {
+            account: self.account.clone(),
+            amount: self.amount,
+            approval_signature: approval_signature.to_string(),
+            description: self.description.clone(),
+            release_after: self.release_after,
+            released: false,
         }
     }
 }
+
+/// Inserts the uploaded tokens, released to the claim table by distribute_manual_tokens.
+pub async fn add_manual_tokens(
+    database: &Database,
+    manual_tokens: &[ManualToken],
+    approval_signature: &str,
+) {
+    for token in manual_tokens {
+        let token = token.to_database(approval_signature);
+        if let Err(e) = token.insert(database).await {
+            log::error!("COULD NOT INSERT MANUAL TOKEN {token:?}: {e}");
+        }
+    }
+}
+
+pub async fn distribute_manual_tokens(database: &Database) -> JobResult {
+    log::info!("Distributing manual token rewards");
+    let tokens = DatabaseManualTokens::get_all_releasable_not_released(database)
+        .await
+        .map_err(|e| format!("COULD NOT GET MANUAL TOKENS: {e}"))?;
+
+    let mut failed = 0;
+    for mut token in tokens {
+        if let Err(e) = token.release(database).await {
+            // Unreleased tokens are picked up again by the retry
+            log::error!("COULD NOT MARK MANUAL TOKEN {token:?} AS RELEASED INTO DATABASE: {e}");
+            failed += 1;
+            continue;
+        }
+
+        let claim: DatabaseClaim = (&token).into();
+        if let Err(e) = claim.insert(database).await {
+            log::error!("COULD NOT INSERT CLAIM {claim:?} INTO DATABASE: {e}");
+        }
+    }
+
+    match failed {
+        0 => Ok(()),
+        failed => Err(format!("COULD NOT RELEASE {failed} MANUAL TOKENS")),
+    }
+}
@@ -0,0 +1,303 @@
+use std::{collections::HashSet, str::FromStr};
+
+use alloy::{
+    primitives::{Address, B256, U256, keccak256},
+    sol_types::SolValue,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    database::{
+        Database,
+        claim::DatabaseClaim,
+        claim_merkle::{DatabaseClaimMerkleLeaf, DatabaseClaimMerkleRoot},
+        denylist::DatabaseDenylist,
+    },
+    utils::{
+        decimals::to_18_decimals, denylist::DenylistScope, env::claimmerkle, scheduler::JobResult,
+        time::get_time_i64,
+    },
+};
+
+/// Leaf of (account, total in 18 decimals), double hashed like the OpenZeppelin StandardMerkleTree.
+fn leaf_hash(account: Address, total: U256) -> B256 {
+    keccak256(keccak256((account, total).abi_encode()))
+}
+
+/// Sorted pair hash, as verified by the OpenZeppelin MerkleProof library.
+fn node_hash(a: B256, b: B256) -> B256 {
+    if a < b {
+        keccak256([a.as_slice(), b.as_slice()].concat())
+    } else {
+        keccak256([b.as_slice(), a.as_slice()].concat())
+    }
+}
+
+/// Root and proof of every leaf. Leaves are sorted so equal sets give the same root, odd nodes move up unpaired.
+fn merkle_tree(leaves: &[B256]) -> (B256, Vec<Vec<B256>>) {
+    let mut proofs = vec![vec![]; leaves.len()];
+    if leaves.is_empty() {
+        return (B256::ZERO, proofs);
+    }
+
+    // Per layer node: hash and the leaves below it
+    let mut layer: Vec<(B256, Vec<usize>)> = leaves
+        .iter()
+        .enumerate()
+        .map(|(index, leaf)| (*leaf, vec![index]))
+        .collect();
+    layer.sort_by_key(|(hash, _)| *hash);
+    while layer.len() > 1 {
+        let mut next = vec![];
+        let mut nodes = layer.into_iter();
+        while let Some((left, mut left_leaves)) = nodes.next() {
+            match nodes.next() {
+                Some((right, right_leaves)) => {
+                    for leaf in &left_leaves {
+                        proofs[*leaf].push(right);
+                    }
+                    for leaf in &right_leaves {
+                        proofs[*leaf].push(left);
+                    }
+                    left_leaves.extend(right_leaves);
+                    next.push((node_hash(left, right), left_leaves));
+                }
+                None => next.push((left, left_leaves)),
+            }
+        }
+        layer = next;
+    }
+
+    (layer[0].0, proofs)
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ClaimMerkleLeaf {
+    pub account: String,
+    /// Total in 18 decimals, as encoded in the leaf
+    pub total: U256,
+    pub proof: Vec<String>,
+}
+
+impl From<DatabaseClaimMerkleLeaf> for ClaimMerkleLeaf {
+    fn from(val: DatabaseClaimMerkleLeaf) -> Self {
+        ClaimMerkleLeaf {
+            account: val.account,
+            total: to_18_decimals(U256::from(val.total)),
+            proof: val.proof,
+        }
+    }
+}
+
+/// Full tree, to set the root on the Merkle distributor contract.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ClaimMerkleExport {
+    pub root: String,
+    pub total: U256,
+    pub date: i64,
+    /// abi.encode of each leaf, hashed twice with keccak256
+    pub leaf_encoding: Vec<String>,
+    pub leaves: Vec<ClaimMerkleLeaf>,
+}
+
+pub async fn get_claim_merkle_export(
+    database: &Database,
+    root: DatabaseClaimMerkleRoot,
+) -> Result<ClaimMerkleExport, sqlx::Error> {
+    let leaves = DatabaseClaimMerkleLeaf::get_all_by_root_id(database, root.id).await?;
+    Ok(ClaimMerkleExport {
+        root: root.root,
+        total: to_18_decimals(U256::from(root.total)),
+        date: root.date,
+        leaf_encoding: vec!["address".to_string(), "uint256".to_string()],
+        leaves: leaves.into_iter().map(ClaimMerkleLeaf::from).collect(),
+    })
+}
+
+/// Snapshots the claim totals into a new Merkle root, unless they are unchanged since the latest one.
+pub async fn snapshot_claim_merkle(database: &Database) -> JobResult {
+    if !claimmerkle() {
+        return Ok(());
+    }
+
+    // Denied accounts get no leaf, so they have no proof to claim with
+    let denied: HashSet<String> = DatabaseDenylist::get_all_active_by_scope(
+        database,
+        &DenylistScope::Claim.to_string(),
+        get_time_i64(),
+    )
+    .await
+    .map_err(|e| format!("COULD NOT GET CLAIM DENYLIST: {e}"))?
+    .into_iter()
+    .map(|entry| entry.account.to_lowercase())
+    .collect();
+    let totals: Vec<(String, i64)> = DatabaseClaim::get_totals(database)
+        .await
+        .map_err(|e| format!("COULD NOT GET CLAIM TOTALS: {e}"))?
+        .into_iter()
+        .filter(|(account, total)| {
+            if Address::from_str(account).is_err() {
+                log::warn!("Claim account {account} is not an address, left out of Merkle tree");
+                return false;
+            }
+            if denied.contains(&account.to_lowercase()) {
+                log::warn!("Claim account {account} is denied, left out of Merkle tree");
+                return false;
+            }
+            *total > 0
+        })
+        .collect();
+    let hashes: Vec<B256> = totals
+        .iter()
+        .map(|(account, total)| {
+            leaf_hash(
+                Address::from_str(account).unwrap_or_default(),
+                to_18_decimals(U256::from(*total)),
+            )
+        })
+        .collect();
+    let (root, proofs) = merkle_tree(&hashes);
+    let root = root.to_string();
+
+    match DatabaseClaimMerkleRoot::get_latest(database).await {
+        Ok(Some(latest)) if latest.root == root => {
+            return Ok(());
+        }
+        Ok(_) => (),
+        Err(e) => {
+            return Err(format!("COULD NOT GET LATEST CLAIM MERKLE ROOT: {e}"));
+        }
+    }
+
+    let merkle_root = DatabaseClaimMerkleRoot {
+        id: 0,
+        root,
+        accounts: totals.len() as i32,
+        total: totals.iter().map(|(_, total)| total).sum(),
+        date: get_time_i64(),
+    };
+    let leaves: Vec<DatabaseClaimMerkleLeaf> = totals
+        .into_iter()
+        .zip(proofs)
+        .map(|((account, total), proof)| DatabaseClaimMerkleLeaf {
+            account,
+            total,
+            proof: proof.iter().map(|hash| hash.to_string()).collect(),
+        })
+        .collect();
+
+    let mut transaction = database
+        .begin()
+        .await
+        .map_err(|e| format!("COULD NOT START CLAIM MERKLE TRANSACTION: {e}"))?;
+    merkle_root
+        .insert(&mut transaction, &leaves)
+        .await
+        .map_err(|e| format!("COULD NOT INSERT CLAIM MERKLE ROOT {merkle_root:?}: {e}"))?;
+    transaction
+        .commit()
+        .await
+        .map_err(|e| format!("COULD NOT COMMIT CLAIM MERKLE ROOT {merkle_root:?}: {e}"))?;
+
+    log::info!(
+        "New claim Merkle root {root} over {accounts} accounts",
+        root = merkle_root.root,
+        accounts = merkle_root.accounts
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::primitives::{address, b256};
+
+    use super::*;
+
+    /// MerkleProof.verify of OpenZeppelin: fold the proof into the leaf with sorted pair hashing.
+    fn verify(proof: &[B256], root: B256, leaf: B256) -> bool {
+        proof
+            .iter()
+            .fold(leaf, |computed, sibling| node_hash(computed, *sibling))
+            == root
+    }
+
+    fn leaves(count: u64) -> Vec<B256> {
+        (1..=count)
+            .map(|i| leaf_hash(Address::with_last_byte(i as u8), U256::from(i * 1_000)))
+            .collect()
+    }
+
+    #[test]
+    fn empty_tree() {
+        let (root, proofs) = merkle_tree(&[]);
+        assert_eq!(root, B256::ZERO);
+        assert!(proofs.is_empty());
+    }
+
+    #[test]
+    fn single_leaf_is_root() {
+        let leaves = leaves(1);
+        let (root, proofs) = merkle_tree(&leaves);
+        assert_eq!(root, leaves[0]);
+        assert_eq!(proofs, vec![Vec::<B256>::new()]);
+    }
+
+    #[test]
+    fn every_proof_verifies() {
+        for count in 1..=17 {
+            let leaves = leaves(count);
+            let (root, proofs) = merkle_tree(&leaves);
+            for (leaf, proof) in leaves.iter().zip(&proofs) {
+                assert!(verify(proof, root, *leaf), "{count} leaves");
+            }
+        }
+    }
+
+    #[test]
+    fn proof_of_other_leaf_fails() {
+        let leaves = leaves(5);
+        let (root, proofs) = merkle_tree(&leaves);
+        assert!(!verify(&proofs[0], root, leaves[1]));
+    }
+
+    #[test]
+    fn root_independent_of_order() {
+        let mut leaves = leaves(6);
+        let (root, _) = merkle_tree(&leaves);
+        leaves.reverse();
+        assert_eq!(merkle_tree(&leaves).0, root);
+    }
+
+    #[test]
+    fn known_root() {
+        // Example of the OpenZeppelin merkle-tree README, equal to StandardMerkleTree.of(values, ["address", "uint256"]).root
+        let leaves = [
+            leaf_hash(
+                address!("0x1111111111111111111111111111111111111111"),
+                U256::from(5_000_000_000_000_000_000_u64),
+            ),
+            leaf_hash(
+                address!("0x2222222222222222222222222222222222222222"),
+                U256::from(2_500_000_000_000_000_000_u64),
+            ),
+        ];
+        assert_eq!(
+            merkle_tree(&leaves).0,
+            b256!("0xd4dee0beab2d53f2cc83e567171bd2820e49898130a22622b10ead383e90bd77")
+        );
+
+        // Odd node moves up a level unhashed
+        let leaves = [
+            leaves[0],
+            leaves[1],
+            leaf_hash(
+                address!("0x3333333333333333333333333333333333333333"),
+                U256::from(1_000_000_000_000_000_000_u64),
+            ),
+        ];
+        assert_eq!(
+            merkle_tree(&leaves).0,
+            b256!("0x1bc1c2bd927f8451332d9a50179032863d2cf0ffca6fcfec1b3e36e0f6260d3e")
+        );
+    }
+}
@@ -0,0 +1,87 @@
+use std::{
+    collections::BTreeMap,
+    sync::{
+        Arc,
+        atomic::{AtomicI64, Ordering},
+    },
+};
+
+use alloy::{primitives::Address, providers::DynProvider};
+use sqlx::types::Json;
+
+use crate::{
+    database::{
+        Database,
+        tokenized_server::{Chain, Collection, DatabaseTokenizedServer},
+    },
+    utils::{
+        preset::AppPreset,
+        stock::StockClient,
+        tier::Tier,
+        time::get_time_i64,
+        wallet::mint_tokenized_server,
+        xnode::{address_to_xnode_user, deploy_v1},
+    },
+};
+
+#[derive(Clone)]
+pub struct OwnAIV1TokenCounter {
+    counter: Arc<AtomicI64>,
+}
+impl OwnAIV1TokenCounter {
+    pub async fn new(database: Database) -> Self {
+        let collection = Collection::OwnAIv1.to_string();
+        let chain = Chain::Base.to_string();
+        let max = match DatabaseTokenizedServer::get_max_token_id_by_collection(
+            &database,
+            &collection,
+            &chain,
+        )
+        .await
+        {
+            Ok(max) => max.unwrap_or(0),
+            Err(e) => {
+                panic!("Could not fetch max token_id for {collection} minting: {e}")
+            }
+        };
+
+        Self {
+            counter: Arc::new(AtomicI64::from(max + 1)),
+        }
+    }
+}
+
+/// Inserts, mints and deploys the next OwnAIV1 token, payment should already be handled.
+pub async fn mint_v1(
+    database: &Database,
+    provider: &DynProvider,
+    stock: &StockClient,
+    counter: &OwnAIV1TokenCounter,
+    to: Address,
+    tier: Tier,
+    (preset, preset_parameters): (AppPreset, BTreeMap<String, String>),
+) -> Result<i64, sqlx::Error> {
+    let token_id = counter.counter.fetch_add(1, Ordering::Relaxed);
+    let one_month = 30 * 24 * 60 * 60; // 1 month in seconds
+    let mut server = DatabaseTokenizedServer {
+        collection: Collection::OwnAIv1.to_string(),
+        chain: Chain::Base.to_string(),
+        token_id: token_id.to_string(),
+        owner: to.to_string(),
+        controller: address_to_xnode_user(to),
+        deployment: None,
+        expires: get_time_i64() + one_month,
+        tier: Some(tier.id),
+        preset: Some(preset.id),
+        preset_parameters: Some(Json(preset_parameters)),
+        owner_since: get_time_i64(),
+    };
+    if let Err(e) = server.insert(database).await {
+        log::error!("COULD NOT INSERT TOKENIZED SERVER {server:?}: {e}");
+        return Err(e);
+    }
+    mint_tokenized_server(provider, to, token_id).await;
+    deploy_v1(database, stock, &mut server).await;
+
+    Ok(token_id)
+}
@@ -1,9 +1,33 @@
+pub mod admin_signer;
+pub mod auto_renew;
+pub mod claim;
 pub mod controller;
+pub mod custom_domain;
 pub mod decimals;
+pub mod denylist;
 pub mod env;
+pub mod heal;
+pub mod health;
+pub mod http;
+pub mod inventory;
 pub mod manual_tokens;
+pub mod merkle;
+pub mod mint;
+pub mod power;
+pub mod preset;
+pub mod promo_code;
+pub mod proposal;
+pub mod provider;
+pub mod replay;
+pub mod reservation;
+pub mod scheduler;
 pub mod signature_validator;
+pub mod signer;
 pub mod staking;
+pub mod stock;
+pub mod subdomain;
+pub mod tier;
 pub mod time;
+pub mod typed_data;
 pub mod wallet;
 pub mod xnode;
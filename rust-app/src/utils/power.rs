@@ -0,0 +1,190 @@
+use std::{fmt::Display, str::FromStr};
+
+use crate::{
+    database::{
+        Database,
+        power_action::DatabasePowerAction,
+        tokenized_server::{DatabaseTokenizedServer, TokenizedServerDeployment},
+    },
+    utils::{
+        controller::ControlledXnode,
+        env::hyperstackapikey,
+        stock::StockClient,
+        xnode::{deploy_v1, undeploy},
+    },
+};
+
+pub enum PowerAction {
+    Reboot,
+    Stop,
+    Start,
+    Reinstall,
+}
+
+impl Display for PowerAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PowerAction::Reboot => f.write_str("reboot"),
+            PowerAction::Stop => f.write_str("stop"),
+            PowerAction::Start => f.write_str("start"),
+            PowerAction::Reinstall => f.write_str("reinstall"),
+        }
+    }
+}
+
+impl FromStr for PowerAction {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "reboot" => Ok(PowerAction::Reboot),
+            "stop" => Ok(PowerAction::Stop),
+            "start" => Ok(PowerAction::Start),
+            "reinstall" => Ok(PowerAction::Reinstall),
+            _ => Err(()),
+        }
+    }
+}
+
+impl PowerAction {
+    /// Message the owner signs to perform this action.
+    pub fn message(&self, collection: &str, chain: &str, token_id: &str) -> String {
+        let verb = match self {
+            PowerAction::Reboot => "Reboot",
+            PowerAction::Stop => "Stop",
+            PowerAction::Start => "Start",
+            PowerAction::Reinstall => "Reinstall",
+        };
+        format!("{verb} {collection}@{chain}@{token_id}")
+    }
+}
+
+#[derive(Debug)]
+pub enum PowerError {
+    NotDeployed,
+    Request(String),
+    Reinstall(String),
+}
+
+impl Display for PowerError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PowerError::NotDeployed => f.write_str("not deployed"),
+            PowerError::Request(e) => write!(f, "request failed: {e}"),
+            PowerError::Reinstall(e) => write!(f, "reinstall failed: {e}"),
+        }
+    }
+}
+
+/// Stores the outcome of the claimed power action.
+async fn finish_power_action(database: &Database, id: i32, result: &Result<(), PowerError>) {
+    let error = result.as_ref().err().map(|e| e.to_string());
+    if let Err(e) = DatabasePowerAction::finish(database, id, error).await {
+        log::error!("COULD NOT FINISH POWER ACTION {id}: {e}");
+    }
+}
+
+/// Power actions on the VM itself, for when the Xnode manager is unreachable.
+async fn provider_power_action(
+    deployment: &TokenizedServerDeployment,
+    action: &str,
+) -> Result<(), PowerError> {
+    match deployment {
+        TokenizedServerDeployment::Hyperstack { id } => reqwest::Client::new()
+            .get(format!(
+                "https://infrahub-api.nexgencloud.com/v1/core/virtual-machines/{id}/{action}"
+            ))
+            .header("api_key", hyperstackapikey())
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map(|_| ())
+            .map_err(|e| PowerError::Request(e.to_string())),
+        TokenizedServerDeployment::Mock { id } => {
+            log::info!("Mock {action} of {id}");
+            Ok(())
+        }
+    }
+}
+
+/// Performs the power action claimed as id and records its outcome.
+/// Reinstalls continue in the background and are recorded once finished.
+pub async fn execute_power_action(
+    database: &Database,
+    stock: &StockClient,
+    server: DatabaseTokenizedServer,
+    action: &PowerAction,
+    id: i32,
+) -> Result<(), PowerError> {
+    let deployment = match &server.deployment {
+        Some(deployment) => deployment.0.clone(),
+        None => {
+            let result = Err(PowerError::NotDeployed);
+            finish_power_action(database, id, &result).await;
+            return result;
+        }
+    };
+
+    let result = match action {
+        PowerAction::Reboot => reboot(database, &server, &deployment).await,
+        PowerAction::Stop => provider_power_action(&deployment, "stop").await,
+        PowerAction::Start => provider_power_action(&deployment, "start").await,
+        PowerAction::Reinstall => {
+            // Deployment waits for the new VM to come online
+            let database = database.clone();
+            let stock = stock.clone();
+            tokio::spawn(async move {
+                let result = reinstall(&database, &stock, server).await;
+                finish_power_action(&database, id, &result).await;
+            });
+            return Ok(());
+        }
+    };
+    finish_power_action(database, id, &result).await;
+    result
+}
+
+async fn reboot(
+    database: &Database,
+    server: &DatabaseTokenizedServer,
+    deployment: &TokenizedServerDeployment,
+) -> Result<(), PowerError> {
+    let reboot = match ControlledXnode::new(
+        database.clone(),
+        server.collection.clone(),
+        server.chain.clone(),
+        server.token_id.clone(),
+    )
+    .await
+    {
+        Ok(xnode) => xnode.reboot().await.map(|_| ()),
+        Err(e) => Err(e),
+    };
+    if let Err(e) = reboot {
+        // Hanging servers cannot be rebooted gracefully
+        log::warn!(
+            "Graceful reboot of {collection}@{chain}@{token_id} failed, hard rebooting: {e:?}",
+            collection = server.collection,
+            chain = server.chain,
+            token_id = server.token_id
+        );
+        provider_power_action(deployment, "hard-reboot").await?;
+    }
+    Ok(())
+}
+
+async fn reinstall(
+    database: &Database,
+    stock: &StockClient,
+    mut server: DatabaseTokenizedServer,
+) -> Result<(), PowerError> {
+    undeploy(database, &mut server).await;
+    if server.deployment.is_some() {
+        return Err(PowerError::Reinstall("undeployment failed".to_string()));
+    }
+    deploy_v1(database, stock, &mut server).await;
+    if server.deployment.is_none() {
+        return Err(PowerError::Reinstall("deployment failed".to_string()));
+    }
+    Ok(())
+}
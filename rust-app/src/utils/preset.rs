@@ -0,0 +1,263 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{database::tokenized_server::DatabaseTokenizedServer, utils::env::ownaiv1presets};
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PresetParameter {
+    pub name: String,
+    pub description: String,
+    pub default: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AppPreset {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    /// Where the server domain is forwarded to, templated
+    pub forward: String,
+    /// Extra NixOS config, templated
+    #[serde(default)]
+    pub config: String,
+    #[serde(default)]
+    pub parameters: Vec<PresetParameter>,
+}
+
+#[allow(dead_code)]
+#[derive(Debug)]
+pub enum PresetError {
+    MissingParameter(String),
+    UnknownParameter(String),
+    InvalidValue(String),
+}
+
+/// Parameters end up in the NixOS config, so only allow characters that cannot escape a string.
+fn is_valid_parameter_value(value: &str) -> bool {
+    !value.is_empty()
+        && value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || ['.', '_', ':', '/', '-', '@'].contains(&c))
+}
+
+impl AppPreset {
+    /// Provided parameters completed with defaults.
+    pub fn resolve_parameters(
+        &self,
+        parameters: &BTreeMap<String, String>,
+    ) -> Result<BTreeMap<String, String>, PresetError> {
+        if let Some(name) = parameters.keys().find(|name| {
+            !self
+                .parameters
+                .iter()
+                .any(|parameter| &parameter.name == *name)
+        }) {
+            return Err(PresetError::UnknownParameter(name.clone()));
+        }
+
+        let mut resolved = BTreeMap::new();
+        for parameter in &self.parameters {
+            let value = match parameters
+                .get(&parameter.name)
+                .or(parameter.default.as_ref())
+            {
+                Some(value) => value,
+                None => {
+                    return Err(PresetError::MissingParameter(parameter.name.clone()));
+                }
+            };
+            if !is_valid_parameter_value(value) {
+                return Err(PresetError::InvalidValue(parameter.name.clone()));
+            }
+            resolved.insert(parameter.name.clone(), value.clone());
+        }
+
+        Ok(resolved)
+    }
+
+    fn template(&self, text: &str, domain: &str, parameters: &BTreeMap<String, String>) -> String {
+        parameters
+            .iter()
+            .fold(text.replace("{{domain}}", domain), |text, (name, value)| {
+                text.replace(&format!("{{{{{name}}}}}"), value)
+            })
+    }
+
+    pub fn forward(
+        &self,
+        domain: &str,
+        parameters: &BTreeMap<String, String>,
+    ) -> Result<String, PresetError> {
+        let parameters = self.resolve_parameters(parameters)?;
+        Ok(self.template(&self.forward, domain, &parameters))
+    }
+
+    /// NixOS config serving the app on the domain.
+    pub fn config(
+        &self,
+        domain: &str,
+        parameters: &BTreeMap<String, String>,
+    ) -> Result<String, PresetError> {
+        let parameters = self.resolve_parameters(parameters)?;
+        let forward = self.template(&self.forward, domain, &parameters);
+        let mut config = format!(
+            "services.xnode-reverse-proxy.rules.\"{domain}\" = [ {{ forward = \"{forward}\"; }} ];"
+        );
+        if !self.config.is_empty() {
+            config.push('\n');
+            config.push_str(&self.template(&self.config, domain, &parameters));
+        }
+        Ok(config)
+    }
+}
+
+/// Chat interface of the base Xnode config and an inference API configured here.
+fn default_presets() -> Vec<AppPreset> {
+    vec![
+        AppPreset {
+            id: "ai-chat".to_string(),
+            name: "AI Chat".to_string(),
+            description: "Chat interface for local AI models.".to_string(),
+            forward: "http://xnode-ai-chat:8080".to_string(),
+            config: String::new(),
+            parameters: vec![],
+        },
+        AppPreset {
+            id: "inference-api".to_string(),
+            name: "Inference API".to_string(),
+            description: "Ollama API serving a local AI model.".to_string(),
+            forward: "http://127.0.0.1:11434".to_string(),
+            config: "services.ollama = { enable = true; acceleration = \"cuda\"; loadModels = [ \"{{model}}\" ]; };".to_string(),
+            parameters: vec![PresetParameter {
+                name: "model".to_string(),
+                description: "Ollama model to serve.".to_string(),
+                default: Some("llama3.1:8b".to_string()),
+            }],
+        },
+    ]
+}
+
+pub fn get_presets() -> Vec<AppPreset> {
+    match ownaiv1presets() {
+        Some(presets) => serde_json::from_str(&presets)
+            .unwrap_or_else(|e| panic!("Invalid OWNAIV1PRESETS provided: {e}")),
+        None => default_presets(),
+    }
+}
+
+/// Preset by id, servers without a stored preset use the first (default) preset.
+pub fn get_preset(id: Option<&str>) -> Option<AppPreset> {
+    let presets = get_presets();
+    match id {
+        Some(id) => presets.into_iter().find(|preset| preset.id == id),
+        None => presets.into_iter().next(),
+    }
+}
+
+/// Preset and stored parameters of the server.
+pub fn server_preset(
+    server: &DatabaseTokenizedServer,
+) -> Option<(AppPreset, BTreeMap<String, String>)> {
+    get_preset(server.preset.as_deref()).map(|preset| {
+        (
+            preset,
+            server
+                .preset_parameters
+                .as_ref()
+                .map(|parameters| parameters.0.clone())
+                .unwrap_or_default(),
+        )
+    })
+}
+
+/// Config servers were deployed with before presets existed, replaced on the first switch.
+pub fn legacy_app_config(domain: &str) -> String {
+    format!(
+        "services.xnode-reverse-proxy.rules.\"{domain}\" = [ {{ forward = \"http://xnode-ai-chat:8080\"; }} ];"
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parameters(parameters: &[(&str, &str)]) -> BTreeMap<String, String> {
+        parameters
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    }
+
+    fn inference_api() -> AppPreset {
+        default_presets()
+            .into_iter()
+            .find(|preset| preset.id == "inference-api")
+            .unwrap()
+    }
+
+    #[test]
+    fn parse_presets() {
+        let presets: Vec<AppPreset> = serde_json::from_str(
+            r#"[{"id":"web","name":"Web","description":"Static site.","forward":"http://127.0.0.1:{{port}}","parameters":[{"name":"port","description":"Port.","default":"80"}]},{"id":"chat","name":"Chat","description":"Chat.","forward":"http://xnode-ai-chat:8080"}]"#,
+        )
+        .unwrap();
+        assert_eq!(presets[0].parameters[0].default.as_deref(), Some("80"));
+        assert!(presets[0].config.is_empty());
+        assert!(presets[1].parameters.is_empty());
+    }
+
+    #[test]
+    fn default_presets_resolve_without_parameters() {
+        for preset in default_presets() {
+            assert!(
+                preset.config("app.example.com", &BTreeMap::new()).is_ok(),
+                "{id}",
+                id = preset.id
+            );
+        }
+    }
+
+    #[test]
+    fn config_substitutes_parameters() {
+        let config = inference_api()
+            .config("app.example.com", &parameters(&[("model", "qwen3:8b")]))
+            .unwrap();
+        assert_eq!(
+            config,
+            "services.xnode-reverse-proxy.rules.\"app.example.com\" = [ { forward = \"http://127.0.0.1:11434\"; } ];\nservices.ollama = { enable = true; acceleration = \"cuda\"; loadModels = [ \"qwen3:8b\" ]; };"
+        );
+    }
+
+    #[test]
+    fn config_uses_default_parameters() {
+        let config = inference_api()
+            .config("app.example.com", &BTreeMap::new())
+            .unwrap();
+        assert!(config.contains("\"llama3.1:8b\""));
+    }
+
+    #[test]
+    fn rejects_invalid_parameters() {
+        let preset = inference_api();
+        assert!(matches!(
+            preset.resolve_parameters(&parameters(&[("gpu", "all")])),
+            Err(PresetError::UnknownParameter(name)) if name == "gpu"
+        ));
+        assert!(matches!(
+            preset.resolve_parameters(&parameters(&[("model", "x\"; }; evil = {")])),
+            Err(PresetError::InvalidValue(name)) if name == "model"
+        ));
+        assert!(matches!(
+            preset.resolve_parameters(&parameters(&[("model", "")])),
+            Err(PresetError::InvalidValue(_))
+        ));
+
+        let mut required = preset;
+        required.parameters[0].default = None;
+        assert!(matches!(
+            required.resolve_parameters(&BTreeMap::new()),
+            Err(PresetError::MissingParameter(name)) if name == "model"
+        ));
+    }
+}
@@ -0,0 +1,30 @@
+use serde::{Deserialize, Serialize};
+
+use crate::database::{Database, promo_code::DatabasePromoCode};
+
+#[derive(Serialize, Deserialize)]
+pub struct PromoCode {
+    pub code: String,
+    pub credits: i64,
+    pub description: String,
+}
+
+impl From<&PromoCode> for DatabasePromoCode {
+    fn from(code: &PromoCode) -> Self {
+        DatabasePromoCode {
+            code: code.code.clone(),
+            credits: code.credits,
+            description: code.description.clone(),
+            redeemed_by: None,
+        }
+    }
+}
+
+pub async fn add_promo_codes(database: &Database, promo_codes: &[PromoCode]) {
+    for code in promo_codes {
+        let code: DatabasePromoCode = code.into();
+        if let Err(e) = code.insert(database).await {
+            log::error!("COULD NOT INSERT PROMO CODE {code:?}: {e}");
+        }
+    }
+}
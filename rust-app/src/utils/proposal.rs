@@ -0,0 +1,143 @@
+use crate::{
+    database::{
+        Database, DatabaseTransaction,
+        admin_proposal::{DatabaseAdminProposal, DatabaseAdminProposalApproval, ProposalStatus},
+        promo_code::DatabasePromoCode,
+    },
+    utils::{
+        admin_signer::{AdminRole, active_admin_signers},
+        env::adminthresholds,
+        manual_tokens::ManualToken,
+        promo_code::PromoCode,
+        time::get_time_i64,
+    },
+};
+
+/// Approvals needed before an upload of the role is applied, 1 allows direct uploads.
+pub fn proposal_threshold(role: AdminRole) -> i32 {
+    adminthresholds()
+        .get(&role.to_string())
+        .copied()
+        .unwrap_or(1)
+        .max(1)
+}
+
+/// Payload of a role that can be proposed.
+pub enum ProposalPayload {
+    ManualTokens(Vec<ManualToken>),
+    PromoCodes(Vec<PromoCode>),
+}
+
+impl ProposalPayload {
+    /// None when the role has no proposals or the payload does not parse.
+    pub fn parse(role: AdminRole, payload: &str) -> Option<Self> {
+        match role {
+            AdminRole::ManualTokens => serde_json::from_str(payload)
+                .ok()
+                .map(ProposalPayload::ManualTokens),
+            AdminRole::PromoCode => serde_json::from_str(payload)
+                .ok()
+                .map(ProposalPayload::PromoCodes),
+            AdminRole::Agreement | AdminRole::Job | AdminRole::Denylist => None,
+        }
+    }
+
+    /// Inserts every row of the payload, the first failing row fails the whole payload.
+    async fn apply(
+        &self,
+        transaction: &mut DatabaseTransaction,
+        proposal_id: i32,
+    ) -> Result<(), sqlx::Error> {
+        match self {
+            ProposalPayload::ManualTokens(manual_tokens) => {
+                let approval = format!("proposal {proposal_id}");
+                for token in manual_tokens {
+                    token
+                        .to_database(&approval)
+                        .insert_transaction(transaction)
+                        .await?;
+                }
+            }
+            ProposalPayload::PromoCodes(promo_codes) => {
+                for code in promo_codes {
+                    DatabasePromoCode::from(code)
+                        .insert_transaction(transaction)
+                        .await?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Applies the proposal once approvals from currently active signers reach its threshold.
+/// Approvals of revoked or expired signers no longer count.
+pub async fn apply_if_approved(
+    database: &Database,
+    proposal: &mut DatabaseAdminProposal,
+) -> Result<(), sqlx::Error> {
+    let (Ok(role), Ok(ProposalStatus::Pending)) = (
+        proposal.role.parse::<AdminRole>(),
+        proposal.status.parse::<ProposalStatus>(),
+    ) else {
+        return Ok(());
+    };
+
+    let signers = active_admin_signers(database, role).await?;
+    let approvals = DatabaseAdminProposalApproval::get_all_by_proposal_id(database, proposal.id)
+        .await?
+        .into_iter()
+        .filter(|approval| {
+            signers
+                .iter()
+                .any(|signer| signer.eq_ignore_ascii_case(&approval.signer))
+        })
+        .count();
+    if (approvals as i32) < proposal.threshold {
+        return Ok(());
+    }
+
+    let Some(payload) = ProposalPayload::parse(role, &proposal.payload) else {
+        proposal
+            .finish(
+                database,
+                ProposalStatus::Failed,
+                Some("Invalid payload".to_string()),
+                get_time_i64(),
+            )
+            .await?;
+        return Ok(());
+    };
+    // Only the request that moves it out of pending applies it, together with its payload
+    let now = get_time_i64();
+    let mut transaction = database.begin().await?;
+    if !proposal.apply_transaction(&mut transaction, now).await? {
+        return Ok(());
+    }
+    log::info!(
+        "Applying {role} proposal {id} with {approvals} approvals",
+        id = proposal.id
+    );
+    let applied = match payload.apply(&mut transaction, proposal.id).await {
+        Ok(()) => transaction.commit().await,
+        Err(e) => Err(e),
+    };
+    match applied {
+        Ok(()) => {
+            proposal.status = ProposalStatus::Applied.to_string();
+            proposal.error = None;
+            proposal.applied_at = Some(now);
+        }
+        Err(e) => {
+            log::error!(
+                "COULD NOT APPLY {role} PROPOSAL {id}: {e}",
+                id = proposal.id
+            );
+            proposal
+                .finish(database, ProposalStatus::Failed, Some(e.to_string()), now)
+                .await?;
+        }
+    }
+
+    Ok(())
+}
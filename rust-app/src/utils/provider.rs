@@ -0,0 +1,378 @@
+use std::{
+    net::Ipv4Addr,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+use serde::{Deserialize, Serialize};
+use xnode_deployer::{
+    DeployInput, Error, OptionalSupport, XnodeDeployer,
+    hyperstack::{HyperstackDeployer, HyperstackHardware, HyperstackOutput},
+};
+
+use crate::{
+    database::tokenized_server::TokenizedServerDeployment,
+    utils::{
+        env::{deploymentpolicy, deploymentproviders, hyperstackapikey},
+        http::HttpError,
+        stock::StockClient,
+        time::get_time_u64,
+    },
+};
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub enum ProviderKind {
+    Hyperstack,
+    Mock { available: u64 },
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RegionConfig {
+    pub name: String,
+    pub environment: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct FlavorConfig {
+    pub name: String,
+    pub model: String,
+    #[serde(default = "default_configuration")]
+    pub configuration: String,
+    pub cost: i64,
+}
+
+fn default_configuration() -> String {
+    "1x".to_string()
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ProviderConfig {
+    pub provider: ProviderKind,
+    pub regions: Vec<RegionConfig>,
+    pub flavors: Vec<FlavorConfig>,
+}
+
+pub enum DeploymentPolicy {
+    Cheapest,
+    RegionPreference(Vec<String>),
+    Failover,
+}
+
+/// Configured providers with their regions and flavors.
+pub fn get_providers() -> Vec<ProviderConfig> {
+    serde_json::from_str(&deploymentproviders())
+        .unwrap_or_else(|e| panic!("Invalid DEPLOYMENTPROVIDERS provided: {e}"))
+}
+
+impl DeploymentPolicy {
+    pub fn parse(policy: &str) -> Option<Self> {
+        match policy.split_once(":") {
+            Some(("region", regions)) => Some(DeploymentPolicy::RegionPreference(
+                regions
+                    .split(",")
+                    .map(|region| region.to_string())
+                    .collect(),
+            )),
+            _ => match policy {
+                "cheapest" => Some(DeploymentPolicy::Cheapest),
+                "failover" => Some(DeploymentPolicy::Failover),
+                _ => None,
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct DeploymentTarget {
+    pub provider: ProviderKind,
+    pub region: RegionConfig,
+    pub flavor: FlavorConfig,
+}
+
+impl DeploymentTarget {
+    pub async fn available(&self, stock: &StockClient) -> Result<u64, HttpError> {
+        match &self.provider {
+            ProviderKind::Hyperstack => stock.hyperstack().await.map(|stocks| {
+                stocks.available(
+                    &self.region.name,
+                    &self.flavor.model,
+                    &self.flavor.configuration,
+                )
+            }),
+            ProviderKind::Mock { available } => Ok(*available),
+        }
+    }
+
+    pub fn deployer(&self, name: String) -> Deployer {
+        match &self.provider {
+            ProviderKind::Hyperstack => Deployer::Hyperstack(HyperstackDeployer::new(
+                hyperstackapikey(),
+                HyperstackHardware::VirtualMachine {
+                    name,
+                    environment_name: self.region.environment.clone(),
+                    flavor_name: self.flavor.name.clone(),
+                    key_name: "NixOS".to_string(),
+                },
+            )),
+            ProviderKind::Mock { .. } => Deployer::Mock(MockDeployer {}),
+        }
+    }
+}
+
+pub fn get_deployment_policy() -> DeploymentPolicy {
+    let policy = deploymentpolicy();
+    DeploymentPolicy::parse(&policy)
+        .unwrap_or_else(|| panic!("Invalid DEPLOYMENTPOLICY provided: {policy}"))
+}
+
+/// All configured provider, region and flavor combinations.
+pub fn get_deployment_targets() -> Vec<DeploymentTarget> {
+    get_providers()
+        .into_iter()
+        .flat_map(|provider| {
+            provider
+                .regions
+                .iter()
+                .flat_map(|region| {
+                    provider.flavors.iter().map(|flavor| DeploymentTarget {
+                        provider: provider.provider.clone(),
+                        region: region.clone(),
+                        flavor: flavor.clone(),
+                    })
+                })
+                .collect::<Vec<DeploymentTarget>>()
+        })
+        .collect()
+}
+
+/// Available deployment targets, in the order the policy attempts them.
+pub async fn select_deployment_targets(
+    stock: &StockClient,
+    targets: Vec<DeploymentTarget>,
+    check_availability: bool,
+    policy: DeploymentPolicy,
+) -> Vec<DeploymentTarget> {
+    let mut available = vec![];
+    for target in targets {
+        if !check_availability {
+            available.push(target);
+            continue;
+        }
+
+        match target.available(stock).await {
+            Ok(0) => (),
+            Ok(_) => available.push(target),
+            Err(e) => {
+                log::warn!("Could not check availability of {target:?}: {e}");
+            }
+        }
+    }
+
+    match policy {
+        DeploymentPolicy::Cheapest => available.sort_by_key(|target| target.flavor.cost),
+        DeploymentPolicy::RegionPreference(regions) => available.sort_by_key(|target| {
+            regions
+                .iter()
+                .position(|region| region == &target.region.name)
+                .unwrap_or(regions.len())
+        }),
+        DeploymentPolicy::Failover => (),
+    }
+
+    available
+}
+
+pub enum Deployer {
+    Hyperstack(HyperstackDeployer),
+    Mock(MockDeployer),
+}
+
+impl Deployer {
+    pub async fn deploy(&self, input: DeployInput) -> Result<TokenizedServerDeployment, Error> {
+        match self {
+            Deployer::Hyperstack(deployer) => deployer
+                .deploy(input)
+                .await
+                .map(|output| TokenizedServerDeployment::Hyperstack { id: output.id }),
+            Deployer::Mock(deployer) => deployer
+                .deploy(input)
+                .await
+                .map(|output| TokenizedServerDeployment::Mock { id: output.id }),
+        }
+    }
+}
+
+fn get_hyperstack_deployer() -> HyperstackDeployer {
+    // Hardware is only used for new deployments
+    HyperstackDeployer::new(
+        hyperstackapikey(),
+        HyperstackHardware::VirtualMachine {
+            name: String::new(),
+            environment_name: String::new(),
+            flavor_name: String::new(),
+            key_name: String::new(),
+        },
+    )
+}
+
+pub async fn undeploy_deployment(deployment: &TokenizedServerDeployment) -> Option<Error> {
+    match deployment {
+        TokenizedServerDeployment::Hyperstack { id } => {
+            get_hyperstack_deployer()
+                .undeploy(HyperstackOutput { id: *id })
+                .await
+        }
+        TokenizedServerDeployment::Mock { id } => {
+            MockDeployer {}.undeploy(MockOutput { id: *id }).await
+        }
+    }
+}
+
+pub async fn deployment_ipv4(
+    deployment: &TokenizedServerDeployment,
+) -> Result<OptionalSupport<Option<Ipv4Addr>>, Error> {
+    match deployment {
+        TokenizedServerDeployment::Hyperstack { id } => {
+            get_hyperstack_deployer()
+                .ipv4(&HyperstackOutput { id: *id })
+                .await
+        }
+        TokenizedServerDeployment::Mock { id } => {
+            MockDeployer {}.ipv4(&MockOutput { id: *id }).await
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MockOutput {
+    pub id: u64,
+}
+
+/// Deployer that provisions nothing, for local development and testing.
+pub struct MockDeployer {}
+
+static MOCK_ID: AtomicU64 = AtomicU64::new(0);
+
+impl XnodeDeployer for MockDeployer {
+    type ProviderOutput = MockOutput;
+
+    async fn deploy(&self, input: DeployInput) -> Result<Self::ProviderOutput, Error> {
+        let id = get_time_u64() * 1000 + MOCK_ID.fetch_add(1, Ordering::Relaxed) % 1000;
+        log::info!("Mock deployment {id} of {input:?}");
+        Ok(MockOutput { id })
+    }
+
+    async fn undeploy(&self, xnode: Self::ProviderOutput) -> Option<Error> {
+        log::info!("Mock undeployment {id}", id = xnode.id);
+        None
+    }
+
+    async fn ipv4(
+        &self,
+        _xnode: &Self::ProviderOutput,
+    ) -> Result<OptionalSupport<Option<Ipv4Addr>>, Error> {
+        Ok(OptionalSupport::Supported(Some(Ipv4Addr::LOCALHOST)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mock_target(region: &str, flavor: &str, cost: i64, available: u64) -> DeploymentTarget {
+        DeploymentTarget {
+            provider: ProviderKind::Mock { available },
+            region: RegionConfig {
+                name: region.to_string(),
+                environment: region.to_lowercase(),
+            },
+            flavor: FlavorConfig {
+                name: flavor.to_string(),
+                model: flavor.to_string(),
+                configuration: default_configuration(),
+                cost,
+            },
+        }
+    }
+
+    #[test]
+    fn parse_providers() {
+        let providers: Vec<ProviderConfig> = serde_json::from_str(
+            r#"[{"provider":"Hyperstack","regions":[{"name":"NORWAY-1","environment":"default-NORWAY-1"}],"flavors":[{"name":"n3-RTX-A4000x1","model":"RTX-A4000","cost":0}]},{"provider":{"Mock":{"available":10}},"regions":[{"name":"LOCAL","environment":"local"}],"flavors":[{"name":"mock","model":"mock","configuration":"2x","cost":5}]}]"#,
+        )
+        .unwrap();
+        assert_eq!(providers[0].provider, ProviderKind::Hyperstack);
+        assert_eq!(providers[0].flavors[0].configuration, "1x");
+        assert_eq!(providers[1].provider, ProviderKind::Mock { available: 10 });
+        assert_eq!(providers[1].flavors[0].configuration, "2x");
+    }
+
+    #[test]
+    fn parse_policy() {
+        assert!(matches!(
+            DeploymentPolicy::parse("cheapest"),
+            Some(DeploymentPolicy::Cheapest)
+        ));
+        assert!(matches!(
+            DeploymentPolicy::parse("failover"),
+            Some(DeploymentPolicy::Failover)
+        ));
+        assert!(matches!(
+            DeploymentPolicy::parse("region:CANADA-1,NORWAY-1"),
+            Some(DeploymentPolicy::RegionPreference(regions)) if regions == ["CANADA-1", "NORWAY-1"]
+        ));
+        assert!(DeploymentPolicy::parse("random").is_none());
+        assert!(DeploymentPolicy::parse("zone:NORWAY-1").is_none());
+    }
+
+    #[tokio::test]
+    async fn select_skips_unavailable_targets() {
+        let stock = StockClient::new();
+        let targets = vec![
+            mock_target("A", "mock", 0, 0),
+            mock_target("B", "mock", 0, 3),
+            mock_target("C", "mock", 0, 1),
+        ];
+
+        let selected =
+            select_deployment_targets(&stock, targets.clone(), true, DeploymentPolicy::Failover)
+                .await;
+        let regions: Vec<&str> = selected.iter().map(|t| t.region.name.as_str()).collect();
+        assert_eq!(regions, ["B", "C"]);
+
+        let unchecked =
+            select_deployment_targets(&stock, targets, false, DeploymentPolicy::Failover).await;
+        assert_eq!(unchecked.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn mock_deployment_lifecycle() {
+        let target = mock_target("LOCAL", "mock", 0, 1);
+        assert_eq!(target.available(&StockClient::new()).await.unwrap(), 1);
+
+        let deployer = target.deployer("test".to_string());
+        let input = || DeployInput {
+            xnode_owner: None,
+            domain: None,
+            acme_email: None,
+            user_passwd: None,
+            encrypted: None,
+            initial_config: None,
+        };
+        let first = deployer.deploy(input()).await.unwrap();
+        let second = deployer.deploy(input()).await.unwrap();
+        let (
+            TokenizedServerDeployment::Mock { id: first_id },
+            TokenizedServerDeployment::Mock { id: second_id },
+        ) = (&first, &second)
+        else {
+            panic!("Mock target deployed {first:?} and {second:?}");
+        };
+        assert_ne!(first_id, second_id);
+
+        assert!(matches!(
+            deployment_ipv4(&first).await,
+            Ok(OptionalSupport::Supported(Some(Ipv4Addr::LOCALHOST)))
+        ));
+        assert!(undeploy_deployment(&first).await.is_none());
+    }
+}
@@ -0,0 +1,160 @@
+use alloy::{primitives::keccak256, providers::Provider, sol_types::SolStruct};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    database::{Database, used_signature::DatabaseUsedSignature},
+    utils::{
+        env::{legacysignaturesuntil, signaturewindow},
+        scheduler::JobResult,
+        signature_validator::{validate_hash_signature, validate_signature},
+        time::get_time_i64,
+        typed_data::signature_domain,
+    },
+};
+
+/// Allowed clock drift of signers running ahead.
+const MAX_CLOCK_SKEW: i64 = 60; // 1 minute in seconds
+
+/// Part of every signed request, so a signature is only accepted once and shortly after signing.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Freshness {
+    /// Unix timestamp of signing
+    pub timestamp: i64,
+    /// Random value chosen by the signer
+    pub nonce: String,
+}
+
+impl Freshness {
+    /// Message as it has to be signed.
+    pub fn message(&self, message: &str) -> String {
+        format!(
+            "{message}\nNonce: {nonce}\nTimestamp: {timestamp}",
+            nonce = self.nonce,
+            timestamp = self.timestamp
+        )
+    }
+
+    pub fn is_fresh(&self) -> bool {
+        let now = get_time_i64();
+        self.timestamp > now - signaturewindow() && self.timestamp <= now + MAX_CLOCK_SKEW
+    }
+}
+
+/// Marks the signed message as used, false when it was used before.
+pub async fn use_signature(
+    database: &Database,
+    signer: &str,
+    message: &str,
+    freshness: &Freshness,
+) -> bool {
+    let used_signature = DatabaseUsedSignature {
+        hash: keccak256(format!(
+            "{signer}\n{message}",
+            signer = signer.to_lowercase(),
+            message = freshness.message(message)
+        ))
+        .to_string(),
+        signer: signer.to_string(),
+        timestamp: freshness.timestamp,
+        used_at: get_time_i64(),
+    };
+    match used_signature.insert(database).await {
+        Ok(unused) => unused,
+        Err(e) => {
+            log::error!("COULD NOT INSERT USED SIGNATURE {used_signature:?}: {e}");
+            false
+        }
+    }
+}
+
+/// validate_signature over the message with freshness, consuming the signature.
+pub async fn validate_fresh_signature<P: Provider>(
+    provider: &P,
+    database: &Database,
+    account: &str,
+    message: &str,
+    signature: &str,
+    freshness: &Freshness,
+) -> bool {
+    freshness.is_fresh()
+        && validate_signature(provider, account, &freshness.message(message), signature).await
+        && use_signature(database, account, message, freshness).await
+}
+
+/// Whether plain text signatures are still accepted where typed data exists.
+pub fn legacy_signatures_accepted() -> bool {
+    legacysignaturesuntil().is_none_or(|until| get_time_i64() < until)
+}
+
+/// EIP-712 signature of the typed data, or during the migration window the legacy message, consuming the signature.
+pub async fn validate_fresh_typed_signature<P: Provider, T: SolStruct>(
+    provider: &P,
+    database: &Database,
+    account: &str,
+    data: &T,
+    legacy_message: Option<&str>,
+    signature: &str,
+    freshness: &Freshness,
+) -> bool {
+    if !freshness.is_fresh() {
+        return false;
+    }
+
+    let hash = data.eip712_signing_hash(&signature_domain());
+    if validate_hash_signature(provider, account, hash, signature).await {
+        return use_signature(database, account, &hash.to_string(), freshness).await;
+    }
+
+    match legacy_message {
+        Some(message) if legacy_signatures_accepted() => {
+            validate_signature(provider, account, &freshness.message(message), signature).await
+                && use_signature(database, account, message, freshness).await
+        }
+        _ => false,
+    }
+}
+
+/// Only signatures of the recent window need to be remembered.
+pub async fn prune_used_signatures(database: &Database) -> JobResult {
+    DatabaseUsedSignature::delete_before(database, get_time_i64() - signaturewindow())
+        .await
+        .map_err(|e| format!("COULD NOT DELETE OLD USED SIGNATURES: {e}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn freshness(age: i64) -> Freshness {
+        Freshness {
+            timestamp: get_time_i64() - age,
+            nonce: "nonce".to_string(),
+        }
+    }
+
+    #[test]
+    fn fresh_within_window() {
+        let window = signaturewindow();
+        assert!(freshness(0).is_fresh());
+        assert!(freshness(window - 10).is_fresh());
+        assert!(!freshness(window + 10).is_fresh());
+    }
+
+    #[test]
+    fn fresh_with_clock_skew() {
+        assert!(freshness(-(MAX_CLOCK_SKEW - 10)).is_fresh());
+        assert!(!freshness(-(MAX_CLOCK_SKEW + 10)).is_fresh());
+    }
+
+    #[test]
+    fn message_with_freshness() {
+        let freshness = Freshness {
+            timestamp: 1760000000,
+            nonce: "abc".to_string(),
+        };
+        assert_eq!(
+            freshness.message("Run job staking"),
+            "Run job staking\nNonce: abc\nTimestamp: 1760000000"
+        );
+    }
+}
@@ -0,0 +1,205 @@
+use std::collections::{BTreeMap, HashMap};
+
+use alloy::{primitives::Address, providers::DynProvider};
+
+use crate::{
+    database::{
+        Database, credits::DatabaseCredits, notification::DatabaseNotification,
+        reservation::DatabaseReservation,
+    },
+    utils::{
+        denylist::{DenylistScope, is_denied},
+        mint::{OwnAIV1TokenCounter, mint_v1},
+        preset::get_preset,
+        scheduler::JobResult,
+        stock::StockClient,
+        tier::get_tier,
+        time::get_time_i64,
+    },
+};
+
+/// Cancels the reservation of a payer denied spending credits, releasing its hold.
+async fn cancel_denied_reservation(database: &Database, reservation: &mut DatabaseReservation) {
+    let id = reservation.id;
+    // Cancellation and hold release are written together, a failed release keeps the reservation waiting
+    let mut transaction = match database.begin().await {
+        Ok(transaction) => transaction,
+        Err(e) => {
+            log::error!("COULD NOT START CANCELLATION OF RESERVATION {id}: {e}");
+            return;
+        }
+    };
+    match reservation.cancel_transaction(&mut transaction).await {
+        Ok(true) => (),
+        Ok(false) => {
+            return;
+        }
+        Err(e) => {
+            log::error!("COULD NOT CANCEL RESERVATION {id} OF DENIED PAYER: {e}");
+            return;
+        }
+    }
+
+    let credits = DatabaseCredits {
+        account: reservation.payer.clone(),
+        credits: reservation.hold,
+        description: format!("Release of hold for cancelled reservation {id}"),
+        date: get_time_i64(),
+    };
+    if let Err(e) = credits.insert_transaction(&mut transaction).await {
+        log::error!("COULD NOT INSERT CREDITS {credits:?}: {e}");
+        return;
+    }
+
+    if let Err(e) = transaction.commit().await {
+        log::error!("COULD NOT COMMIT CANCELLATION OF RESERVATION {id}: {e}");
+        return;
+    }
+
+    let notification = DatabaseNotification {
+        account: reservation.payer.clone(),
+        message: format!(
+            "Reservation {id} was cancelled: {payer} is not allowed to spend credits",
+            payer = reservation.payer
+        ),
+        date: get_time_i64(),
+    };
+    if let Err(e) = notification.insert(database).await {
+        log::error!("COULD NOT INSERT NOTIFICATION {notification:?}: {e}");
+    }
+}
+
+pub async fn fulfill_reservations(
+    database: &Database,
+    provider: &DynProvider,
+    stock: &StockClient,
+    counter: &OwnAIV1TokenCounter,
+) -> JobResult {
+    let reservations = DatabaseReservation::get_all_waiting(database)
+        .await
+        .map_err(|e| format!("COULD NOT GET WAITING RESERVATIONS: {e}"))?;
+
+    // Stock left per tier this run, the cached stock count does not drop with each mint
+    let mut remaining: HashMap<String, u64> = HashMap::new();
+    for mut reservation in reservations {
+        if remaining.get(&reservation.tier) == Some(&0) {
+            continue;
+        }
+
+        let tier = match get_tier(Some(&reservation.tier)) {
+            Some(tier) => tier,
+            None => {
+                log::error!(
+                    "RESERVATION {id} OF UNKNOWN TIER {tier}",
+                    id = reservation.id,
+                    tier = reservation.tier
+                );
+                continue;
+            }
+        };
+        if !remaining.contains_key(&reservation.tier) {
+            let available = tier.available(database, stock).await.unwrap_or_else(|e| {
+                log::warn!(
+                    "Could not check stock of {tier} for reservations: {e}",
+                    tier = reservation.tier
+                );
+                0
+            });
+            remaining.insert(reservation.tier.clone(), available);
+            if available == 0 {
+                continue;
+            }
+        }
+
+        match is_denied(database, &reservation.payer, DenylistScope::Credits).await {
+            Ok(false) => (),
+            Ok(true) => {
+                // Would otherwise keep waiting and hold back stock from everyone else
+                cancel_denied_reservation(database, &mut reservation).await;
+                continue;
+            }
+            Err(e) => {
+                log::error!(
+                    "Fetching credits denylist for {payer}: {e}",
+                    payer = reservation.payer
+                );
+                continue;
+            }
+        }
+
+        let to = match Address::parse_checksummed(&reservation.to_account, None) {
+            Ok(to) => to,
+            Err(e) => {
+                log::error!(
+                    "RESERVATION {id} TO INVALID ADDRESS {to}: {e}",
+                    id = reservation.id,
+                    to = reservation.to_account
+                );
+                continue;
+            }
+        };
+
+        // Reservations get the default app preset, owners can switch after minting
+        let Some(preset) = get_preset(None).and_then(|preset| {
+            preset
+                .resolve_parameters(&BTreeMap::new())
+                .ok()
+                .map(|preset_parameters| (preset, preset_parameters))
+        }) else {
+            log::error!(
+                "RESERVATION {id} HAS NO USABLE DEFAULT APP PRESET",
+                id = reservation.id
+            );
+            continue;
+        };
+
+        match reservation.fulfill(database).await {
+            Ok(true) => (),
+            Ok(false) => {
+                // Cancelled in the meantime
+                continue;
+            }
+            Err(e) => {
+                log::error!(
+                    "COULD NOT MARK RESERVATION {id} AS FULFILLED: {e}",
+                    id = reservation.id
+                );
+                continue;
+            }
+        }
+
+        log::info!(
+            "Fulfilling reservation {id} of {tier} to {to}",
+            id = reservation.id,
+            tier = reservation.tier
+        );
+        let token_id = match mint_v1(database, provider, stock, counter, to, tier, preset).await {
+            Ok(token_id) => token_id,
+            Err(e) => {
+                log::error!("MINT FOR RESERVATION {id} FAILED: {e}", id = reservation.id);
+                // Nothing was minted, the reservation keeps its hold and place in the queue
+                if let Err(e) = reservation.unfulfill(database).await {
+                    log::error!(
+                        "COULD NOT RETURN RESERVATION {id} TO THE QUEUE: {e}",
+                        id = reservation.id
+                    );
+                }
+                continue;
+            }
+        };
+        if let Some(available) = remaining.get_mut(&reservation.tier) {
+            *available = available.saturating_sub(1);
+        }
+        if let Err(e) = reservation
+            .update_token_id(database, token_id.to_string())
+            .await
+        {
+            log::error!(
+                "COULD NOT SET TOKEN ID {token_id} OF RESERVATION {id}: {e}",
+                id = reservation.id
+            );
+        }
+    }
+
+    Ok(())
+}
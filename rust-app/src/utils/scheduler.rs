@@ -0,0 +1,239 @@
+use std::{
+    collections::HashSet,
+    pin::Pin,
+    str::FromStr,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use chrono::{DateTime, Utc};
+use cron::Schedule;
+use tokio::time;
+
+use crate::{
+    database::{Database, job::DatabaseJob, job_run::DatabaseJobRun},
+    utils::time::get_time_i64,
+};
+
+/// Failed runs are retried with backoff until this many attempts, after which the next scheduled run is awaited.
+const MAX_ATTEMPTS: i32 = 3;
+const RETRY_BACKOFF: i64 = 30; // 30 seconds, doubled every attempt
+const RUN_RETENTION: i64 = 7 * 24 * 60 * 60; // 7 days in seconds
+/// Runs taking longer are aborted, a stuck run would otherwise hold the job lock forever.
+const JOB_TIMEOUT: u64 = 60 * 60; // 1 hour in seconds
+
+pub type JobResult = Result<(), String>;
+type JobFn = Arc<dyn Fn() -> Pin<Box<dyn Future<Output = JobResult> + Send>> + Send + Sync>;
+
+pub struct Job {
+    pub name: &'static str,
+    /// Cron expression with seconds: sec min hour day month weekday
+    pub schedule: &'static str,
+    run: JobFn,
+}
+
+impl Job {
+    pub fn new<F, Fut>(name: &'static str, schedule: &'static str, run: F) -> Self
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = JobResult> + Send + 'static,
+    {
+        Self {
+            name,
+            schedule,
+            run: Arc::new(move || Box::pin(run())),
+        }
+    }
+
+    fn next_run(&self, after: i64) -> i64 {
+        let after = DateTime::<Utc>::from_timestamp(after, 0).unwrap_or_default();
+        Schedule::from_str(self.schedule)
+            .unwrap_or_else(|e| {
+                panic!(
+                    "Invalid schedule {} of job {}: {e}",
+                    self.schedule, self.name
+                )
+            })
+            .after(&after)
+            .next()
+            .map(|next| next.timestamp())
+            .unwrap_or(i64::MAX)
+    }
+}
+
+/// Runs the job if it is still due, only on the instance holding its lock.
+async fn run_job(database: &Database, job: &Job) {
+    let lock = match database
+        .try_lock(&format!("job:{name}", name = job.name))
+        .await
+    {
+        Ok(Some(lock)) => lock,
+        Ok(None) => {
+            // Running on another instance
+            return;
+        }
+        Err(e) => {
+            log::error!("COULD NOT LOCK JOB {name}: {e}", name = job.name);
+            return;
+        }
+    };
+
+    // Another instance might have completed this run before the lock was acquired
+    let started = get_time_i64();
+    match DatabaseJob::get_by_name(database, job.name).await {
+        Ok(Some(mut state)) if state.next_run <= started => {
+            // Panics and hanging runs are reported as failures, so the lock is always released
+            let mut handle = tokio::spawn((job.run)());
+            let result = match time::timeout(Duration::from_secs(JOB_TIMEOUT), &mut handle).await {
+                Ok(joined) => joined.unwrap_or_else(|e| Err(format!("Job panicked: {e}"))),
+                Err(_) => {
+                    handle.abort();
+                    Err(format!("Job timed out after {JOB_TIMEOUT} seconds"))
+                }
+            };
+            let finished = get_time_i64();
+
+            let attempt = state.attempts + 1;
+            let (attempts, next_run) = match &result {
+                Ok(()) => (0, job.next_run(finished)),
+                Err(e) => {
+                    log::error!(
+                        "JOB {name} FAILED (ATTEMPT {attempt}/{MAX_ATTEMPTS}): {e}",
+                        name = job.name
+                    );
+                    if attempt < MAX_ATTEMPTS {
+                        (
+                            attempt,
+                            (finished + RETRY_BACKOFF * 2_i64.pow(attempt as u32 - 1))
+                                .min(job.next_run(finished)),
+                        )
+                    } else {
+                        (0, job.next_run(finished))
+                    }
+                }
+            };
+
+            let run = DatabaseJobRun {
+                id: 0,
+                name: job.name.to_string(),
+                started,
+                finished,
+                success: result.is_ok(),
+                error: result.err(),
+                attempt,
+                manual: state.triggered,
+            };
+            if let Err(e) = run.insert(database).await {
+                log::error!("COULD NOT INSERT JOB RUN {run:?}: {e}");
+            }
+            if let Err(e) = state
+                .update_run(database, started, run.success, attempts, next_run)
+                .await
+            {
+                log::error!("COULD NOT UPDATE JOB {state:?}: {e}");
+            }
+            if let Err(e) =
+                DatabaseJobRun::delete_before(database, job.name, finished - RUN_RETENTION).await
+            {
+                log::error!(
+                    "COULD NOT DELETE OLD RUNS OF JOB {name}: {e}",
+                    name = job.name
+                );
+            }
+        }
+        Ok(_) => (),
+        Err(e) => {
+            log::error!("Fetching job {name}: {e}", name = job.name);
+        }
+    }
+
+    if let Err(e) = lock.unlock().await {
+        log::error!("COULD NOT UNLOCK JOB {name}: {e}", name = job.name);
+    }
+}
+
+/// Runs every due job, persisting its next run so schedules survive restarts and span instances.
+pub async fn run_scheduler(database: Database, jobs: Vec<Job>) {
+    let now = get_time_i64();
+    for job in &jobs {
+        let state = DatabaseJob {
+            name: job.name.to_string(),
+            schedule: job.schedule.to_string(),
+            next_run: job.next_run(now),
+            last_run: None,
+            last_success: None,
+            attempts: 0,
+            triggered: false,
+        };
+        if let Err(e) = state.register(&database).await {
+            log::error!("COULD NOT REGISTER JOB {state:?}: {e}");
+        }
+    }
+
+    let jobs: Vec<Arc<Job>> = jobs.into_iter().map(Arc::new).collect();
+    let running: Arc<Mutex<HashSet<&'static str>>> = Arc::new(Mutex::new(HashSet::new()));
+    let mut interval = time::interval(Duration::from_secs(5)); // 5 seconds
+
+    loop {
+        interval.tick().await;
+        let due = match DatabaseJob::get_all_due(&database, get_time_i64()).await {
+            Ok(due) => due,
+            Err(e) => {
+                log::error!("COULD NOT GET DUE JOBS: {e}");
+                continue;
+            }
+        };
+
+        for state in due {
+            let Some(job) = jobs.iter().find(|job| job.name == state.name).cloned() else {
+                // Registered by an instance running a different version
+                continue;
+            };
+            if !running
+                .lock()
+                .expect("Scheduler running jobs poisoned")
+                .insert(job.name)
+            {
+                continue;
+            }
+
+            let database = database.clone();
+            let running = running.clone();
+            tokio::spawn(async move {
+                run_job(&database, &job).await;
+                running
+                    .lock()
+                    .expect("Scheduler running jobs poisoned")
+                    .remove(job.name);
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn job(schedule: &'static str) -> Job {
+        Job::new("test", schedule, || async { Ok(()) })
+    }
+
+    #[test]
+    fn next_run_after() {
+        let hourly = job("0 0 * * * *");
+        assert_eq!(hourly.next_run(0), 3600);
+        assert_eq!(hourly.next_run(1), 3600);
+        // Strictly after, a run at the scheduled time schedules the next one
+        assert_eq!(hourly.next_run(3600), 7200);
+
+        let daily = job("0 30 2 * * *");
+        assert_eq!(daily.next_run(0), 2 * 3600 + 30 * 60);
+        assert_eq!(daily.next_run(3 * 3600), 86400 + 2 * 3600 + 30 * 60);
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid schedule")]
+    fn next_run_invalid_schedule() {
+        job("every hour").next_run(0);
+    }
+}
@@ -1,7 +1,7 @@
 use std::str::FromStr;
 
 use alloy::{
-    primitives::{Address, Bytes, eip191_hash_message, fixed_bytes},
+    primitives::{Address, B256, Bytes, eip191_hash_message, fixed_bytes},
     providers::Provider,
     signers::Signature,
     sol,
@@ -14,11 +14,22 @@ sol! {
     }
 }
 
+/// EIP-191 personal message signature.
 pub async fn validate_signature<P: Provider>(
     provider: &P,
     account: &str,
     message: &str,
     signature: &str,
+) -> bool {
+    validate_hash_signature(provider, account, eip191_hash_message(message), signature).await
+}
+
+/// Signature over a prehashed message, such as an EIP-712 signing hash.
+pub async fn validate_hash_signature<P: Provider>(
+    provider: &P,
+    account: &str,
+    hash: B256,
+    signature: &str,
 ) -> bool {
     let signature = match Signature::from_str(signature) {
         Ok(signature) => signature,
@@ -37,18 +48,18 @@ pub async fn validate_signature<P: Provider>(
     match provider.get_code_at(account).await {
         Ok(code) => {
             if code.is_empty() {
-                validate_eoa_signature(account, message, signature)
+                validate_eoa_signature(account, hash, signature)
             } else {
-                validate_smart_contract_signature(provider, account, message, signature).await
+                validate_smart_contract_signature(provider, account, hash, signature).await
             }
         }
         Err(_e) => false,
     }
 }
 
-pub fn validate_eoa_signature(account: Address, message: &str, signature: Signature) -> bool {
+pub fn validate_eoa_signature(account: Address, hash: B256, signature: Signature) -> bool {
     signature
-        .recover_address_from_msg(message)
+        .recover_address_from_prehash(&hash)
         .is_ok_and(|signer| signer == account)
 }
 
@@ -56,14 +67,11 @@ pub fn validate_eoa_signature(account: Address, message: &str, signature: Signat
 pub async fn validate_smart_contract_signature<P: Provider>(
     provider: &P,
     account: Address,
-    message: &str,
+    hash: B256,
     signature: Signature,
 ) -> bool {
     IERC1271::new(account, provider)
-        .isValidSignature(
-            eip191_hash_message(message),
-            Bytes::from_iter(signature.as_bytes()),
-        )
+        .isValidSignature(hash, Bytes::from_iter(signature.as_bytes()))
         .call()
         .await
         .is_ok_and(|magic_value| magic_value == fixed_bytes!("1626ba7e"))
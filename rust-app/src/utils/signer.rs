@@ -0,0 +1,317 @@
+use std::sync::LazyLock;
+
+use alloy::{
+    dyn_abi::TypedData,
+    network::{EthereumWallet, TransactionBuilder},
+    primitives::{Address, Bytes, TxHash},
+    providers::{Provider, ProviderBuilder, RootProvider},
+    rpc::types::TransactionRequest,
+    signers::{Error, Signature, Signer, local::PrivateKeySigner},
+    sol_types::{Eip712Domain, SolStruct},
+};
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+
+use crate::utils::env::{
+    claimerkey, claimersigner, tokenminterkey, tokenmintersigner, tokenownerkey, tokenownersigner,
+};
+
+/// Where the key of a signer lives.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum SignerConfig {
+    /// Raw hex private key
+    Local { key: String },
+    /// Encrypted JSON keystore, decrypted with the password in password_file
+    Keystore { path: String, password_file: String },
+    /// Web3Signer compatible JSON-RPC endpoint holding the key of address, a local node such as anvil can stand in for it
+    Remote { url: String, address: Address },
+}
+
+impl SignerConfig {
+    /// Parses the JSON config of the signer, falling back to its raw private key.
+    fn from_env(name: &str, config: Option<String>, key: Option<String>) -> Self {
+        match config {
+            Some(config) => serde_json::from_str(&config)
+                .unwrap_or_else(|e| panic!("Invalid {name}SIGNER provided: {e}")),
+            None => SignerConfig::Local {
+                key: key.unwrap_or_else(|| panic!("No {name}SIGNER or {name}KEY provided.")),
+            },
+        }
+    }
+}
+
+pub struct RemoteSigner {
+    address: Address,
+    provider: RootProvider,
+    /// Held from nonce lookup until broadcast, concurrent sends would otherwise get the same pending nonce
+    send_lock: Mutex<()>,
+}
+
+/// Signs without exposing key material to the caller.
+pub enum AppSigner {
+    Local(PrivateKeySigner),
+    Remote(RemoteSigner),
+}
+
+impl AppSigner {
+    pub fn new(config: SignerConfig) -> Self {
+        match config {
+            SignerConfig::Local { key } => AppSigner::Local(
+                key.parse()
+                    .unwrap_or_else(|e| panic!("Could not parse private key: {e}")),
+            ),
+            SignerConfig::Keystore {
+                path,
+                password_file,
+            } => {
+                let password = std::fs::read_to_string(&password_file).unwrap_or_else(|e| {
+                    panic!("Could not read keystore password file {password_file}: {e}")
+                });
+                AppSigner::Local(
+                    PrivateKeySigner::decrypt_keystore(&path, password.trim_end())
+                        .unwrap_or_else(|e| panic!("Could not decrypt keystore {path}: {e}")),
+                )
+            }
+            SignerConfig::Remote { url, address } => AppSigner::Remote(RemoteSigner {
+                address,
+                provider: RootProvider::new_http(
+                    url.parse()
+                        .unwrap_or_else(|e| panic!("Invalid remote signer url {url}: {e}")),
+                ),
+                send_lock: Mutex::new(()),
+            }),
+        }
+    }
+
+    pub fn address(&self) -> Address {
+        match self {
+            AppSigner::Local(signer) => signer.address(),
+            AppSigner::Remote(signer) => signer.address,
+        }
+    }
+
+    /// EIP-191 personal message signature.
+    pub async fn sign_message(&self, message: &[u8]) -> Result<Signature, Error> {
+        match self {
+            AppSigner::Local(signer) => signer.sign_message(message).await,
+            AppSigner::Remote(signer) => {
+                signer
+                    .request(
+                        "eth_sign",
+                        (signer.address, Bytes::copy_from_slice(message)),
+                    )
+                    .await
+            }
+        }
+    }
+
+    /// EIP-712 typed data signature.
+    pub async fn sign_typed_data<T: SolStruct + Serialize>(
+        &self,
+        data: &T,
+        domain: &Eip712Domain,
+    ) -> Result<Signature, Error> {
+        match self {
+            AppSigner::Local(signer) => signer.sign_hash(&data.eip712_signing_hash(domain)).await,
+            AppSigner::Remote(signer) => {
+                let typed_data = TypedData::from_struct(data, Some(domain.clone()));
+                signer
+                    .request("eth_signTypedData", (signer.address, typed_data))
+                    .await
+            }
+        }
+    }
+
+    /// Signs the transaction from this signer and broadcasts it through the provider.
+    pub async fn send_transaction<P: Provider>(
+        &self,
+        provider: P,
+        transaction: TransactionRequest,
+    ) -> Result<TxHash, Error> {
+        let transaction = transaction.with_from(self.address());
+        match self {
+            AppSigner::Local(signer) => ProviderBuilder::new()
+                .wallet(EthereumWallet::from(signer.clone()))
+                .connect_provider(provider)
+                .send_transaction(transaction)
+                .await
+                .map(|pending| *pending.tx_hash())
+                .map_err(Error::other),
+            AppSigner::Remote(signer) => {
+                // Filled here, so the remote signer only needs to sign
+                let _send = signer.send_lock.lock().await;
+                let nonce = provider
+                    .get_transaction_count(signer.address)
+                    .pending()
+                    .await
+                    .map_err(Error::other)?;
+                let chain_id = provider.get_chain_id().await.map_err(Error::other)?;
+                let gas = provider
+                    .estimate_gas(transaction.clone())
+                    .await
+                    .map_err(Error::other)?;
+                let fees = provider
+                    .estimate_eip1559_fees()
+                    .await
+                    .map_err(Error::other)?;
+                let transaction = transaction
+                    .with_nonce(nonce)
+                    .with_chain_id(chain_id)
+                    .with_gas_limit(gas)
+                    .with_max_fee_per_gas(fees.max_fee_per_gas)
+                    .with_max_priority_fee_per_gas(fees.max_priority_fee_per_gas);
+
+                let raw: Bytes = signer
+                    .provider
+                    .raw_request("eth_signTransaction".into(), (transaction,))
+                    .await
+                    .map_err(Error::other)?;
+                provider
+                    .send_raw_transaction(&raw)
+                    .await
+                    .map(|pending| *pending.tx_hash())
+                    .map_err(Error::other)
+            }
+        }
+    }
+}
+
+impl RemoteSigner {
+    async fn request<P>(&self, method: &'static str, params: P) -> Result<Signature, Error>
+    where
+        P: Serialize + Clone + Send + Sync + std::fmt::Debug + Unpin + 'static,
+    {
+        let signature: Bytes = self
+            .provider
+            .raw_request(method.into(), params)
+            .await
+            .map_err(Error::other)?;
+        Signature::try_from(signature.as_ref()).map_err(Error::other)
+    }
+}
+
+static CLAIMER: LazyLock<AppSigner> = LazyLock::new(|| {
+    AppSigner::new(SignerConfig::from_env(
+        "CLAIMER",
+        claimersigner(),
+        claimerkey(),
+    ))
+});
+static TOKEN_OWNER: LazyLock<AppSigner> = LazyLock::new(|| {
+    AppSigner::new(SignerConfig::from_env(
+        "TOKENOWNER",
+        tokenownersigner(),
+        tokenownerkey(),
+    ))
+});
+static TOKEN_MINTER: LazyLock<AppSigner> = LazyLock::new(|| {
+    AppSigner::new(SignerConfig::from_env(
+        "TOKENMINTER",
+        tokenmintersigner(),
+        tokenminterkey(),
+    ))
+});
+
+/// Loaded on first use, keystores are only decrypted once.
+pub fn claimer_signer() -> &'static AppSigner {
+    &CLAIMER
+}
+
+pub fn token_owner_signer() -> &'static AppSigner {
+    &TOKEN_OWNER
+}
+
+pub fn token_minter_signer() -> &'static AppSigner {
+    &TOKEN_MINTER
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{App, HttpResponse, HttpServer, web};
+    use alloy::{hex, sol, sol_types::eip712_domain};
+    use serde_json::{Value, json};
+
+    use super::*;
+
+    const KEY: &str = "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80";
+
+    sol! {
+        #[derive(serde::Serialize)]
+        struct Action {
+            string name;
+            int64 timestamp;
+        }
+    }
+
+    /// Answers the signing calls of a Web3Signer with a local key.
+    async fn stand_in(key: web::Data<PrivateKeySigner>, request: web::Json<Value>) -> HttpResponse {
+        let params = &request["params"];
+        let signature = match request["method"].as_str() {
+            Some("eth_sign") => {
+                let message = hex::decode(params[1].as_str().unwrap_or_default()).unwrap();
+                key.sign_message(&message).await.unwrap()
+            }
+            Some("eth_signTypedData") => {
+                let typed_data: TypedData = serde_json::from_value(params[1].clone()).unwrap();
+                key.sign_hash(&typed_data.eip712_signing_hash().unwrap())
+                    .await
+                    .unwrap()
+            }
+            _ => return HttpResponse::BadRequest().finish(),
+        };
+        HttpResponse::Ok().json(json!({
+            "jsonrpc": "2.0",
+            "id": request["id"],
+            "result": Bytes::from(signature.as_bytes()),
+        }))
+    }
+
+    async fn remote_signer() -> AppSigner {
+        let key: PrivateKeySigner = KEY.parse().unwrap();
+        let address = key.address();
+        let server = HttpServer::new(move || {
+            App::new()
+                .app_data(web::Data::new(key.clone()))
+                .default_service(web::post().to(stand_in))
+        })
+        .bind(("127.0.0.1", 0))
+        .unwrap();
+        let url = format!("http://{}", server.addrs()[0]);
+        tokio::spawn(server.run());
+
+        AppSigner::new(SignerConfig::Remote { url, address })
+    }
+
+    #[tokio::test]
+    async fn remote_sign_message() {
+        let signer = remote_signer().await;
+        let message = b"Xnode Auth authenticate manager.1.base.ownaiv1.openxai.network at 0";
+        let signature = signer.sign_message(message).await.unwrap();
+        assert_eq!(
+            signature.recover_address_from_msg(message).unwrap(),
+            signer.address()
+        );
+    }
+
+    #[tokio::test]
+    async fn remote_sign_typed_data() {
+        let signer = remote_signer().await;
+        let domain = eip712_domain! {
+            name: "OpenxAI",
+            version: "1",
+            chain_id: 8453,
+        };
+        let data = Action {
+            name: "test".to_string(),
+            timestamp: 1,
+        };
+        let signature = signer.sign_typed_data(&data, &domain).await.unwrap();
+        assert_eq!(
+            signature
+                .recover_address_from_prehash(&data.eip712_signing_hash(&domain))
+                .unwrap(),
+            signer.address()
+        );
+    }
+}
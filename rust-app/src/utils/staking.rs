@@ -1,63 +1,463 @@
-use std::time::Duration;
+use std::{
+    cmp::Reverse,
+    collections::{BTreeMap, BTreeSet},
+    ops::Range,
+};
 
-use chrono::{NaiveTime, Utc};
-use tokio::time::{self, Instant};
+use chrono::{DateTime, NaiveTime, Utc};
+use serde::{Deserialize, Serialize};
 
 use crate::{
     database::{
-        Database, claim::DatabaseClaim, nft_staking::DatabaseNFTStaking,
+        Database, claim::DatabaseClaim, health_sample::DatabaseHealthSample,
+        nft_staking::DatabaseNFTStaking, staking_run::DatabaseStakingRun,
         tokenized_server::DatabaseTokenizedServer,
     },
-    utils::time::get_time_i64,
+    utils::{env::stakingrates, scheduler::JobResult, tier::get_tier, time::get_time_i64},
 };
 
-pub async fn distribute_staking_rewards(database: Database) {
-    let utc_now = Utc::now();
-    let utc_midnight = (utc_now + chrono::Duration::days(1))
-        .with_time(NaiveTime::from_hms_opt(0, 0, 0).expect("Invalid staking time"))
-        .unwrap();
-    let until_utc_midnight = utc_midnight
-        .signed_duration_since(utc_now)
-        .to_std()
-        .expect("Unable to convert until_utc_midnight into std duration");
-
-    let mut interval = time::interval_at(
-        Instant::now() + until_utc_midnight,
-        Duration::from_secs(24 * 60 * 60),
-    );
-
-    loop {
-        interval.tick().await;
-        log::info!("Distributing staking rewards");
-        let servers = match DatabaseTokenizedServer::get_all_not_expired(&database).await {
-            Ok(servers) => servers,
-            Err(e) => {
-                log::error!("COULD NOT GET STAKING REWARD ELIGIBLE SERVERS: {e}");
-                continue;
+const DAY: i64 = 24 * 60 * 60; // 1 day in seconds
+const WEIGHT_PRECISION: f64 = 1_000_000.0; // Weights are split in millionths
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct HoldingMultiplier {
+    /// Minimum days the current owner has held the server
+    pub days: i64,
+    pub multiplier: f64,
+}
+
+/// Reward rate of a collection, active from its effective date until a later version takes over.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct StakingRate {
+    pub version: String,
+    pub collection: String,
+    /// Unix timestamp from which this rate applies
+    pub effective: i64,
+    /// Tokens (6 decimals) distributed per day over all active servers
+    pub emission: i64,
+    /// Multiplier per tier id, tiers not listed count as 1
+    #[serde(default)]
+    pub tiers: BTreeMap<String, f64>,
+    /// The highest multiplier reached applies
+    #[serde(default)]
+    pub holding: Vec<HoldingMultiplier>,
+    /// Whether rewards are scaled by the uptime over the last day
+    #[serde(default)]
+    pub uptime: bool,
+}
+
+impl StakingRate {
+    fn tier_multiplier(&self, tier: &str) -> f64 {
+        self.tiers.get(tier).copied().unwrap_or(1.0)
+    }
+
+    fn holding_multiplier(&self, held_days: i64) -> f64 {
+        self.holding
+            .iter()
+            .filter(|holding| held_days >= holding.days)
+            .max_by_key(|holding| holding.days)
+            .map(|holding| holding.multiplier)
+            .unwrap_or(1.0)
+    }
+}
+
+fn all_staking_rates() -> Vec<StakingRate> {
+    serde_json::from_str(&stakingrates())
+        .unwrap_or_else(|e| panic!("Invalid STAKINGRATES provided: {e}"))
+}
+
+/// Latest version per collection that is effective at the given time.
+fn rates_in_effect(all_rates: Vec<StakingRate>, date: i64) -> Vec<StakingRate> {
+    let mut rates: BTreeMap<String, StakingRate> = BTreeMap::new();
+    for rate in all_rates.into_iter().filter(|rate| rate.effective <= date) {
+        if rates
+            .get(&rate.collection)
+            .is_none_or(|current| current.effective < rate.effective)
+        {
+            rates.insert(rate.collection.clone(), rate);
+        }
+    }
+    rates.into_values().collect()
+}
+
+/// Rate versions in effect at the given time, one per collection.
+pub fn get_staking_rates(date: i64) -> Vec<StakingRate> {
+    rates_in_effect(all_staking_rates(), date)
+}
+
+/// Health samples counting towards the uptime of the distribution at the given date: the day before it.
+fn uptime_window(date: i64) -> Range<i64> {
+    date - DAY..date
+}
+
+/// Splits the emission by weight in integer arithmetic, the rounding remainder goes to the largest weight.
+fn split_emission(emission: i64, weights: &[f64]) -> Vec<i64> {
+    let weights: Vec<u128> = weights
+        .iter()
+        .map(|weight| (weight.max(0.0) * WEIGHT_PRECISION).round() as u128)
+        .collect();
+    let total: u128 = weights.iter().sum();
+    let mut amounts = vec![0; weights.len()];
+    if total == 0 || emission <= 0 {
+        return amounts;
+    }
+
+    for (amount, weight) in amounts.iter_mut().zip(&weights) {
+        *amount = (emission as u128 * weight / total) as i64;
+    }
+    // First of the largest weights on a tie
+    if let Some((largest, _)) = weights
+        .iter()
+        .enumerate()
+        .max_by_key(|(index, weight)| (**weight, Reverse(*index)))
+    {
+        amounts[largest] += emission - amounts.iter().sum::<i64>();
+    }
+    amounts
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct StakingReward {
+    pub account: String,
+    pub collection: String,
+    pub chain: String,
+    pub token_id: String,
+    pub tier: String,
+    pub held_days: i64,
+    /// Fraction of healthy samples over the last day, None without samples
+    pub uptime: Option<f64>,
+    pub weight: f64,
+    pub amount: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct StakingDistribution {
+    pub collection: String,
+    pub version: String,
+    pub date: i64,
+    pub emission: i64,
+    pub rewards: Vec<StakingReward>,
+}
+
+/// Splits the emission of the rate over the active (not expired and deployed) servers of its collection by weight.
+/// No history of servers is kept, so past dates are calculated with their current owner, expiry and deployment.
+async fn calculate_staking_distribution(
+    database: &Database,
+    servers: &[DatabaseTokenizedServer],
+    rate: StakingRate,
+    date: i64,
+) -> Result<StakingDistribution, sqlx::Error> {
+    let mut rewards = vec![];
+    for server in servers.iter().filter(|server| {
+        server.collection == rate.collection && server.expires > date && server.deployment.is_some()
+    }) {
+        let tier = match get_tier(server.tier.as_deref()) {
+            Some(tier) => tier.id,
+            None => server.tier.clone().unwrap_or_default(),
+        };
+        let held_days = (date - server.owner_since).max(0) / DAY;
+        let uptime = if rate.uptime {
+            let window = uptime_window(date);
+            let uptime = DatabaseHealthSample::get_uptime_by_collection_token_id(
+                database,
+                &server.collection,
+                &server.chain,
+                &server.token_id,
+                window.start,
+                window.end,
+            )
+            .await?;
+            if uptime.total == 0 {
+                None
+            } else {
+                Some(uptime.healthy as f64 / uptime.total as f64)
             }
+        } else {
+            None
+        };
+        // Servers without health samples are not penalized
+        let weight = rate.tier_multiplier(&tier)
+            * rate.holding_multiplier(held_days)
+            * uptime.unwrap_or(1.0);
+        rewards.push(StakingReward {
+            account: server.owner.clone(),
+            collection: server.collection.clone(),
+            chain: server.chain.clone(),
+            token_id: server.token_id.clone(),
+            tier,
+            held_days,
+            uptime,
+            weight,
+            amount: 0,
+        });
+    }
+
+    let weights: Vec<f64> = rewards.iter().map(|reward| reward.weight).collect();
+    for (reward, amount) in rewards
+        .iter_mut()
+        .zip(split_emission(rate.emission, &weights))
+    {
+        reward.amount = amount;
+    }
+
+    Ok(StakingDistribution {
+        collection: rate.collection,
+        version: rate.version,
+        date,
+        emission: rate.emission,
+        rewards,
+    })
+}
+
+/// Distribution of every collection with a rate in effect at the given time.
+pub async fn calculate_staking_rewards(
+    database: &Database,
+    date: i64,
+) -> Result<Vec<StakingDistribution>, sqlx::Error> {
+    let servers = DatabaseTokenizedServer::get_all(database).await?;
+    let mut distributions = vec![];
+    for rate in get_staking_rates(date) {
+        distributions.push(calculate_staking_distribution(database, &servers, rate, date).await?);
+    }
+    Ok(distributions)
+}
+
+/// Next UTC midnight, when staking rewards are distributed.
+pub fn next_staking_distribution() -> DateTime<Utc> {
+    (Utc::now() + chrono::Duration::days(1))
+        .with_time(NaiveTime::from_hms_opt(0, 0, 0).expect("Invalid staking time"))
+        .unwrap()
+}
+
+/// Writes the run together with all its rewards and claims, or nothing when the day was already distributed.
+async fn commit_staking_distribution(
+    database: &Database,
+    day: i64,
+    distribution: StakingDistribution,
+) -> Result<bool, sqlx::Error> {
+    let mut transaction = database.begin().await?;
+
+    let run = DatabaseStakingRun {
+        collection: distribution.collection.clone(),
+        day,
+        version: distribution.version.clone(),
+        emission: distribution.emission,
+        servers: distribution.rewards.len() as i32,
+        date: get_time_i64(),
+    };
+    if !run.insert(&mut transaction).await? {
+        transaction.rollback().await?;
+        return Ok(false);
+    }
+
+    for reward in distribution.rewards {
+        if reward.amount == 0 {
+            continue;
+        }
+
+        let staking_reward = DatabaseNFTStaking {
+            account: reward.account,
+            amount: reward.amount,
+            collection: reward.collection,
+            chain: reward.chain,
+            token_id: reward.token_id,
+            date: distribution.date,
+            day: Some(day),
         };
-        for server in servers {
-            let staking_reward = DatabaseNFTStaking {
-                account: server.owner.clone(),
-                amount: calculate_staking_reward(&server),
-                collection: server.collection.clone(),
-                chain: server.chain.clone(),
-                token_id: server.token_id.clone(),
-                date: get_time_i64(),
+        staking_reward.insert(&mut transaction).await?;
+
+        let claim: DatabaseClaim = (&staking_reward).into();
+        claim.insert_transaction(&mut transaction).await?;
+    }
+
+    transaction.commit().await?;
+    Ok(true)
+}
+
+/// Distributes every day since the last run of each collection up to today.
+/// Collections without any run start today.
+/// Missed days pay the current owners of servers active now, transfers and expiries since then are not known.
+pub async fn distribute_staking_rewards(database: &Database) -> JobResult {
+    let today = get_time_i64() / DAY;
+    let collections: BTreeSet<String> = all_staking_rates()
+        .into_iter()
+        .map(|rate| rate.collection)
+        .collect();
+
+    // Failing collections do not hold back the others, later days are retried after the failed one succeeds
+    let mut errors = vec![];
+    for collection in collections {
+        let first_day =
+            match DatabaseStakingRun::get_last_day_by_collection(database, &collection).await {
+                Ok(last_day) => last_day.map(|day| day + 1).unwrap_or(today),
+                Err(e) => {
+                    errors.push(format!(
+                        "COULD NOT GET LAST STAKING RUN OF {collection}: {e}"
+                    ));
+                    continue;
+                }
             };
 
-            if let Err(e) = staking_reward.insert(&database).await {
-                log::error!("COULD NOT INSERT STAKING REWARD {staking_reward:?}: {e}");
-            }
+        for day in first_day..=today {
+            let date = day * DAY;
+            let Some(rate) = get_staking_rates(date)
+                .into_iter()
+                .find(|rate| rate.collection == collection)
+            else {
+                // No rate in effect yet on this day
+                continue;
+            };
+            let servers = DatabaseTokenizedServer::get_all(database)
+                .await
+                .map_err(|e| format!("COULD NOT GET STAKING REWARD ELIGIBLE SERVERS: {e}"))?;
+            let distribution =
+                match calculate_staking_distribution(database, &servers, rate, date).await {
+                    Ok(distribution) => distribution,
+                    Err(e) => {
+                        errors.push(format!(
+                            "COULD NOT CALCULATE STAKING REWARDS OF {collection} FOR DAY {day}: {e}"
+                        ));
+                        break;
+                    }
+                };
 
-            let claim: DatabaseClaim = (&staking_reward).into();
-            if let Err(e) = claim.insert(&database).await {
-                log::error!("COULD NOT INSERT CLAIM {claim:?} INTO DATABASE: {e}");
+            log::info!(
+                "Distributing {emission} staking rewards of {collection} for day {day} (rate {version}) over {servers} servers",
+                emission = distribution.emission,
+                version = distribution.version,
+                servers = distribution.rewards.len()
+            );
+            match commit_staking_distribution(database, day, distribution).await {
+                Ok(true) => (),
+                Ok(false) => {
+                    log::info!("Staking rewards of {collection} for day {day} already distributed");
+                }
+                Err(e) => {
+                    errors.push(format!(
+                        "COULD NOT COMMIT STAKING REWARDS OF {collection} FOR DAY {day}: {e}"
+                    ));
+                    break;
+                }
             }
         }
     }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors.join("; "))
+    }
 }
 
-fn calculate_staking_reward(_server: &DatabaseTokenizedServer) -> i64 {
-    0
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rate(version: &str, collection: &str, effective: i64) -> StakingRate {
+        StakingRate {
+            version: version.to_string(),
+            collection: collection.to_string(),
+            effective,
+            emission: 1_000_000,
+            tiers: BTreeMap::from([("rtx-a6000".to_string(), 2.0)]),
+            holding: vec![
+                HoldingMultiplier {
+                    days: 30,
+                    multiplier: 1.1,
+                },
+                HoldingMultiplier {
+                    days: 90,
+                    multiplier: 1.25,
+                },
+            ],
+            uptime: false,
+        }
+    }
+
+    #[test]
+    fn parse_rates() {
+        let rates: Vec<StakingRate> = serde_json::from_str(
+            r#"[{"version":"2026-11","collection":"ownaiv1","effective":1761955200,"emission":1000000000,"tiers":{"rtx-a6000":2.0},"holding":[{"days":30,"multiplier":1.1}],"uptime":true},{"version":"2026-12","collection":"ownaiv1","effective":1764547200,"emission":500000000}]"#,
+        )
+        .unwrap();
+        assert!(rates[0].uptime);
+        assert!(rates[1].tiers.is_empty() && rates[1].holding.is_empty() && !rates[1].uptime);
+    }
+
+    #[test]
+    fn multipliers() {
+        let rate = rate("1", "ownaiv1", 0);
+        assert_eq!(rate.tier_multiplier("rtx-a6000"), 2.0);
+        assert_eq!(rate.tier_multiplier("rtx-a4000"), 1.0);
+        assert_eq!(rate.holding_multiplier(0), 1.0);
+        assert_eq!(rate.holding_multiplier(29), 1.0);
+        assert_eq!(rate.holding_multiplier(30), 1.1);
+        assert_eq!(rate.holding_multiplier(89), 1.1);
+        assert_eq!(rate.holding_multiplier(365), 1.25);
+    }
+
+    #[test]
+    fn latest_effective_rate_per_collection() {
+        let rates = vec![
+            rate("1", "ownaiv1", 100),
+            rate("2", "ownaiv1", 200),
+            rate("3", "ownaiv1", 300),
+            rate("a", "other", 250),
+        ];
+        let versions = |date| {
+            rates_in_effect(rates.clone(), date)
+                .into_iter()
+                .map(|rate| rate.version)
+                .collect::<Vec<String>>()
+        };
+        assert!(versions(99).is_empty());
+        assert_eq!(versions(100), ["1"]);
+        assert_eq!(versions(250), ["a", "2"]);
+        assert_eq!(versions(1000), ["a", "3"]);
+    }
+
+    #[test]
+    fn uptime_of_the_day_before() {
+        let date = 20_000 * DAY;
+        let window = uptime_window(date);
+        assert!(window.contains(&(date - DAY)));
+        assert!(window.contains(&(date - 1)));
+        assert!(!window.contains(&(date - DAY - 1)));
+        // Samples after the day, such as when catching up, do not change its weight
+        assert!(!window.contains(&date));
+        assert!(!window.contains(&(date + DAY)));
+        assert!(!window.contains(&get_time_i64()));
+    }
+
+    #[test]
+    fn split_proportional_to_weight() {
+        assert_eq!(split_emission(1_000, &[1.0, 3.0]), [250, 750]);
+        assert_eq!(split_emission(1_000, &[2.0, 1.1, 0.9]), [500, 275, 225]);
+    }
+
+    #[test]
+    fn split_remainder_to_largest_weight() {
+        assert_eq!(split_emission(100, &[1.0, 1.0, 1.0]), [34, 33, 33]);
+        assert_eq!(split_emission(100, &[1.0, 2.0, 1.0]), [25, 50, 25]);
+        assert_eq!(split_emission(10, &[1.0, 1.1, 1.0]), [3, 4, 3]);
+        assert_eq!(split_emission(1, &[0.5, 0.7]), [0, 1]);
+    }
+
+    #[test]
+    fn split_distributes_exact_emission() {
+        let weights = [2.2, 1.0, 0.3333, 1.25, 0.97, 2.5];
+        for emission in [1, 7, 999_999, 1_000_000_000, i64::MAX / 4] {
+            assert_eq!(
+                split_emission(emission, &weights).iter().sum::<i64>(),
+                emission
+            );
+        }
+    }
+
+    #[test]
+    fn split_without_weight() {
+        assert!(split_emission(1_000, &[]).is_empty());
+        assert_eq!(split_emission(1_000, &[0.0, 0.0]), [0, 0]);
+        assert_eq!(split_emission(0, &[1.0]), [0]);
+    }
 }
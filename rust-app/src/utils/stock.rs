@@ -0,0 +1,113 @@
+use std::{collections::HashMap, sync::Arc, time::Duration};
+
+use serde::{Deserialize, Serialize};
+use tokio::{
+    sync::RwLock,
+    time::{self, Instant},
+};
+
+use crate::utils::{
+    env::{hyperstackapikey, stockcachettl},
+    http::HttpError,
+    provider::{ProviderKind, get_providers},
+};
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct HyperstackModelStock {
+    pub model: String,
+    pub configurations: HashMap<String, Option<u64>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct HyperstackRegionStock {
+    pub region: String,
+    pub models: Vec<HyperstackModelStock>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct HyperstackStocks {
+    pub stocks: Vec<HyperstackRegionStock>,
+}
+
+impl HyperstackStocks {
+    pub fn available(&self, region: &str, model: &str, configuration: &str) -> u64 {
+        self.stocks
+            .iter()
+            .find(|stock| stock.region == region)
+            .and_then(|stock| stock.models.iter().find(|stock| stock.model == model))
+            .and_then(|stock| stock.configurations.get(configuration).copied().flatten())
+            .unwrap_or(0)
+    }
+}
+
+struct CachedStocks {
+    stocks: Result<HyperstackStocks, HttpError>,
+    fetched_at: Instant,
+}
+
+#[derive(Clone)]
+pub struct StockClient {
+    client: reqwest::Client,
+    cache: Arc<RwLock<Option<CachedStocks>>>,
+}
+
+impl StockClient {
+    pub fn new() -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            cache: Arc::new(RwLock::new(None)),
+        }
+    }
+
+    /// Hyperstack stocks, fetched from upstream when the cached stocks are older than the ttl.
+    pub async fn hyperstack(&self) -> Result<HyperstackStocks, HttpError> {
+        if let Some(cached) = self.cache.read().await.as_ref()
+            && cached.fetched_at.elapsed() < Duration::from_secs(stockcachettl())
+        {
+            return cached.stocks.clone();
+        }
+
+        self.refresh().await
+    }
+
+    pub async fn refresh(&self) -> Result<HyperstackStocks, HttpError> {
+        let stocks = self.fetch().await;
+        if let Err(e) = &stocks {
+            log::warn!("Fetching hyperstack stocks failed: {e:?}");
+        }
+        *self.cache.write().await = Some(CachedStocks {
+            stocks: stocks.clone(),
+            fetched_at: Instant::now(),
+        });
+        stocks
+    }
+
+    async fn fetch(&self) -> Result<HyperstackStocks, HttpError> {
+        self.client
+            .get("https://infrahub-api.nexgencloud.com/v1/core/stocks")
+            .header("api_key", hyperstackapikey())
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| HttpError::Request(e.to_string()))?
+            .json::<HyperstackStocks>()
+            .await
+            .map_err(|e| HttpError::Response(e.to_string()))
+    }
+}
+
+pub async fn refresh_stocks(stock: StockClient) {
+    if !get_providers()
+        .iter()
+        .any(|provider| provider.provider == ProviderKind::Hyperstack)
+    {
+        return;
+    }
+
+    let mut interval = time::interval(Duration::from_secs(stockcachettl()));
+
+    loop {
+        interval.tick().await;
+        let _ = stock.refresh().await;
+    }
+}
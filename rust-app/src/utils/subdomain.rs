@@ -0,0 +1,290 @@
+use std::{fmt::Display, time::Duration};
+
+use serde_json::json;
+use tokio::time;
+use xnode_deployer::OptionalSupport;
+
+use crate::{
+    database::{
+        Database,
+        subdomain::{DatabaseSubdomain, SubdomainStatus},
+        tokenized_server::{DatabaseTokenizedServer, TokenizedServerDeployment},
+    },
+    utils::{
+        env::subdomaindistributor, provider::deployment_ipv4, scheduler::JobResult,
+        time::get_time_i64,
+    },
+};
+
+const ATTEMPTS: u32 = 3;
+const IPV4_TIMEOUT: u64 = 10 * 60; // 10 minutes in seconds
+
+#[derive(Debug)]
+pub enum SubdomainError {
+    Request(String),
+    NoIpv4,
+}
+
+impl Display for SubdomainError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SubdomainError::Request(e) => write!(f, "request failed: {e}"),
+            SubdomainError::NoIpv4 => f.write_str("no ipv4 address"),
+        }
+    }
+}
+
+pub struct SubdomainClient {
+    client: reqwest::Client,
+    base_url: String,
+}
+
+impl SubdomainClient {
+    pub fn new() -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            base_url: subdomaindistributor(),
+        }
+    }
+
+    pub async fn reserve(&self, subdomain: &str, ipv4: &str) -> Result<(), SubdomainError> {
+        self.post(
+            format!("{base_url}/{subdomain}/reserve", base_url = self.base_url),
+            json!({
+                "user": "",
+                "ipv4": ipv4
+            }),
+        )
+        .await
+    }
+
+    pub async fn release(&self, subdomain: &str) -> Result<(), SubdomainError> {
+        self.post(
+            format!("{base_url}/{subdomain}/release", base_url = self.base_url),
+            json!({
+                "user": ""
+            }),
+        )
+        .await
+    }
+
+    /// Posts to the subdomain distributor, retrying with exponential backoff.
+    async fn post(&self, url: String, body: serde_json::Value) -> Result<(), SubdomainError> {
+        let mut attempt = 1;
+        loop {
+            match self
+                .client
+                .post(&url)
+                .json(&body)
+                .send()
+                .await
+                .and_then(|response| response.error_for_status())
+            {
+                Ok(_) => return Ok(()),
+                Err(e) => {
+                    if attempt >= ATTEMPTS {
+                        return Err(SubdomainError::Request(e.to_string()));
+                    }
+                    log::warn!("Subdomain distributor request {url} attempt {attempt} failed: {e}");
+                    time::sleep(Duration::from_secs(2u64.pow(attempt - 1))).await;
+                    attempt += 1;
+                }
+            }
+        }
+    }
+}
+
+pub fn server_subdomain(server: &DatabaseTokenizedServer) -> String {
+    format!(
+        "{token_id}.{chain}.{collection}",
+        token_id = server.token_id,
+        chain = server.chain,
+        collection = server.collection,
+    )
+}
+
+pub fn server_domain(server: &DatabaseTokenizedServer) -> String {
+    format!(
+        "{subdomain}.openxai.network",
+        subdomain = server_subdomain(server)
+    )
+}
+
+async fn save_subdomain_state(
+    database: &Database,
+    server: &DatabaseTokenizedServer,
+    ipv4: Option<String>,
+    status: SubdomainStatus,
+    error: Option<SubdomainError>,
+) {
+    let state = DatabaseSubdomain {
+        collection: server.collection.clone(),
+        chain: server.chain.clone(),
+        token_id: server.token_id.clone(),
+        subdomain: server_subdomain(server),
+        ipv4,
+        status: status.to_string(),
+        error: error.map(|e| e.to_string()),
+        updated_at: get_time_i64(),
+    };
+    if let Err(e) = state.upsert(database).await {
+        log::error!("DATABASE UPDATE OF SUBDOMAIN {state:?} FAILED: {e}");
+    }
+}
+
+/// Points the server subdomain to ipv4, skipped when it already does.
+pub async fn reserve_server_subdomain(
+    database: &Database,
+    server: &DatabaseTokenizedServer,
+    ipv4: String,
+) {
+    let subdomain = server_subdomain(server);
+    match DatabaseSubdomain::get_by_collection_token_id(
+        database,
+        &server.collection,
+        &server.chain,
+        &server.token_id,
+    )
+    .await
+    {
+        Ok(Some(state))
+            if state.status == SubdomainStatus::Reserved.to_string()
+                && state.ipv4.as_ref() == Some(&ipv4) =>
+        {
+            return;
+        }
+        Ok(_) => (),
+        Err(e) => {
+            log::error!("Fetching subdomain state of {subdomain}: {e}");
+        }
+    }
+
+    match SubdomainClient::new().reserve(&subdomain, &ipv4).await {
+        Ok(()) => {
+            log::info!("Subdomain {subdomain} reserved for {ipv4}");
+            save_subdomain_state(
+                database,
+                server,
+                Some(ipv4),
+                SubdomainStatus::Reserved,
+                None,
+            )
+            .await;
+        }
+        Err(e) => {
+            log::error!("SUBDOMAIN RESERVATION FOR {subdomain} -> {ipv4} FAILED: {e:?}");
+            save_subdomain_state(
+                database,
+                server,
+                Some(ipv4),
+                SubdomainStatus::ReserveFailed,
+                Some(e),
+            )
+            .await;
+        }
+    }
+}
+
+/// Waits for the IPv4 address of a new deployment to point the server subdomain to it.
+/// Gives up after IPV4_TIMEOUT, leaving the reservation to retry_failed_subdomains.
+pub async fn reserve_deployment_subdomain(
+    database: &Database,
+    server: &DatabaseTokenizedServer,
+    deployment: &TokenizedServerDeployment,
+) {
+    let wait = async {
+        let mut interval = time::interval(Duration::from_secs(1)); // 1 second
+        loop {
+            interval.tick().await;
+            if let Ok(OptionalSupport::Supported(Some(ip))) = deployment_ipv4(deployment).await {
+                return ip;
+            }
+        }
+    };
+    match time::timeout(Duration::from_secs(IPV4_TIMEOUT), wait).await {
+        Ok(ip) => reserve_server_subdomain(database, server, ip.to_string()).await,
+        Err(_) => {
+            let subdomain = server_subdomain(server);
+            log::error!(
+                "SUBDOMAIN RESERVATION FOR {subdomain} FAILED: NO IPV4 ADDRESS AFTER {IPV4_TIMEOUT} SECONDS"
+            );
+            save_subdomain_state(
+                database,
+                server,
+                None,
+                SubdomainStatus::ReserveFailed,
+                Some(SubdomainError::NoIpv4),
+            )
+            .await;
+        }
+    }
+}
+
+pub async fn release_server_subdomain(database: &Database, server: &DatabaseTokenizedServer) {
+    let subdomain = server_subdomain(server);
+    match SubdomainClient::new().release(&subdomain).await {
+        Ok(()) => {
+            log::info!("Subdomain {subdomain} released");
+            save_subdomain_state(database, server, None, SubdomainStatus::Released, None).await;
+        }
+        Err(e) => {
+            log::error!("SUBDOMAIN RELEASE OF {subdomain} FAILED: {e:?}");
+            save_subdomain_state(
+                database,
+                server,
+                None,
+                SubdomainStatus::ReleaseFailed,
+                Some(e),
+            )
+            .await;
+        }
+    }
+}
+
+pub async fn retry_failed_subdomains(database: &Database) -> JobResult {
+    let failed = DatabaseSubdomain::get_all_failed(database)
+        .await
+        .map_err(|e| format!("COULD NOT GET FAILED SUBDOMAINS: {e}"))?;
+    for state in failed {
+        let server = match DatabaseTokenizedServer::get_by_collection_token_id(
+            database,
+            &state.collection,
+            &state.chain,
+            &state.token_id,
+        )
+        .await
+        {
+            Ok(Some(server)) => server,
+            Ok(None) => continue,
+            Err(e) => {
+                log::error!(
+                    "Fetching tokenized server of subdomain {subdomain}: {e}",
+                    subdomain = state.subdomain
+                );
+                continue;
+            }
+        };
+
+        // Retry whatever matches the current deployment state
+        match (server.deployment.is_some(), state.ipv4) {
+            (true, Some(ipv4)) if state.status == SubdomainStatus::ReserveFailed.to_string() => {
+                reserve_server_subdomain(database, &server, ipv4).await;
+            }
+            (true, None) if state.status == SubdomainStatus::ReserveFailed.to_string() => {
+                // The deployment had no address yet when it was created
+                if let Some(deployment) = &server.deployment
+                    && let Ok(OptionalSupport::Supported(Some(ip))) =
+                        deployment_ipv4(deployment).await
+                {
+                    reserve_server_subdomain(database, &server, ip.to_string()).await;
+                }
+            }
+            (false, _) => {
+                release_server_subdomain(database, &server).await;
+            }
+            _ => (),
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,212 @@
+use std::fmt::Display;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    database::{Database, tokenized_server::DatabaseTokenizedServer},
+    utils::{
+        env::{ownaiv1price, ownaiv1tiers},
+        http::HttpError,
+        provider::{
+            DeploymentPolicy, DeploymentTarget, get_deployment_policy, get_deployment_targets,
+        },
+        stock::StockClient,
+    },
+};
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub enum TierStock {
+    Provider,
+    /// Total number of servers of the tier, each not expired server takes one
+    Fixed {
+        available: u64,
+    },
+}
+
+#[derive(Debug)]
+pub enum AvailabilityError {
+    Stock(HttpError),
+    Database(sqlx::Error),
+}
+
+impl Display for AvailabilityError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AvailabilityError::Stock(e) => write!(f, "stock {e}"),
+            AvailabilityError::Database(e) => write!(f, "database {e}"),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Tier {
+    pub id: String,
+    pub name: String,
+    pub flavor: String,
+    pub regions: Vec<String>,
+    pub price: i64,
+    pub stock: TierStock,
+}
+
+impl Tier {
+    /// Deployment targets offering this tier's hardware, in region preference order.
+    pub fn deployment_targets(&self) -> Vec<DeploymentTarget> {
+        self.filter_targets(get_deployment_targets())
+    }
+
+    fn filter_targets(&self, targets: Vec<DeploymentTarget>) -> Vec<DeploymentTarget> {
+        let mut targets: Vec<DeploymentTarget> = targets
+            .into_iter()
+            .filter(|target| target.flavor.name == self.flavor)
+            .filter(|target| self.regions.is_empty() || self.regions.contains(&target.region.name))
+            .collect();
+        targets.sort_by_key(|target| {
+            self.regions
+                .iter()
+                .position(|region| region == &target.region.name)
+                .unwrap_or(self.regions.len())
+        });
+        targets
+    }
+
+    /// Regions of the tier take precedence over the global policy.
+    pub fn deployment_policy(&self) -> DeploymentPolicy {
+        if self.regions.is_empty() {
+            get_deployment_policy()
+        } else {
+            // Deployment targets are already in the tier's region order
+            DeploymentPolicy::Failover
+        }
+    }
+
+    /// Errors only when the stock of none of the deployment targets could be checked.
+    pub async fn available(
+        &self,
+        database: &Database,
+        stock: &StockClient,
+    ) -> Result<u64, AvailabilityError> {
+        match &self.stock {
+            TierStock::Provider => {
+                let mut available = 0;
+                let mut error = None;
+                for target in self.deployment_targets() {
+                    match target.available(stock).await {
+                        Ok(target_available) => available += target_available,
+                        Err(e) => error = Some(e),
+                    }
+                }
+                match error {
+                    Some(e) if available == 0 => Err(AvailabilityError::Stock(e)),
+                    _ => Ok(available),
+                }
+            }
+            TierStock::Fixed { available } => {
+                let default = get_tiers().first().is_some_and(|tier| tier.id == self.id);
+                let taken = DatabaseTokenizedServer::get_not_expired_count_by_tier(
+                    database, &self.id, default,
+                )
+                .await
+                .map_err(AvailabilityError::Database)?;
+                Ok(available.saturating_sub(taken.unsigned_abs()))
+            }
+        }
+    }
+}
+
+pub fn get_tiers() -> Vec<Tier> {
+    match ownaiv1tiers() {
+        Some(tiers) => serde_json::from_str(&tiers)
+            .unwrap_or_else(|e| panic!("Invalid OWNAIV1TIERS provided: {e}")),
+        None => vec![Tier {
+            id: "rtx-a4000".to_string(),
+            name: "RTX A4000".to_string(),
+            flavor: "n3-RTX-A4000x1".to_string(),
+            regions: vec![],
+            price: ownaiv1price(),
+            stock: TierStock::Provider,
+        }],
+    }
+}
+
+/// Tier by id, servers without a stored tier use the first (default) tier.
+pub fn get_tier(id: Option<&str>) -> Option<Tier> {
+    let tiers = get_tiers();
+    match id {
+        Some(id) => tiers.into_iter().find(|tier| tier.id == id),
+        None => tiers.into_iter().next(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::provider::{FlavorConfig, ProviderKind, RegionConfig};
+
+    fn target(region: &str, flavor: &str) -> DeploymentTarget {
+        DeploymentTarget {
+            provider: ProviderKind::Mock { available: 1 },
+            region: RegionConfig {
+                name: region.to_string(),
+                environment: region.to_lowercase(),
+            },
+            flavor: FlavorConfig {
+                name: flavor.to_string(),
+                model: flavor.to_string(),
+                configuration: "1x".to_string(),
+                cost: 0,
+            },
+        }
+    }
+
+    fn tier(regions: &[&str]) -> Tier {
+        Tier {
+            id: "rtx-a6000".to_string(),
+            name: "RTX A6000".to_string(),
+            flavor: "n3-RTX-A6000x1".to_string(),
+            regions: regions.iter().map(|region| region.to_string()).collect(),
+            price: 100,
+            stock: TierStock::Provider,
+        }
+    }
+
+    #[test]
+    fn parse_tiers() {
+        let tiers: Vec<Tier> = serde_json::from_str(
+            r#"[{"id":"rtx-a4000","name":"RTX A4000","flavor":"n3-RTX-A4000x1","regions":[],"price":50,"stock":"Provider"},{"id":"rtx-a6000","name":"RTX A6000","flavor":"n3-RTX-A6000x1","regions":["NORWAY-1","CANADA-1"],"price":100,"stock":{"Fixed":{"available":5}}}]"#,
+        )
+        .unwrap();
+        assert!(matches!(tiers[0].stock, TierStock::Provider));
+        assert_eq!(tiers[1].regions, ["NORWAY-1", "CANADA-1"]);
+        assert!(matches!(tiers[1].stock, TierStock::Fixed { available: 5 }));
+        assert!(serde_json::from_str::<Vec<Tier>>(r#"[{"id":"rtx-a4000"}]"#).is_err());
+    }
+
+    #[test]
+    fn targets_in_tier_region_order() {
+        let targets = vec![
+            target("US-1", "n3-RTX-A6000x1"),
+            target("CANADA-1", "n3-RTX-A6000x1"),
+            target("NORWAY-1", "n3-RTX-A4000x1"),
+            target("NORWAY-1", "n3-RTX-A6000x1"),
+        ];
+        let regions = |tier: Tier| {
+            tier.filter_targets(targets.clone())
+                .into_iter()
+                .map(|target| target.region.name)
+                .collect::<Vec<String>>()
+        };
+        assert_eq!(
+            regions(tier(&["NORWAY-1", "CANADA-1"])),
+            ["NORWAY-1", "CANADA-1"]
+        );
+        assert_eq!(regions(tier(&[])), ["US-1", "CANADA-1", "NORWAY-1"]);
+    }
+
+    #[test]
+    fn tier_regions_override_policy() {
+        assert!(matches!(
+            tier(&["NORWAY-1"]).deployment_policy(),
+            DeploymentPolicy::Failover
+        ));
+    }
+}
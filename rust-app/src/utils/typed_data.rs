@@ -0,0 +1,78 @@
+use alloy::{
+    sol,
+    sol_types::{Eip712Domain, eip712_domain},
+};
+
+use crate::utils::env::{chainid, signaturedomain};
+
+// EIP-712 counterparts of the signed user action messages, nonce and timestamp come from the request freshness
+sol! {
+    struct MintServer {
+        string collection;
+        string chain;
+        string tier;
+        string to;
+        string preset;
+        string presetParameters;
+        string nonce;
+        int64 timestamp;
+    }
+
+    struct ExtendExpiry {
+        string collection;
+        string chain;
+        string tokenId;
+        int64 months;
+        string nonce;
+        int64 timestamp;
+    }
+
+    struct AuthorizeAutoRenew {
+        string collection;
+        string chain;
+        string tokenId;
+        int64 maxMonths;
+        string payer;
+        string nonce;
+        int64 timestamp;
+    }
+
+    struct UpdateController {
+        string collection;
+        string chain;
+        string tokenId;
+        string controller;
+        string nonce;
+        int64 timestamp;
+    }
+
+    struct SignAgreement {
+        bytes32 agreement;
+        int64 signedAt;
+        string nonce;
+        int64 timestamp;
+    }
+
+    struct RedeemPromoCode {
+        string code;
+        string account;
+        string nonce;
+        int64 timestamp;
+    }
+
+    struct DeployApp {
+        string app;
+        string version;
+        string nonce;
+        int64 timestamp;
+    }
+}
+
+/// Binds user signatures to this service on the configured chain.
+pub fn signature_domain() -> Eip712Domain {
+    eip712_domain! {
+        name: signaturedomain(),
+        version: "1",
+        chain_id: chainid(),
+    }
+}
@@ -1,20 +1,22 @@
 use alloy::{
     primitives::{Address, Uint},
-    providers::{Provider, ProviderBuilder},
-    signers::{Signature, Signer, local::PrivateKeySigner},
+    providers::Provider,
+    signers::Signature,
     sol_types::{SolStruct, eip712_domain},
 };
+use serde::Serialize;
 
 use crate::{
-    blockchain::{claimer::Claim, ownai_v1::OpenxAITokenizedServerV1},
-    utils::env::{chainid, claimer, claimerkey, ownaiv1, tokenminterkey, tokenownerkey},
+    blockchain::ownai_v1::OpenxAITokenizedServerV1,
+    utils::{
+        env::{chainid, claimer, ownaiv1},
+        signer::{AppSigner, claimer_signer, token_minter_signer, token_owner_signer},
+    },
 };
 
-pub async fn get_claimer_signature(claim: &Claim) -> Result<Signature, alloy::signers::Error> {
-    let signer: PrivateKeySigner = claimerkey()
-        .parse()
-        .unwrap_or_else(|e| panic!("Could not parse claimerkey: {e}"));
-
+pub async fn get_claimer_signature<C: SolStruct + Serialize>(
+    claim: &C,
+) -> Result<Signature, alloy::signers::Error> {
     let domain = eip712_domain! {
         name: "OpenxAIClaiming",
         version: "1",
@@ -22,31 +24,22 @@ pub async fn get_claimer_signature(claim: &Claim) -> Result<Signature, alloy::si
         verifying_contract: claimer(),
     };
 
-    // Derive the EIP-712 signing hash.
-    let hash = claim.eip712_signing_hash(&domain);
-
-    // Sign the hash asynchronously with the wallet.
-    let signature = signer.sign_hash(&hash).await?;
-
-    Ok(signature)
+    claimer_signer().sign_typed_data(claim, &domain).await
 }
 
-pub fn get_tokenized_server_owner() -> PrivateKeySigner {
-    tokenownerkey()
-        .parse()
-        .unwrap_or_else(|e| panic!("Could not parse tokenownerkey: {e}"))
+pub fn get_tokenized_server_owner() -> &'static AppSigner {
+    token_owner_signer()
 }
 
 pub async fn mint_tokenized_server<P: Provider>(provider: P, to: Address, token_id: i64) {
-    let signer: PrivateKeySigner = tokenminterkey()
-        .parse()
-        .unwrap_or_else(|e| panic!("Could not parse tokenminterkey: {e}"));
-    let provider = ProviderBuilder::new()
-        .wallet(signer)
-        .connect_provider(provider);
-
-    let ownaiv1 = OpenxAITokenizedServerV1::new(ownaiv1(), provider);
-    if let Err(e) = ownaiv1.mint(to, Uint::from(token_id)).send().await {
+    let ownaiv1 = OpenxAITokenizedServerV1::new(ownaiv1(), &provider);
+    let transaction = ownaiv1
+        .mint(to, Uint::from(token_id))
+        .into_transaction_request();
+    if let Err(e) = token_minter_signer()
+        .send_transaction(&provider, transaction)
+        .await
+    {
         log::error!("MINT TRANSACTION OF TOKEN {token_id} TO {to} FAILED: {e}");
     }
 }
@@ -1,23 +1,23 @@
-use std::time::Duration;
-
 use alloy::primitives::Address;
-use serde_json::json;
 use sqlx::types::Json;
-use tokio::time;
-use xnode_controller::XnodeController;
-use xnode_deployer::{
-    DeployInput, OptionalSupport, XnodeDeployer,
-    hyperstack::{HyperstackDeployer, HyperstackHardware, HyperstackOutput},
-};
+use xnode_deployer::DeployInput;
 
 use crate::{
-    database::{
-        Database,
-        tokenized_server::{DatabaseTokenizedServer, TokenizedServerDeployment},
-    },
+    database::{Database, tokenized_server::DatabaseTokenizedServer},
     utils::{
-        controller::{ControlledXnode, get_controller_config},
-        env::{hyperstackapikey, subdomaindistributor},
+        controller::{
+            ControlledXnode, ControllerRole, ServerController, get_controller_config,
+            server_controllers,
+        },
+        custom_domain::{CUSTOM_DOMAINS_BLOCK, apply_custom_domains, server_custom_domains_config},
+        preset::server_preset,
+        provider::{select_deployment_targets, undeploy_deployment},
+        scheduler::JobResult,
+        stock::StockClient,
+        subdomain::{
+            release_server_subdomain, reserve_deployment_subdomain, server_domain, server_subdomain,
+        },
+        tier::{TierStock, get_tier},
         wallet::get_tokenized_server_owner,
     },
 };
@@ -30,27 +30,22 @@ pub fn str_to_xnode_user(address: &str) -> String {
     address.replace("0x", "eth:").to_ascii_lowercase()
 }
 
-pub fn get_v1_deployer(name: String) -> HyperstackDeployer {
-    HyperstackDeployer::new(
-        hyperstackapikey(),
-        HyperstackHardware::VirtualMachine {
-            name,
-            environment_name: "default-NORWAY-1".to_string(),
-            flavor_name: "n3-RTX-A4000x1".to_string(),
-            key_name: "NixOS".to_string(),
-        },
-    )
-}
-
-pub fn get_deploy_input(domain: String, xnode_owner: String, controller: String) -> DeployInput {
+pub fn get_deploy_input(
+    domain: String,
+    xnode_owner: String,
+    controllers: &[ServerController],
+    app_config: String,
+    custom_domains_config: String,
+) -> DeployInput {
     let base_url = format!("https://manager.{domain}");
-    let controller_config = get_controller_config(base_url.clone(), controller);
+    let controller_config = get_controller_config(base_url.clone(), controllers);
     DeployInput {
         acme_email: Some("sam@openxai.org".to_string()),
         domain: Some(format!("manager.{domain}")),
         encrypted: None,
-        initial_config: Some(format!(
-            "\
+        initial_config: Some(
+            format!(
+                "\
 # START XNODE CONTROLLER {base_url}
 {controller_config}
 # END XNODE CONTROLLER {base_url}
@@ -59,107 +54,116 @@ nixpkgs.config.allowUnfree = true;
 hardware.graphics = {{ enable = true; extraPackages = [ pkgs.nvidia-vaapi-driver ]; }};
 hardware.nvidia.open = true;
 services.xserver.videoDrivers = [ \"nvidia\" ];
-services.xnode-reverse-proxy.rules.\"{domain}\" = [ {{ forward = \"http://xnode-ai-chat:8080\"; }} ];\
+# START XNODE APP PRESET {base_url}
+{app_config}
+# END XNODE APP PRESET {base_url}
+# START {CUSTOM_DOMAINS_BLOCK} {base_url}
+{custom_domains_config}
+# END {CUSTOM_DOMAINS_BLOCK} {base_url}\
 "
-        ).replace("\"", "\\\"").replace("\n", "\\n").replace("\\", "\\\\\\")),
+            )
+            .replace("\"", "\\\"")
+            .replace("\n", "\\n")
+            .replace("\\", "\\\\\\"),
+        ),
         user_passwd: None,
         xnode_owner: Some(xnode_owner),
     }
 }
 
-pub async fn available_v1() -> u64 {
-    let client = reqwest::Client::new();
-    let target_region = "NORWAY-1".to_string();
-    let target_model = "RTX-A4000".to_string();
-
-    let response = match client
-        .get("https://infrahub-api.nexgencloud.com/v1/core/stocks")
-        .header("api_key", hyperstackapikey())
-        .send()
-        .await
-        .and_then(|response| response.error_for_status())
-    {
-        Ok(response) => match response.json::<serde_json::Value>().await {
-            Ok(response) => response,
-            Err(_e) => {
-                return 0;
-            }
-        },
-        Err(_e) => {
-            return 0;
+pub async fn deploy_v1(
+    database: &Database,
+    stock: &StockClient,
+    server: &mut DatabaseTokenizedServer,
+) {
+    let subdomain = server_subdomain(server);
+    let domain = server_domain(server);
+    let tier = match get_tier(server.tier.as_deref()) {
+        Some(tier) => tier,
+        None => {
+            log::error!(
+                "DEPLOYMENT OF {collection}@{chain}@{token_id} FAILED: UNKNOWN TIER {tier:?}",
+                collection = server.collection,
+                chain = server.chain,
+                token_id = server.token_id,
+                tier = server.tier
+            );
+            return;
         }
     };
-
-    if let serde_json::Value::Object(map) = &response {
-        if let Some(serde_json::Value::Array(stocks)) = map.get("stocks") {
-            if let Some(serde_json::Value::Object(stock)) = stocks.iter().find(|stock| {
-                if let serde_json::Value::Object(stock) = stock {
-                    stock.get("region").is_some_and(|region| {
-                        if let serde_json::Value::String(region) = region {
-                            region == &target_region
-                        } else {
-                            false
-                        }
-                    })
-                } else {
-                    false
-                }
-            }) {
-                if let Some(serde_json::Value::Array(models)) = stock.get("models") {
-                    if let Some(serde_json::Value::Object(model)) = models.iter().find(|model| {
-                        if let serde_json::Value::Object(model) = model {
-                            model.get("model").is_some_and(|model| {
-                                if let serde_json::Value::String(model) = model {
-                                    model == &target_model
-                                } else {
-                                    false
-                                }
-                            })
-                        } else {
-                            false
-                        }
-                    }) {
-                        if let Some(serde_json::Value::Object(configurations)) =
-                            model.get("configurations")
-                        {
-                            if let Some(serde_json::Value::Number(available)) =
-                                configurations.get("1x")
-                            {
-                                if let Some(available) = available.as_u64() {
-                                    return available;
-                                }
-                            }
-                        }
-                    }
-                }
-            }
+    let app_config = match server_preset(server)
+        .ok_or(None)
+        .and_then(|(preset, parameters)| preset.config(&domain, &parameters).map_err(Some))
+    {
+        Ok(app_config) => app_config,
+        Err(e) => {
+            log::error!(
+                "DEPLOYMENT OF {collection}@{chain}@{token_id} FAILED: INVALID APP PRESET {preset:?}: {e:?}",
+                collection = server.collection,
+                chain = server.chain,
+                token_id = server.token_id,
+                preset = server.preset
+            );
+            return;
         }
     };
-
-    0
-}
-
-pub async fn deploy_v1(database: &Database, server: &mut DatabaseTokenizedServer) {
-    let subdomain = format!(
-        "{token_id}.{chain}.{collection}",
-        token_id = server.token_id,
-        chain = server.chain,
-        collection = server.collection,
-    );
-    let domain = format!("{subdomain}.openxai.network");
-    let deployer = get_v1_deployer(subdomain.replace(".", "-"));
-    let deployment = match deployer
-        .deploy(get_deploy_input(
-            domain.clone(),
-            address_to_xnode_user(get_tokenized_server_owner().address()),
-            server.controller.clone(),
-        ))
+    let controllers = server_controllers(database, server)
         .await
+        .unwrap_or_else(|e| {
+            log::error!(
+                "Fetching controllers of {collection}@{chain}@{token_id}, deploying with main controller only: {e}",
+                collection = server.collection,
+                chain = server.chain,
+                token_id = server.token_id
+            );
+            vec![ServerController {
+                controller: server.controller.clone(),
+                role: ControllerRole::Admin,
+            }]
+        });
+    // Custom domains only live in the OS config, so a redeployment has to bring them along
+    let custom_domains_config = server_custom_domains_config(database, server)
+        .await
+        .unwrap_or_default();
+    let mut deployment = None;
+    for target in select_deployment_targets(
+        stock,
+        tier.deployment_targets(),
+        matches!(tier.stock, TierStock::Provider),
+        tier.deployment_policy(),
+    )
+    .await
     {
-        Ok(deployment) => deployment,
-        Err(e) => {
+        let deployer = target.deployer(subdomain.replace(".", "-"));
+        match deployer
+            .deploy(get_deploy_input(
+                domain.clone(),
+                address_to_xnode_user(get_tokenized_server_owner().address()),
+                &controllers,
+                app_config.clone(),
+                custom_domains_config.clone(),
+            ))
+            .await
+        {
+            Ok(target_deployment) => {
+                deployment = Some(target_deployment);
+                break;
+            }
+            Err(e) => {
+                log::warn!(
+                    "Deployment of {collection}@{chain}@{token_id} on {target:?} failed: {e:?}",
+                    collection = server.collection,
+                    chain = server.chain,
+                    token_id = server.token_id
+                );
+            }
+        }
+    }
+    let deployment = match deployment {
+        Some(deployment) => deployment,
+        None => {
             log::error!(
-                "DEPLOYMENT OF {collection}@{chain}@{token_id} FAILED: {e:?}",
+                "DEPLOYMENT OF {collection}@{chain}@{token_id} FAILED: NO AVAILABLE PROVIDER",
                 collection = server.collection,
                 chain = server.chain,
                 token_id = server.token_id
@@ -167,48 +171,16 @@ pub async fn deploy_v1(database: &Database, server: &mut DatabaseTokenizedServer
             return;
         }
     };
-    if let Err(e) = server
-        .deploy(
-            database,
-            Json(TokenizedServerDeployment::Hyperstack { id: deployment.id }),
-        )
-        .await
-    {
+    if let Err(e) = server.deploy(database, Json(deployment.clone())).await {
         log::error!(
-            "DATABASE UPDATE OF DEPLOYMENT {id} FOR {collection}@{chain}@{token_id} FAILED: {e}",
-            id = deployment.id,
+            "DATABASE UPDATE OF DEPLOYMENT {deployment:?} FOR {collection}@{chain}@{token_id} FAILED: {e}",
             collection = server.collection,
             chain = server.chain,
             token_id = server.token_id
         );
     };
 
-    let mut interval = time::interval(Duration::from_secs(1)); // 1 second
-    loop {
-        interval.tick().await;
-        if let Ok(OptionalSupport::Supported(Some(ip))) = deployer.ipv4(&deployment).await {
-            let ipv4 = ip.to_string();
-            let client = reqwest::Client::new();
-            if let Err(e) = client
-                .post(format!(
-                    "{subdomaindistributor}/{subdomain}/reserve",
-                    subdomaindistributor = subdomaindistributor()
-                ))
-                .json(&json!({
-                    "user": "",
-                    "ipv4": ipv4
-                }))
-                .send()
-                .await
-                .and_then(|response| response.error_for_status())
-            {
-                log::error!("SUBDOMAIN RESERVATION FOR {subdomain} -> {ipv4} FAILED: {e}");
-            } else {
-                log::info!("Subdomain {subdomain} reserved for {ipv4}");
-            }
-            break;
-        }
-    }
+    reserve_deployment_subdomain(database, server, &deployment).await;
 }
 
 pub async fn undeploy(database: &Database, server: &mut DatabaseTokenizedServer) {
@@ -225,25 +197,15 @@ pub async fn undeploy(database: &Database, server: &mut DatabaseTokenizedServer)
         }
     };
 
-    match deployment {
-        TokenizedServerDeployment::Hyperstack { id } => {
-            let deployer = get_v1_deployer(format!(
-                "{collection}-{chain}-{token_id}",
-                collection = server.collection,
-                chain = server.chain,
-                token_id = server.token_id
-            ));
-            if let Some(e) = deployer.undeploy(HyperstackOutput { id }).await {
-                log::error!(
-                    "UNDEPLOYMENT OF {collection}@{chain}@{token_id} FAILED: {e:?}",
-                    collection = server.collection,
-                    chain = server.chain,
-                    token_id = server.token_id
-                );
-                return;
-            };
-        }
-    }
+    if let Some(e) = undeploy_deployment(&deployment).await {
+        log::error!(
+            "UNDEPLOYMENT OF {collection}@{chain}@{token_id} FAILED: {e:?}",
+            collection = server.collection,
+            chain = server.chain,
+            token_id = server.token_id
+        );
+        return;
+    };
 
     if let Err(e) = server.undeploy(database).await {
         log::error!(
@@ -253,25 +215,19 @@ pub async fn undeploy(database: &Database, server: &mut DatabaseTokenizedServer)
             token_id = server.token_id
         );
     };
-}
 
-pub async fn undeploy_expired_servers(database: Database) {
-    let mut interval = time::interval(Duration::from_secs(60)); // 1 minute
+    release_server_subdomain(database, server).await;
+}
 
-    loop {
-        interval.tick().await;
-        let expired_servers =
-            match DatabaseTokenizedServer::get_all_deployed_expired(&database).await {
-                Ok(expired_servers) => expired_servers,
-                Err(e) => {
-                    log::error!("COULD NOT GET EXPIRED SERVERS: {e}");
-                    continue;
-                }
-            };
-        for mut server in expired_servers {
-            undeploy(&database, &mut server).await;
-        }
+pub async fn undeploy_expired_servers(database: &Database) -> JobResult {
+    let expired_servers = DatabaseTokenizedServer::get_all_deployed_expired(database)
+        .await
+        .map_err(|e| format!("COULD NOT GET EXPIRED SERVERS: {e}"))?;
+    for mut server in expired_servers {
+        undeploy(database, &mut server).await;
     }
+
+    Ok(())
 }
 
 pub async fn update_controller(
@@ -279,6 +235,20 @@ pub async fn update_controller(
     server: &mut DatabaseTokenizedServer,
     controller: String,
 ) {
+    if let Err(e) = server.update_controller(database, controller.clone()).await {
+        log::error!(
+            "DATABASE UPDATE OF CONTROLLER {controller} FOR {collection}@{chain}@{token_id} FAILED: {e}",
+            collection = server.collection,
+            chain = server.chain,
+            token_id = server.token_id
+        );
+    }
+
+    update_controllers(database, server).await;
+}
+
+/// Pushes the main and additional controllers of the server to its Xnode.
+pub async fn update_controllers(database: &Database, server: &DatabaseTokenizedServer) {
     let xnode = match ControlledXnode::new(
         database.clone(),
         server.collection.clone(),
@@ -299,18 +269,31 @@ pub async fn update_controller(
         }
     };
 
-    if let Err(e) = xnode.set_controller(Some(controller.clone())).await {
+    let controllers = match server_controllers(database, server).await {
+        Ok(controllers) => controllers,
+        Err(e) => {
+            log::error!(
+                "COULD NOT FETCH CONTROLLERS OF {collection}@{chain}@{token_id}: {e}",
+                collection = server.collection,
+                chain = server.chain,
+                token_id = server.token_id
+            );
+            return;
+        }
+    };
+    if let Err(e) = xnode.set_controllers(&controllers).await {
         log::error!(
-            "XNODE MANAGER UPDATE OF CONTROLLER {controller} FOR {collection}@{chain}@{token_id} FAILED: {e:?}",
+            "XNODE MANAGER UPDATE OF CONTROLLERS {controllers:?} FOR {collection}@{chain}@{token_id} FAILED: {e:?}",
             collection = server.collection,
             chain = server.chain,
             token_id = server.token_id
         );
     }
 
-    if let Err(e) = server.update_controller(database, controller.clone()).await {
+    // Custom domains grant access to the controllers
+    if let Err(e) = apply_custom_domains(database, &xnode, server).await {
         log::error!(
-            "DATABASE UPDATE OF CONTROLLER {controller} FOR {collection}@{chain}@{token_id} FAILED: {e}",
+            "XNODE MANAGER UPDATE OF CUSTOM DOMAINS FOR {collection}@{chain}@{token_id} FAILED: {e:?}",
             collection = server.collection,
             chain = server.chain,
             token_id = server.token_id